@@ -0,0 +1,113 @@
+/// NOTE: this example is for external signer integration tests, the key is
+/// deterministic (or given by `MOCK_SIGNER_KEY` as 64 bytes hex) and is NOT
+/// safe for real funds.
+///
+/// Protocol: read one json request line from stdin, write one json response
+/// line to stdout, see `docs/External-Signer.md` for details.
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use bitcoin::bip32::DerivationPath;
+use ckb_signer::MasterPrivKey;
+use ckb_types::H256;
+use serde_json::{json, Value};
+
+fn main() {
+    let master_privkey = load_master_privkey();
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_err) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                write_response(json!({
+                    "id": Value::Null,
+                    "error": { "message": format!("invalid request: {}", err) },
+                }));
+                continue;
+            }
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let response = match handle(&master_privkey, &request) {
+            Ok(result) => json!({ "id": id, "result": result }),
+            Err(message) => json!({ "id": id, "error": { "message": message } }),
+        };
+        write_response(response);
+    }
+}
+
+fn load_master_privkey() -> MasterPrivKey {
+    let mut bytes = [0u8; 64];
+    if let Ok(key_hex) = std::env::var("MOCK_SIGNER_KEY") {
+        let key_hex = key_hex.trim_start_matches("0x");
+        faster_hex::hex_decode(key_hex.as_bytes(), &mut bytes).expect("invalid MOCK_SIGNER_KEY");
+    } else {
+        for (idx, byte) in bytes.iter_mut().enumerate() {
+            *byte = idx as u8 + 1;
+        }
+    }
+    MasterPrivKey::from_bytes(bytes).expect("invalid master private key")
+}
+
+fn handle(master_privkey: &MasterPrivKey, request: &Value) -> Result<Value, String> {
+    let method = request
+        .get("method")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| "missing method".to_string())?;
+    let params = request
+        .get("params")
+        .ok_or_else(|| "missing params".to_string())?;
+    let path = params
+        .get("path")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| "missing path".to_string())?;
+    let path = DerivationPath::from_str(path).map_err(|err| err.to_string())?;
+    match method {
+        "get_xpub" => {
+            let xpub = master_privkey.extended_pubkey(&path);
+            Ok(json!({ "xpub": xpub.to_string() }))
+        }
+        "sign" => {
+            let digest = params
+                .get("digest")
+                .and_then(|value| value.as_str())
+                .ok_or_else(|| "missing digest".to_string())?;
+            let digest =
+                H256::from_str(digest.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+            let recoverable = params
+                .get("recoverable")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(true);
+            let signature = if recoverable {
+                let signature = master_privkey.sign_recoverable(&digest, &path);
+                let (recov_id, data) = signature.serialize_compact();
+                let mut signature_bytes = data.to_vec();
+                signature_bytes.push(recov_id.to_i32() as u8);
+                signature_bytes
+            } else {
+                master_privkey
+                    .sign(&digest, &path)
+                    .serialize_compact()
+                    .to_vec()
+            };
+            Ok(json!({
+                "signature": format!("0x{}", faster_hex::hex_string(&signature)),
+            }))
+        }
+        _ => Err(format!("unknown method: {}", method)),
+    }
+}
+
+fn write_response(response: Value) {
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(format!("{}\n", response).as_bytes())
+        .unwrap();
+    stdout.flush().unwrap();
+}
@@ -17,6 +17,9 @@ pub enum Error {
     #[error("Key already exists {0:x}")]
     KeyExists(H160),
 
+    #[error("Account is watch-only, can not sign with it: {0:x}")]
+    WatchOnly(H160),
+
     #[error("Wrong password for {0:x}")]
     WrongPassword(H160),
 
@@ -4,7 +4,7 @@ pub(crate) mod signer;
 mod util;
 
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fmt;
 use std::fs;
@@ -38,6 +38,7 @@ pub struct KeyStore {
     storage: PassphraseKeyStore,
     files: HashMap<H160, PathBuf>,
     ckb_roots: HashMap<H160, CkbRoot>,
+    watch_only: HashSet<H160>,
     unlocked_keys: HashMap<H160, TimedKey>,
 }
 
@@ -48,6 +49,7 @@ impl Clone for KeyStore {
             storage: self.storage.clone(),
             files: self.files.clone(),
             ckb_roots: self.ckb_roots.clone(),
+            watch_only: self.watch_only.clone(),
             unlocked_keys: HashMap::default(),
         }
     }
@@ -64,6 +66,7 @@ impl KeyStore {
             },
             files: HashMap::default(),
             ckb_roots: HashMap::default(),
+            watch_only: HashSet::default(),
             unlocked_keys: HashMap::default(),
         };
         key_store.refresh_dir()?;
@@ -95,6 +98,12 @@ impl KeyStore {
         }
         self.files.contains_key(hash160)
     }
+    pub fn is_watch_only(&mut self, hash160: &H160, refresh: bool) -> bool {
+        if refresh {
+            self.refresh_dir().ok();
+        }
+        self.watch_only.contains(hash160)
+    }
 
     pub fn update(
         &mut self,
@@ -174,6 +183,26 @@ impl KeyStore {
             Ok(key.hash160().clone())
         }
     }
+    /// Import an account only contains the extended public key of CKB root
+    /// path (m/44'/309'/0'), the account can derive addresses but can not sign.
+    ///
+    /// The account id is the pubkey hash of the first receiving key
+    /// (m/44'/309'/0'/0/0), the same lock arg as the first address of the
+    /// original account.
+    pub fn import_watch_only(&mut self, extended_pubkey: &Xpub) -> Result<H160, Error> {
+        let ckb_root = CkbRoot::from_extended_pubkey(extended_pubkey)?;
+        let (_, hash160) = ckb_root.derived_hash160(KeyChain::External, 0);
+        self.refresh_dir()?;
+        if self.files.contains_key(&hash160) {
+            Err(Error::KeyExists(hash160))
+        } else {
+            let filepath = self.storage.store_watch_only(&hash160, &ckb_root)?;
+            self.files.insert(hash160.clone(), filepath);
+            self.ckb_roots.insert(hash160.clone(), ckb_root);
+            self.watch_only.insert(hash160.clone());
+            Ok(hash160)
+        }
+    }
     pub fn upgrade(&self, hash160: &H160, password: &[u8]) -> Result<(), Error> {
         let filepath = self.get_filepath(hash160)?;
         let backup_path = filepath.with_file_name(format!("{:#x}.upgrade-backup", hash160));
@@ -297,12 +326,13 @@ impl KeyStore {
     pub fn refresh_dir(&mut self) -> Result<(), Error> {
         let mut files = HashMap::default();
         let mut ckb_roots = HashMap::default();
+        let mut watch_only = HashSet::default();
         for entry in fs::read_dir(&self.keys_dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_file() {
                 let filename = path.file_name().and_then(OsStr::to_str).expect("file_name");
-                if let Some((hash160, ckb_root_opt)) = filename
+                if let Some((hash160, ckb_root_opt, is_watch_only)) = filename
                     .rsplit_once("--")
                     .map(|x| x.1)
                     .and_then(|hash160_hex| {
@@ -321,11 +351,18 @@ impl KeyStore {
                                 let ckb_root_opt = util::get_value(&value, "ckb_root")
                                     .ok()
                                     .and_then(|value| CkbRoot::from_json(value).ok());
-                                (hash160, ckb_root_opt)
+                                let is_watch_only = value
+                                    .get("watch_only")
+                                    .and_then(serde_json::Value::as_bool)
+                                    .unwrap_or(false);
+                                (hash160, ckb_root_opt, is_watch_only)
                             })
                     })
                 {
                     files.insert(hash160.clone(), path.to_path_buf());
+                    if is_watch_only {
+                        watch_only.insert(hash160.clone());
+                    }
                     if let Some(ckb_root) = ckb_root_opt {
                         ckb_roots.insert(hash160, ckb_root);
                     }
@@ -334,6 +371,7 @@ impl KeyStore {
         }
         self.files = files;
         self.ckb_roots = ckb_roots;
+        self.watch_only = watch_only;
         Ok(())
     }
 
@@ -397,8 +435,11 @@ impl PassphraseKeyStore {
     ) -> Result<Key, Error> {
         let filepath = self.join_path(filename);
         let mut file = fs::File::open(filepath)?;
-        let data = serde_json::from_reader(&mut file)
+        let data: serde_json::Value = serde_json::from_reader(&mut file)
             .map_err(|err| Error::ParseJsonFailed(err.to_string()))?;
+        if let Some(true) = data.get("watch_only").and_then(serde_json::Value::as_bool) {
+            return Err(Error::WatchOnly(hash160.clone()));
+        }
         let key = Key::from_json(&data, password)?;
         if key.hash160() != hash160 {
             return Err(Error::KeyMismatch {
@@ -416,8 +457,28 @@ impl PassphraseKeyStore {
         key: &Key,
         password: &[u8],
     ) -> Result<PathBuf, Error> {
-        let filepath = self.join_path(filename);
         let json_value = key.to_json(password, self.scrypt_type);
+        self.store_json(filename, &json_value)
+    }
+
+    // Writes the public part (ckb root) of a watch-only account.
+    fn store_watch_only(&self, hash160: &H160, ckb_root: &CkbRoot) -> Result<PathBuf, Error> {
+        let json_value = serde_json::json!({
+            "origin": KEYSTORE_ORIGIN,
+            "version": KEYSTORE_VERSION,
+            "hash160": format!("{:x}", hash160),
+            "watch_only": true,
+            "ckb_root": ckb_root.to_json(),
+        });
+        self.store_json(key_filename(hash160), &json_value)
+    }
+
+    fn store_json<P: AsRef<Path>>(
+        &self,
+        filename: P,
+        json_value: &serde_json::Value,
+    ) -> Result<PathBuf, Error> {
+        let filepath = self.join_path(filename);
 
         #[cfg(unix)]
         let mut file = {
@@ -435,7 +496,7 @@ impl PassphraseKeyStore {
         #[cfg(not(unix))]
         let mut file = fs::File::create(&filepath)?;
 
-        serde_json::to_writer(&mut file, &json_value).map_err(|err| Error::Io(err.to_string()))?;
+        serde_json::to_writer(&mut file, json_value).map_err(|err| Error::Io(err.to_string()))?;
         Ok(filepath)
     }
 
@@ -556,6 +617,34 @@ pub struct CkbRoot {
 }
 
 impl CkbRoot {
+    pub fn from_extended_pubkey(extended_pubkey: &Xpub) -> Result<CkbRoot, Error> {
+        if extended_pubkey.depth != 3
+            || extended_pubkey.child_number
+                != ChildNumber::from_hardened_idx(0).expect("child number")
+        {
+            return Err(Error::Other(format!(
+                "The extended public key must be derived from path {}",
+                CKB_ROOT_PATH
+            )));
+        }
+        let extended_pubkey = Xpub {
+            network: bitcoin::NetworkKind::Main,
+            parent_fingerprint: Default::default(),
+            ..*extended_pubkey
+        };
+        Ok(CkbRoot {
+            path: CKB_ROOT_PATH,
+            extended_pubkey,
+        })
+    }
+
+    /// The blake160 hash of the root public key (m/44'/309'/0')
+    pub fn hash160(&self) -> H160 {
+        let pubkey = self.extended_pubkey.public_key;
+        H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20])
+            .expect("Generate hash(H160) from pubkey failed")
+    }
+
     pub fn to_json(&self) -> serde_json::Value {
         assert_eq!(self.extended_pubkey.depth, 3, "depth not 3");
         assert_eq!(
@@ -731,20 +820,7 @@ impl Key {
     }
 
     pub fn filename(&self) -> String {
-        let utc_now = Utc::now();
-        let date = utc_now.date_naive();
-        let time = utc_now.time();
-        format!(
-            "UTC--{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:09}Z--{:x}",
-            date.year(),
-            date.month(),
-            date.day(),
-            time.hour(),
-            time.minute(),
-            time.second(),
-            time.nanosecond(),
-            self.hash160(),
-        )
+        key_filename(self.hash160())
     }
 
     pub fn from_json(data: &serde_json::Value, password: &[u8]) -> Result<Key, Error> {
@@ -799,6 +875,23 @@ impl Key {
     }
 }
 
+fn key_filename(hash160: &H160) -> String {
+    let utc_now = Utc::now();
+    let date = utc_now.date_naive();
+    let time = utc_now.time();
+    format!(
+        "UTC--{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:09}Z--{:x}",
+        date.year(),
+        date.month(),
+        date.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.nanosecond(),
+        hash160,
+    )
+}
+
 #[derive(Clone)]
 pub struct MasterPrivKey {
     secp_secret_key: secp256k1::SecretKey,
@@ -976,4 +1069,38 @@ mod tests {
         assert_eq!(key_set, key_set_by_index);
         assert_eq!(key_set, expected_key_set);
     }
+
+    #[test]
+    fn test_watch_only_ckb_root() {
+        let master_privkey = MasterPrivKey::try_new(1024).unwrap();
+        let path = DerivationPath::from_str(CKB_ROOT_PATH).unwrap();
+        let extended_pubkey = master_privkey.extended_pubkey(&path);
+        let ckb_root = CkbRoot::from_extended_pubkey(&extended_pubkey).unwrap();
+        let keys_dir =
+            std::env::temp_dir().join(format!("ckb-signer-watch-only-{}", std::process::id()));
+        fs::create_dir_all(&keys_dir).unwrap();
+        let mut key_store = KeyStore::from_dir(keys_dir.clone(), ScryptType::default()).unwrap();
+        let hash160 = key_store.import_watch_only(&extended_pubkey).unwrap();
+        assert_eq!(
+            hash160,
+            master_privkey
+                .ckb_root()
+                .derived_hash160(KeyChain::External, 0)
+                .1
+        );
+        assert!(key_store.is_watch_only(&hash160, true));
+        fs::remove_dir_all(&keys_dir).unwrap();
+        assert_eq!(
+            ckb_root.derived_key_set_by_index(0, 5, 0, 5),
+            master_privkey
+                .ckb_root()
+                .derived_key_set_by_index(0, 5, 0, 5)
+        );
+        let value = ckb_root.to_json();
+        let ckb_root = CkbRoot::from_json(&value).unwrap();
+        assert_eq!(ckb_root.to_json(), value);
+
+        let extended_pubkey = master_privkey.extended_pubkey(&DerivationPath::default());
+        assert!(CkbRoot::from_extended_pubkey(&extended_pubkey).is_err());
+    }
 }
@@ -2,6 +2,6 @@ mod keystore;
 
 pub use keystore::signer::FileSystemKeystoreSigner;
 pub use keystore::{
-    CipherParams, Crypto, DerivedKeySet, Error as KeyStoreError, KdfParams, Key, KeyChain,
+    CipherParams, CkbRoot, Crypto, DerivedKeySet, Error as KeyStoreError, KdfParams, Key, KeyChain,
     KeyStore, KeyTimeout, MasterPrivKey, ScryptParams, ScryptType, CKB_ROOT_PATH,
 };
@@ -43,10 +43,23 @@ else
     fi
 fi
 
+# Build the xUDT script and an extension script (shared library) of it
+cd ${CKB_CLI_DIR}/../ckb-cli-integration
+if [ ! -d "ckb-production-scripts" ]; then
+    git clone --depth 1 --recurse-submodules https://github.com/nervosnetwork/ckb-production-scripts.git
+fi
+cd ckb-production-scripts
+if [ ! -f build/xudt_rce ]; then
+    make all-via-docker
+fi
+XUDT_BUILD_DIR="$(pwd)/build"
+
 cd $CKB_CLI_DIR
 
-# Build keystore_no_password plugin
-cd plugin-protocol && cargo build --example keystore_no_password && cd ..
+# Build keystore_no_password and mock_unlocker plugins
+cd plugin-protocol && cargo build --example keystore_no_password --example mock_unlocker && cd ..
+# Build mock external signer
+cd ckb-signer && cargo build --example mock_signer && cd ..
 
 rm -rf test/target && ln -snf "${CKB_CLI_DIR}/target" test/target
 export RUST_LOG=ckb_cli=info,cli_test=info
@@ -54,4 +67,8 @@ export RUST_LOG=ckb_cli=info,cli_test=info
 cd test && cargo run -- \
                  --ckb-bin "${CKB_BIN}" \
                  --cli-bin "${CKB_CLI_DIR}/target/release/ckb-cli" \
-                 --keystore-plugin "${CKB_CLI_DIR}/target/debug/examples/keystore_no_password"
+                 --keystore-plugin "${CKB_CLI_DIR}/target/debug/examples/keystore_no_password" \
+                 --mock-signer "${CKB_CLI_DIR}/target/debug/examples/mock_signer" \
+                 --mock-unlocker "${CKB_CLI_DIR}/target/debug/examples/mock_unlocker" \
+                 --xudt-bin "${XUDT_BUILD_DIR}/xudt_rce" \
+                 --xudt-extension-bin "${XUDT_BUILD_DIR}/extension_script_0"
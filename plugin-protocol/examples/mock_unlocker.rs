@@ -0,0 +1,92 @@
+/// NOTE: this example is for unlocker plugin integration tests.
+///
+/// It unlocks the anyone-can-pay lock (code hash given by
+/// `MOCK_UNLOCKER_CODE_HASH` when the plugin is installed) without signature,
+/// the witness of the lock group is an empty `WitnessArgs`, so the lock script
+/// checks the payment (the output capacity of the same lock is increased).
+use ckb_cli_plugin_protocol::{
+    JsonrpcError, JsonrpcRequest, JsonrpcResponse, PluginConfig, PluginRequest, PluginResponse,
+    PluginRole, UnlockerRequest,
+};
+use ckb_jsonrpc_types::JsonBytes;
+use ckb_types::{packed::WitnessArgs, prelude::*, H256};
+use std::convert::TryInto;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+fn main() {
+    loop {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => {
+                break;
+            }
+            Ok(_n) => {
+                let jsonrpc_request: JsonrpcRequest = serde_json::from_str(&line).unwrap();
+                let (id, request) = jsonrpc_request.try_into().unwrap();
+                if let Some(response) = handle(request) {
+                    let jsonrpc_response = JsonrpcResponse::from((id, response));
+                    let response_string =
+                        format!("{}\n", serde_json::to_string(&jsonrpc_response).unwrap());
+                    io::stdout().write_all(response_string.as_bytes()).unwrap();
+                    io::stdout().flush().unwrap();
+                }
+            }
+            Err(_err) => {}
+        }
+    }
+}
+
+fn handle(request: PluginRequest) -> Option<PluginResponse> {
+    match request {
+        PluginRequest::Quit => None,
+        PluginRequest::GetConfig => {
+            let code_hash = std::env::var("MOCK_UNLOCKER_CODE_HASH")
+                .map_err(|err| err.to_string())
+                .and_then(|input| {
+                    H256::from_str(input.trim_start_matches("0x")).map_err(|err| err.to_string())
+                });
+            let code_hash = match code_hash {
+                Ok(code_hash) => code_hash,
+                Err(err) => {
+                    return Some(error_response(format!(
+                        "invalid env MOCK_UNLOCKER_CODE_HASH: {}",
+                        err
+                    )))
+                }
+            };
+            let config = PluginConfig {
+                name: String::from("mock_unlocker"),
+                description: String::from("It's an anyone-can-pay unlocker for test"),
+                daemon: false,
+                roles: vec![PluginRole::Unlocker { code_hash }],
+            };
+            Some(PluginResponse::PluginConfig(config))
+        }
+        PluginRequest::Unlocker(UnlockerRequest::Unlock {
+            tx, input_indices, ..
+        }) => {
+            let mut witnesses = tx.witnesses;
+            while witnesses.len() < tx.inputs.len() {
+                witnesses.push(JsonBytes::default());
+            }
+            let witness = JsonBytes::from_bytes(WitnessArgs::default().as_bytes());
+            for idx in input_indices {
+                match witnesses.get_mut(idx as usize) {
+                    Some(item) => *item = witness.clone(),
+                    None => return Some(error_response(format!("invalid input index: {}", idx))),
+                }
+            }
+            Some(PluginResponse::BytesVec(witnesses))
+        }
+        _ => Some(error_response(String::from("Invalid request to unlocker"))),
+    }
+}
+
+fn error_response(message: String) -> PluginResponse {
+    PluginResponse::Error(JsonrpcError {
+        code: 0,
+        message,
+        data: None,
+    })
+}
@@ -1,4 +1,4 @@
-use ckb_jsonrpc_types::{JsonBytes, Transaction};
+use ckb_jsonrpc_types::{JsonBytes, Script, Transaction};
 use faster_hex::{hex_decode, hex_string};
 use serde::de::DeserializeOwned;
 use std::convert::TryFrom;
@@ -6,7 +6,7 @@ use std::str::FromStr;
 
 use super::{
     method, CallbackRequest, IndexerRequest, JsonrpcRequest, JsonrpcResponse, KeyStoreRequest,
-    LiveCellIndexType, PluginRequest, PluginResponse, RpcRequest, JSONRPC_VERSION,
+    LiveCellIndexType, PluginRequest, PluginResponse, RpcRequest, UnlockerRequest, JSONRPC_VERSION,
 };
 
 impl From<(u64, PluginRequest)> for JsonrpcRequest {
@@ -28,6 +28,7 @@ impl From<(u64, PluginRequest)> for JsonrpcRequest {
             }
             PluginRequest::SubCommand(args) => (method::SUB_COMMAND, vec![serde_json::json!(args)]),
             PluginRequest::Callback(callback_request) => callback_request.into(),
+            PluginRequest::Unlocker(unlocker_request) => unlocker_request.into(),
             PluginRequest::Rpc(rpc_request) => rpc_request.into(),
             PluginRequest::Indexer {
                 genesis_hash,
@@ -68,6 +69,9 @@ impl TryFrom<JsonrpcRequest> for (u64, PluginRequest) {
             method if method.starts_with(method::CALLBACK_PREFIX) => {
                 CallbackRequest::try_from(&data).map(PluginRequest::Callback)?
             }
+            method if method.starts_with(method::UNLOCKER_PREFIX) => {
+                UnlockerRequest::try_from(&data).map(PluginRequest::Unlocker)?
+            }
             method if method.starts_with(method::RPC_PREFIX) => {
                 RpcRequest::try_from(&data).map(PluginRequest::Rpc)?
             }
@@ -119,6 +123,50 @@ impl TryFrom<&JsonrpcRequest> for CallbackRequest {
     }
 }
 
+impl From<UnlockerRequest> for (&'static str, Vec<serde_json::Value>) {
+    fn from(request: UnlockerRequest) -> (&'static str, Vec<serde_json::Value>) {
+        match request {
+            UnlockerRequest::Unlock {
+                tx,
+                lock_script,
+                input_indices,
+                sub_command,
+            } => {
+                let params = vec![
+                    serde_json::to_value(&tx).expect("Serialize json failed"),
+                    serde_json::to_value(&lock_script).expect("Serialize json failed"),
+                    serde_json::json!(input_indices),
+                    serde_json::json!(sub_command),
+                ];
+                (method::UNLOCKER_UNLOCK, params)
+            }
+        }
+    }
+}
+impl TryFrom<&JsonrpcRequest> for UnlockerRequest {
+    type Error = String;
+    fn try_from(data: &JsonrpcRequest) -> Result<UnlockerRequest, Self::Error> {
+        let request = match data.method.as_str() {
+            method::UNLOCKER_UNLOCK => {
+                let tx: Transaction = parse_param(data, 0, "transaction")?;
+                let lock_script: Script = parse_param(data, 1, "lock_script")?;
+                let input_indices: Vec<u32> = parse_param(data, 2, "input_indices")?;
+                let sub_command: String = parse_param(data, 3, "sub-command")?;
+                UnlockerRequest::Unlock {
+                    tx,
+                    lock_script,
+                    input_indices,
+                    sub_command,
+                }
+            }
+            _ => {
+                return Err(format!("Invalid request method: {}", data.method));
+            }
+        };
+        Ok(request)
+    }
+}
+
 impl From<KeyStoreRequest> for (&'static str, Vec<serde_json::Value>) {
     fn from(request: KeyStoreRequest) -> (&'static str, Vec<serde_json::Value>) {
         match request {
@@ -127,6 +175,10 @@ impl From<KeyStoreRequest> for (&'static str, Vec<serde_json::Value>) {
                 method::KEYSTORE_HAS_ACCOUNT,
                 vec![serde_json::json!(hash160)],
             ),
+            KeyStoreRequest::IsWatchOnly(hash160) => (
+                method::KEYSTORE_IS_WATCH_ONLY,
+                vec![serde_json::json!(hash160)],
+            ),
             KeyStoreRequest::CreateAccount(password) => (
                 method::KEYSTORE_CREATE_ACCOUNT,
                 vec![serde_json::json!(password)],
@@ -245,6 +297,9 @@ impl TryFrom<&JsonrpcRequest> for KeyStoreRequest {
             method::KEYSTORE_HAS_ACCOUNT => {
                 KeyStoreRequest::HasAccount(parse_param(data, 0, "hash160")?)
             }
+            method::KEYSTORE_IS_WATCH_ONLY => {
+                KeyStoreRequest::IsWatchOnly(parse_param(data, 0, "hash160")?)
+            }
             method::KEYSTORE_CREATE_ACCOUNT => {
                 KeyStoreRequest::CreateAccount(parse_param(data, 0, "password")?)
             }
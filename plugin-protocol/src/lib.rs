@@ -60,6 +60,8 @@ pub enum PluginRole {
     SubCommand { name: String },
     // The argument is for the callback function name
     Callback { name: CallbackName },
+    // The argument is for the lock script code hash the plugin can unlock
+    Unlocker { code_hash: H256 },
 }
 
 impl PluginRole {
@@ -85,6 +87,7 @@ pub enum PluginRequest {
     // The plugin need to parse the rest command line arguments
     SubCommand(String),
     Callback(CallbackRequest),
+    Unlocker(UnlockerRequest),
     // == Send from plugin to ckb-cli
     Rpc(RpcRequest),
     ReadPassword(String),
@@ -165,6 +168,20 @@ pub enum CallbackResponse {
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum UnlockerRequest {
+    // Fill the witnesses of the inputs locked by `lock_script`
+    // return: PluginResponse::BytesVec (all the witnesses of the transaction)
+    Unlock {
+        tx: Transaction,
+        lock_script: Script,
+        // The indices of the inputs locked by `lock_script`
+        input_indices: Vec<u32>,
+        // Unlock in which subcommand: sign-inputs/send
+        sub_command: String,
+    },
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "snake_case", content = "content")]
 pub enum SignTarget {
@@ -184,6 +201,8 @@ pub enum KeyStoreRequest {
     ListAccount,
     // return: PluginResponse::Boolean
     HasAccount(H160),
+    // return: PluginResponse::Boolean
+    IsWatchOnly(H160),
     // return: PluginResponse::H160
     CreateAccount(Option<String>),
     // return: PluginResponse::Ok
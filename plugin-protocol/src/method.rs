@@ -9,6 +9,9 @@ pub const SUB_COMMAND: &str = "sub_command";
 pub const CALLBACK_PREFIX: &str = "callback_";
 pub const CALLBACK_SEND_TRANSACTION: &str = "callback_send_transaction";
 
+pub const UNLOCKER_PREFIX: &str = "unlocker_";
+pub const UNLOCKER_UNLOCK: &str = "unlocker_unlock";
+
 pub const RPC_PREFIX: &str = "rpc_";
 pub const RPC_GET_BLOCK: &str = "rpc_get_block";
 pub const RPC_GET_BLOCK_BY_NUMBER: &str = "rpc_get_block_by_number";
@@ -26,6 +29,7 @@ pub const INDEXER_ANY: &str = "indexer_any";
 pub const KEYSTORE_PREFIX: &str = "keystore_";
 pub const KEYSTORE_LIST_ACCOUNT: &str = "keystore_list_account";
 pub const KEYSTORE_HAS_ACCOUNT: &str = "keystore_has_account";
+pub const KEYSTORE_IS_WATCH_ONLY: &str = "keystore_is_watch_only";
 pub const KEYSTORE_CREATE_ACCOUNT: &str = "keystore_create_account";
 pub const KEYSTORE_UPDATE_PASSWORD: &str = "keystore_update_password";
 pub const KEYSTORE_IMPORT: &str = "keystore_import";
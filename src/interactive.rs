@@ -20,17 +20,16 @@ use crate::subcommands::{
 };
 use crate::utils::{
     completer::CkbCompleter,
-    config::GlobalConfig,
+    config::{GlobalConfig, ENV_PATTERN},
     genesis_info::GenesisInfo,
     other::{check_alerts, get_genesis_info, get_network_type},
     printer::{ColorWhen, OutputFormat, Printable},
     rpc::{HttpRpcClient, RawHttpRpcClient},
 };
 
-const ENV_PATTERN: &str = r"\$\{\s*(?P<key>\S+)\s*\}";
-
 /// Interactive command line
 pub struct InteractiveEnv {
+    ckb_cli_dir: PathBuf,
     config: GlobalConfig,
     config_file: PathBuf,
     history_file: PathBuf,
@@ -57,7 +56,7 @@ impl InteractiveEnv {
         let mut config_file = ckb_cli_dir.clone();
         config_file.push("config");
 
-        let mut env_file = ckb_cli_dir;
+        let mut env_file = ckb_cli_dir.clone();
         env_file.push("env_vars");
         if env_file.as_path().exists() {
             let file = fs::File::open(&env_file).map_err(|err| err.to_string())?;
@@ -72,6 +71,7 @@ impl InteractiveEnv {
         let rpc_client = HttpRpcClient::new(config.get_url().to_string());
         let raw_rpc_client = RawHttpRpcClient::new(config.get_url());
         Ok(InteractiveEnv {
+            ckb_cli_dir,
             config,
             config_file,
             history_file,
@@ -380,6 +380,7 @@ impl InteractiveEnv {
                         &mut self.rpc_client,
                         &mut self.plugin_mgr,
                         genesis_info,
+                        &self.ckb_cli_dir,
                     )
                     .process(sub_matches, debug)?;
                     output.print(format, color);
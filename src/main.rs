@@ -159,7 +159,7 @@ fn main() -> Result<(), io::Error> {
         }
         ("sudt", Some(sub_matches)) => {
             get_genesis_info(&None, &mut rpc_client).and_then(|genesis_info| {
-                SudtSubCommand::new(&mut rpc_client, &mut plugin_mgr, genesis_info)
+                SudtSubCommand::new(&mut rpc_client, &mut plugin_mgr, genesis_info, &ckb_cli_dir)
                     .process(sub_matches, debug)
             })
         }
@@ -53,6 +53,9 @@ impl DefaultKeyStore {
                 KeyStoreRequest::HasAccount(hash160) => Ok(PluginResponse::Boolean(
                     keystore.has_account(&hash160, true),
                 )),
+                KeyStoreRequest::IsWatchOnly(hash160) => Ok(PluginResponse::Boolean(
+                    keystore.is_watch_only(&hash160, true),
+                )),
                 KeyStoreRequest::UpdatePassword {
                     hash160,
                     password,
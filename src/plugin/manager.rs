@@ -22,7 +22,7 @@ use crate::utils::rpc::HttpRpcClient;
 use plugin_protocol::{
     CallbackName, CallbackRequest, CallbackResponse, IndexerRequest, JsonrpcError, JsonrpcRequest,
     JsonrpcResponse, KeyStoreRequest, LiveCellIndexType, LiveCellInfo, PluginConfig, PluginRequest,
-    PluginResponse, PluginRole, RpcRequest, SignTarget,
+    PluginResponse, PluginRole, RpcRequest, SignTarget, UnlockerRequest,
 };
 
 pub const PLUGINS_DIRNAME: &str = "plugins";
@@ -47,6 +47,8 @@ pub struct PluginManager {
     sub_commands: HashMap<String, String>,
     // The actived callback plugins. The key is callback name
     callbacks: HashMap<CallbackName, Vec<String>>,
+    // The actived unlocker plugins. The key is lock script code hash
+    unlockers: HashMap<H256, String>,
 
     default_keystore_handler: PluginHandler,
     service_provider: ServiceProvider,
@@ -110,6 +112,7 @@ impl PluginManager {
         let mut indexers = Vec::new();
         let mut sub_commands = HashMap::new();
         let mut callbacks: HashMap<CallbackName, Vec<String>> = HashMap::new();
+        let mut unlockers: HashMap<H256, String> = HashMap::new();
         let mut keystore_plugin = None;
         let mut indexer_plugin = None;
         // TODO plugins order matters
@@ -137,6 +140,11 @@ impl PluginManager {
                             .or_default()
                             .push(plugin_name.clone());
                     }
+                    PluginRole::Unlocker { code_hash } => {
+                        if plugin.is_active() {
+                            unlockers.insert(code_hash.clone(), plugin_name.clone());
+                        }
+                    }
                 }
             }
             if config.is_normal_daemon() {
@@ -168,6 +176,7 @@ impl PluginManager {
             keystores,
             sub_commands,
             callbacks,
+            unlockers,
             service_provider,
             default_keystore_handler,
             _jsonrpc_id: jsonrpc_id,
@@ -184,6 +193,9 @@ impl PluginManager {
     pub fn callbacks(&self) -> &HashMap<CallbackName, Vec<String>> {
         &self.callbacks
     }
+    pub fn unlockers(&self) -> &HashMap<H256, String> {
+        &self.unlockers
+    }
     pub fn actived_keystore(&self) -> Option<(&Plugin, &PluginConfig, bool)> {
         self.keystores
             .iter()
@@ -277,6 +289,10 @@ impl PluginManager {
                             .or_default()
                             .push(config.name.clone());
                     }
+                    PluginRole::Unlocker { code_hash } => {
+                        self.unlockers
+                            .insert(code_hash.clone(), config.name.clone());
+                    }
                 }
             }
             if config.is_normal_daemon() {
@@ -352,6 +368,11 @@ impl PluginManager {
                                 .collect::<Vec<_>>();
                         }
                     }
+                    PluginRole::Unlocker { code_hash } => {
+                        if self.unlockers.get(code_hash).map(String::as_str) == Some(name) {
+                            self.unlockers.remove(code_hash);
+                        }
+                    }
                 }
             }
             if config.is_normal_daemon() {
@@ -520,6 +541,36 @@ impl PluginManager {
             ))
         }
     }
+
+    /// Fill the witnesses of the inputs locked by `lock_script` by the
+    /// unlocker plugin of the lock script code hash, return all the witnesses
+    /// of the transaction.
+    pub fn unlock(
+        &self,
+        code_hash: &H256,
+        arguments: UnlockerRequest,
+    ) -> Result<Vec<JsonBytes>, String> {
+        if let Some(plugin_name) = self.unlockers.get(code_hash) {
+            self.handle(plugin_name.as_str(), |handler| {
+                let request = PluginRequest::Unlocker(arguments);
+                let id: u64 = 0;
+                match Request::call(handler, (id, request))
+                    .ok_or_else(|| format!("Send request to plugin {} failed", plugin_name))?
+                {
+                    (_id, PluginResponse::BytesVec(witnesses)) => Ok(witnesses),
+                    (_id, PluginResponse::Error(rpc_err)) => {
+                        Err(format!("ERROR: {}", rpc_err.message))
+                    }
+                    _ => Err(format!("Invalid response from plugin: {}", plugin_name)),
+                }
+            })
+        } else {
+            Err(format!(
+                "unlocker plugin for lock script code_hash {:#x} not found or inactive",
+                code_hash
+            ))
+        }
+    }
 }
 
 fn deserilize_key_set(set: Vec<(String, H160)>) -> Result<Vec<(DerivationPath, H160)>, String> {
@@ -1178,6 +1229,10 @@ impl KeyStoreHandler {
             KeyStoreRequest::HasAccount(_) => {
                 // Both (or) handle default part out side
             }
+            KeyStoreRequest::IsWatchOnly(ref hash160) => {
+                // Both
+                hash160_opt = Some(hash160.clone());
+            }
             KeyStoreRequest::CreateAccount(_) => {
                 // Both (neet target), currently default only
                 default_only = true;
@@ -1254,13 +1309,26 @@ impl KeyStoreHandler {
     }
 
     pub fn root_key_path(&self, h160: H160) -> Result<DerivationPath, String> {
-        if self.has_account_in_default(h160)? {
-            Ok(DerivationPath::default())
+        if self.has_account_in_default(h160.clone())? {
+            if self.is_watch_only(h160)? {
+                // A watch-only account is identified by its first receiving key
+                let path = format!("{}/0/0", CKB_ROOT_PATH);
+                Ok(DerivationPath::from_str(&path).expect("parse watch-only account path"))
+            } else {
+                Ok(DerivationPath::default())
+            }
         } else {
             Ok(DerivationPath::from_str(CKB_ROOT_PATH).expect("parse ckb root path"))
         }
     }
 
+    pub fn is_watch_only(&self, hash160: H160) -> Result<bool, String> {
+        match self.call(KeyStoreRequest::IsWatchOnly(hash160))? {
+            PluginResponse::Boolean(watch_only) => Ok(watch_only),
+            _ => Err("Mismatch keystore response".to_string()),
+        }
+    }
+
     pub fn has_account(&self, hash160: H160) -> Result<bool, String> {
         let request = ServiceRequest::Request {
             is_from_plugin: false,
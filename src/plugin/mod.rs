@@ -2,4 +2,4 @@ mod builtin;
 mod manager;
 
 pub use manager::{KeyStoreHandler, PluginManager};
-pub use plugin_protocol::SignTarget;
+pub use plugin_protocol::{SignTarget, UnlockerRequest};
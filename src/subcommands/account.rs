@@ -4,7 +4,7 @@ use std::io::Write;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 
-use bitcoin::bip32::DerivationPath;
+use bitcoin::bip32::{DerivationPath, Xpub};
 
 use ckb_sdk::{Address, AddressPayload, NetworkType};
 use ckb_signer::{Key, KeyStore, MasterPrivKey};
@@ -73,6 +73,7 @@ impl<'a> AccountSubCommand<'a> {
     * lock_arg: The blake2b160 hash of the public key.
     * lock_hash: The lock script hash of secp256k1_blake160_sighash_all lock (See [1]).
     * has_ckb_pubkey_derivation_root_path: The CKB public key derivation root path (m/44'/309'/0') is stored so that password is not required to do public key derivation.
+    * watch_only: The account is imported by `ckb-cli account import-watch-only`, it only contains the extended public key and can not sign.
     * address: The Mainnet/Testnet addresses of secp256k1_blake160_sighash_all lock (See [1]).
 
   When `source` is \"[plugin]: xxx_keysotre_plugin\" means the account is stored in keystore plugin (Ledger plugin like [2]). If the account metadata is imported by `ckb-cli account import-from-plugin` the output fields are just like \"Local File System\". If the account is not imported, the output fields are:
@@ -122,6 +123,16 @@ impl<'a> AccountSubCommand<'a> {
                             .validator(|input| FilePathParser::new(true).validate(input))
                             .about("The keystore file path (json format)")
                     ),
+                App::new("import-watch-only")
+                    .about("Import a BIP-32 extended public key of path m/44'/309'/0' as a watch-only account (can not sign), the account lock arg is the one of path m/44'/309'/0'/0/0")
+                    .arg(
+                        Arg::with_name("xpub")
+                            .long("xpub")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FromStrParser::<Xpub>::new().validate(input))
+                            .about("The extended public key in Base58Check format (can be exported by `ckb-cli account bitcoin-xpub --path \"m/44'/309'/0'\"`)")
+                    ),
                 App::new("update")
                     .about("Update password of an account")
                     .arg(lock_arg().required(true)),
@@ -233,12 +244,14 @@ impl<'a> CliSubCommand for AccountSubCommand<'a> {
                                 }
                             } else {
                                 let has_ckb_root = self.key_store.get_ckb_root(&lock_arg, false).is_some();
+                                let watch_only = self.key_store.is_watch_only(&lock_arg, false);
                                 serde_json::json!({
                                     "#": idx,
                                     "source": source,
                                     "lock_arg": format!("{:#x}", lock_arg),
                                     "lock_hash": format!("{:#x}", lock_hash),
                                     "has_ckb_pubkey_derivation_root_path": has_ckb_root,
+                                    "watch_only": watch_only,
                                     "address": address_json(address_payload.clone(), true),
                                     "address(deprecated)": address_json(address_payload, false),
                                 })
@@ -341,6 +354,21 @@ impl<'a> CliSubCommand for AccountSubCommand<'a> {
                 });
                 Ok(Output::new_output(resp))
             }
+            ("import-watch-only", Some(m)) => {
+                let extended_pubkey: Xpub = FromStrParser::<Xpub>::new().from_matches(m, "xpub")?;
+                let lock_arg = self
+                    .key_store
+                    .import_watch_only(&extended_pubkey)
+                    .map_err(|err| err.to_string())?;
+                let address_payload = AddressPayload::from_pubkey_hash(lock_arg.clone());
+                let resp = serde_json::json!({
+                    "lock_arg": format!("{:#x}", lock_arg),
+                    "address": address_json(address_payload.clone(), true),
+                    "address(deprecated)": address_json(address_payload, false),
+                    "watch_only": true,
+                });
+                Ok(Output::new_output(resp))
+            }
             ("update", Some(m)) => {
                 let lock_arg: H160 =
                     FixedHashParser::<H160>::default().from_matches(m, "lock-arg")?;
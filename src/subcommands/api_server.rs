@@ -403,6 +403,12 @@ impl HttpTransferArgs {
             to_data: self.to_data,
             is_type_id: false,
             skip_check_to_address: false,
+            build_only: false,
+            coin_selection: None,
+            use_cells: Vec::new(),
+            exclude_cells: Vec::new(),
+            lock_until: None,
+            signer_cmd: None,
         }
     }
 }
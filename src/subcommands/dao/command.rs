@@ -3,8 +3,8 @@ use crate::subcommands::{CliSubCommand, DAOSubCommand, Output};
 use crate::utils::{
     arg,
     arg_parser::{
-        AddressParser, ArgParser, CapacityParser, FixedHashParser, FromStrParser, OutPointParser,
-        PrivkeyPathParser, PrivkeyWrapper,
+        AddressParser, ArgParser, CapacityParser, FilePathParser, FixedHashParser, FromStrParser,
+        OutPointParser, PrivkeyPathParser, PrivkeyWrapper,
     },
     other::{get_address, get_network_type},
 };
@@ -13,6 +13,7 @@ use ckb_sdk::{Address, AddressPayload, HumanCapacity, NetworkType};
 use ckb_types::{packed::Script, H160};
 use clap::{App, Arg, ArgMatches};
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 impl<'a> CliSubCommand for DAOSubCommand<'a> {
     fn process(&mut self, matches: &ArgMatches, debug: bool) -> Result<Output, String> {
@@ -22,6 +23,9 @@ impl<'a> CliSubCommand for DAOSubCommand<'a> {
                 let args = TransactArgs::from_matches(m, network_type)?;
                 let capacity: u64 = CapacityParser.from_matches(m, "capacity")?;
                 let transaction = self.deposit(&args, capacity)?;
+                if args.dry_run {
+                    return self.dry_run(transaction, 1, network_type);
+                }
                 send_transaction(self.rpc_client, transaction, debug)
             }
             ("prepare", Some(m)) => {
@@ -30,7 +34,11 @@ impl<'a> CliSubCommand for DAOSubCommand<'a> {
                 if out_points.len() != out_points.iter().collect::<HashSet<_>>().len() {
                     return Err("Duplicated out-points".to_string());
                 }
+                let base_outputs = out_points.len();
                 let transaction = self.prepare(&args, out_points)?;
+                if args.dry_run {
+                    return self.dry_run(transaction, base_outputs, network_type);
+                }
                 send_transaction(self.rpc_client, transaction, debug)
             }
             ("withdraw", Some(m)) => {
@@ -40,6 +48,9 @@ impl<'a> CliSubCommand for DAOSubCommand<'a> {
                     return Err("Duplicated out-points".to_string());
                 }
                 let transaction = self.withdraw(&args, out_points)?;
+                if args.dry_run {
+                    return self.dry_run(transaction, 1, network_type);
+                }
                 send_transaction(self.rpc_client, transaction, debug)
             }
             ("query-deposited-cells", Some(m)) => {
@@ -107,9 +118,11 @@ impl<'a> DAOSubCommand<'a> {
 
 pub struct TransactArgs {
     pub(crate) privkey: Option<PrivkeyWrapper>,
+    pub(crate) signer_cmd: Option<PathBuf>,
     pub(crate) address: Address,
     pub(crate) fee_rate: u64,
     pub(crate) force_small_change_as_fee: Option<u64>,
+    pub(crate) dry_run: bool,
 }
 
 impl TransactArgs {
@@ -142,15 +155,19 @@ impl TransactArgs {
             let payload = AddressPayload::from_pubkey_hash(account);
             Address::new(network_type, payload, false)
         };
+        let signer_cmd: Option<PathBuf> =
+            FilePathParser::new(true).from_matches_opt(m, "signer-cmd")?;
         let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
 
         let force_small_change_as_fee =
             FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
         Ok(Self {
             privkey,
+            signer_cmd,
             address,
             fee_rate,
             force_small_change_as_fee,
+            dry_run: m.is_present("dry-run"),
         })
     }
 
@@ -158,8 +175,10 @@ impl TransactArgs {
         vec![
             arg::privkey_path().required_unless(arg::from_account().get_name()),
             arg::from_account().required_unless(arg::privkey_path().get_name()),
+            arg::signer_cmd().conflicts_with(arg::privkey_path().get_name()),
             arg::fee_rate(),
             arg::max_tx_fee(),
+            arg::dry_run(),
         ]
     }
 }
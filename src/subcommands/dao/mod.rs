@@ -20,6 +20,7 @@ use ckb_sdk::{
     },
     types::ScriptId,
     unlock::{ScriptUnlocker, SecpSighashScriptSigner, SecpSighashUnlocker},
+    NetworkType,
 };
 use ckb_types::{
     bytes::Bytes,
@@ -33,11 +34,13 @@ use plugin_protocol::LiveCellInfo;
 use self::command::TransactArgs;
 use crate::{
     plugin::PluginManager,
+    subcommands::Output,
     utils::{
+        dry_run::{dry_run_report, provider_cell_getter},
         genesis_info::GenesisInfo,
         other::{map_tx_builder_error_2_str, read_password, to_live_cell_info},
         rpc::HttpRpcClient,
-        signer::KeyStoreHandlerSigner,
+        signer::{ExternalSigner, KeyStoreHandlerSigner, PrivkeySigner},
     },
 };
 
@@ -92,8 +95,13 @@ impl<'a> DAOSubCommand<'a> {
             force_small_change_as_fee: args.force_small_change_as_fee,
         };
 
-        let signer: Box<dyn Signer> = if let Some(privkey) = args.privkey.as_ref() {
+        let signer: Box<dyn Signer> = if args.dry_run {
+            // Only the placeholder witnesses are required
+            Box::new(PrivkeySigner::new(Vec::new()))
+        } else if let Some(privkey) = args.privkey.as_ref() {
             Box::new(privkey.clone())
+        } else if let Some(cmd) = args.signer_cmd.as_ref() {
+            Box::new(ExternalSigner::new(cmd.clone())?)
         } else {
             let account =
                 H160::from_slice(lock_script.args().raw_data().as_ref()).expect("lock args");
@@ -118,6 +126,20 @@ impl<'a> DAOSubCommand<'a> {
         let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
         unlockers.insert(script_id, Box::new(sighash_unlocker));
 
+        if args.dry_run {
+            return builder
+                .build_balanced(
+                    &mut self.cell_collector,
+                    &self.cell_dep_resolver,
+                    &self.header_dep_resolver,
+                    &self.tx_dep_provider,
+                    &balancer,
+                    &unlockers,
+                )
+                .map_err(|err| {
+                    map_tx_builder_error_2_str(balancer.force_small_change_as_fee.is_none(), err)
+                });
+        }
         let (tx, still_locked_groups) = builder
             .build_unlocked(
                 &mut self.cell_collector,
@@ -185,6 +207,25 @@ impl<'a> DAOSubCommand<'a> {
         self.build_tx(&tx_builder, args)
     }
 
+    /// Report the unsigned transaction, the outputs after `base_outputs` is the change
+    fn dry_run(
+        &mut self,
+        tx: TransactionView,
+        base_outputs: usize,
+        network: NetworkType,
+    ) -> Result<Output, String> {
+        let change_index = Some(base_outputs).filter(|index| *index < tx.outputs().len());
+        let report = dry_run_report(
+            self.rpc_client,
+            network,
+            &tx,
+            change_index,
+            None,
+            provider_cell_getter(&self.tx_dep_provider),
+        )?;
+        Ok(Output::new_output(report))
+    }
+
     fn query_dao_cells(
         &mut self,
         lock: Script,
@@ -47,6 +47,7 @@ impl IntermediumInfo {
                 transaction: cell_tx.clone(),
                 multisig_configs: self.multisig_configs()?,
                 signatures: self.cell_tx_signatures.clone(),
+                ..Default::default()
             };
             let helper = TxHelper::try_from(repr).map_err(Error::msg)?;
             Ok(Some(helper))
@@ -61,6 +62,7 @@ impl IntermediumInfo {
                 transaction: dep_group_tx.clone(),
                 multisig_configs: self.multisig_configs()?,
                 signatures: self.dep_group_tx_signatures.clone(),
+                ..Default::default()
             };
             let helper = TxHelper::try_from(repr).map_err(Error::msg)?;
             Ok(Some(helper))
@@ -25,6 +25,7 @@ use crate::utils::{
         AddressParser, ArgParser, DirPathParser, FilePathParser, FixedHashParser, FromStrParser,
         PrivkeyPathParser, PrivkeyWrapper,
     },
+    dry_run::dry_run_report,
     genesis_info::GenesisInfo,
     other::{get_live_cell_with_cache, get_network_type, read_password},
     rpc::HttpRpcClient,
@@ -112,7 +113,8 @@ impl<'a> DeploySubCommand<'a> {
                         Arg::with_name("sign-now")
                             .long("sign-now")
                             .about("Sign the cell/dep_group transaction add signatures to info-file now"),
-                    ),
+                    )
+                    .arg(arg::dry_run().conflicts_with("sign-now")),
                 App::new("sign-txs")
                     .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
                     .arg(arg::from_account().required_unless(arg::privkey_path().get_name()))
@@ -278,6 +280,56 @@ impl<'a> CliSubCommand for DeploySubCommand<'a> {
                 };
                 explain_txs(&info).map_err(|err| err.to_string())?;
 
+                if m.is_present("dry-run") {
+                    // The change output is after the new cell/dep_group outputs
+                    let cell_outputs = cell_changes
+                        .iter()
+                        .filter(|change| change.has_new_output())
+                        .count();
+                    let dep_group_outputs = dep_group_changes
+                        .iter()
+                        .filter(|change| change.has_new_output())
+                        .count();
+                    let used_input_txs = &info.used_input_txs;
+                    let get_input_cell = |out_point: &packed::OutPoint| {
+                        let tx_hash: H256 = out_point.tx_hash().unpack();
+                        let index: u32 = out_point.index().unpack();
+                        used_input_txs
+                            .get(&tx_hash)
+                            .and_then(|tx| tx.outputs.get(index as usize))
+                            .cloned()
+                            .map(packed::CellOutput::from)
+                            .ok_or_else(|| {
+                                format!("Input cell not found: {:#x}-{}", tx_hash, index)
+                            })
+                    };
+                    let mut reports = serde_json::Map::new();
+                    for (name, tx_opt, base_outputs) in [
+                        ("cell_tx", info.cell_tx.as_ref(), cell_outputs),
+                        (
+                            "dep_group_tx",
+                            info.dep_group_tx.as_ref(),
+                            dep_group_outputs,
+                        ),
+                    ] {
+                        if let Some(tx) = tx_opt {
+                            let tx = packed::Transaction::from(tx.clone()).into_view();
+                            let change_index =
+                                Some(base_outputs).filter(|index| *index < tx.outputs().len());
+                            let report = dry_run_report(
+                                self.rpc_client,
+                                network,
+                                &tx,
+                                change_index,
+                                Some(json_types::OutputsValidator::Passthrough),
+                                get_input_cell,
+                            )?;
+                            reports.insert(name.to_string(), report);
+                        }
+                    }
+                    return Ok(Output::new_output(serde_json::Value::Object(reports)));
+                }
+
                 // Sign if required
                 if m.is_present("sign-now") {
                     let account = H160::from_slice(from_address.payload().args().as_ref()).unwrap();
@@ -12,7 +12,8 @@ use ckb_sdk::constants::SIGHASH_TYPE_HASH;
 use ckb_types::{
     bytes::Bytes,
     core::{
-        capacity_bytes, Capacity, HeaderBuilder, HeaderView, ScriptHashType, TransactionBuilder,
+        capacity_bytes, Capacity, Cycle, HeaderBuilder, HeaderView, ScriptHashType,
+        TransactionBuilder, TransactionView,
     },
     h256,
     packed::{self, Byte32, CellDep, CellInput, CellOutput, OutPoint, Script},
@@ -27,7 +28,7 @@ use crate::utils::{
     arg::lock_arg,
     arg_parser::{ArgParser, FilePathParser, FixedHashParser},
     genesis_info::GenesisInfo,
-    mock_tx_helper::MockTransactionHelper,
+    mock_tx_helper::{MockTransactionHelper, ScriptGroupCycles},
     other::{get_genesis_info, get_signer},
     rpc::HttpRpcClient,
     tx_helper::TxHelper,
@@ -294,85 +295,7 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
                 } else {
                     return Err(String::from("<tx-hash> or <tx-file> is required"));
                 };
-                let mock_inputs = src_tx
-                    .inputs
-                    .iter()
-                    .map(|input| {
-                        let (output, data, block_hash) =
-                            load_output_and_data(self.rpc_client, input.previous_output.clone())?;
-                        Ok(ReprMockInput {
-                            input: input.clone(),
-                            output,
-                            data,
-                            header: Some(block_hash),
-                        })
-                    })
-                    .collect::<Result<Vec<_>, String>>()?;
-                let mock_cell_deps = src_tx
-                    .cell_deps
-                    .iter()
-                    .flat_map(|cell_dep| {
-                        let (output, data, block_hash) =
-                            match load_output_and_data(self.rpc_client, cell_dep.out_point.clone())
-                            {
-                                Ok((output, data, block_hash)) => (output, data, block_hash),
-                                Err(err) => return vec![Err(err)],
-                            };
-                        let mut cell_deps = if cell_dep.dep_type == json_types::DepType::DepGroup {
-                            let out_points = match packed::OutPointVec::from_slice(data.as_bytes())
-                            {
-                                Ok(out_points) => out_points,
-                                Err(err) => return vec![Err(err.to_string())],
-                            };
-                            out_points
-                                .into_iter()
-                                .map(json_types::OutPoint::from)
-                                .map(|out_point| {
-                                    let (output, data, block_hash) =
-                                        load_output_and_data(self.rpc_client, out_point.clone())?;
-                                    Ok(ReprMockCellDep {
-                                        cell_dep: json_types::CellDep {
-                                            out_point,
-                                            dep_type: json_types::DepType::Code,
-                                        },
-                                        output,
-                                        data,
-                                        header: Some(block_hash),
-                                    })
-                                })
-                                .collect::<Vec<_>>()
-                        } else {
-                            Vec::new()
-                        };
-                        cell_deps.push(Ok(ReprMockCellDep {
-                            cell_dep: cell_dep.clone(),
-                            output,
-                            data,
-                            header: Some(block_hash),
-                        }));
-                        cell_deps
-                    })
-                    .collect::<Result<Vec<_>, String>>()?;
-                let mock_header_deps = src_tx
-                    .header_deps
-                    .iter()
-                    .map(|block_hash| {
-                        self.rpc_client
-                            .get_header(block_hash.clone())?
-                            .map(HeaderView::from)
-                            .map(json_types::HeaderView::from)
-                            .ok_or_else(|| format!("header not exists: {:x}", block_hash))
-                    })
-                    .collect::<Result<Vec<_>, String>>()?;
-                let repr_tx = ReprMockTransaction {
-                    mock_info: ReprMockInfo {
-                        inputs: mock_inputs,
-                        cell_deps: mock_cell_deps,
-                        header_deps: mock_header_deps,
-                        extensions: vec![],
-                    },
-                    tx: src_tx,
-                };
+                let repr_tx = dump_mock_tx(self.rpc_client, src_tx, false)?;
                 let content =
                     serde_json::to_string_pretty(&repr_tx).map_err(|err| err.to_string())?;
                 let mut out_file = fs::File::create(output_path).map_err(|err| err.to_string())?;
@@ -406,6 +329,115 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
     }
 }
 
+/// Dump all on-chain data (inputs/cell_deps/header_deps) of the transaction into mock_info,
+/// the headers of input cells are also dumped into header_deps if `with_input_headers` is true.
+pub(crate) fn dump_mock_tx(
+    rpc_client: &mut HttpRpcClient,
+    src_tx: json_types::Transaction,
+    with_input_headers: bool,
+) -> Result<ReprMockTransaction, String> {
+    let mock_inputs = src_tx
+        .inputs
+        .iter()
+        .map(|input| {
+            let (output, data, block_hash) =
+                load_output_and_data(rpc_client, input.previous_output.clone())?;
+            Ok(ReprMockInput {
+                input: input.clone(),
+                output,
+                data,
+                header: Some(block_hash),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let mock_cell_deps = src_tx
+        .cell_deps
+        .iter()
+        .flat_map(|cell_dep| {
+            let (output, data, block_hash) =
+                match load_output_and_data(rpc_client, cell_dep.out_point.clone()) {
+                    Ok((output, data, block_hash)) => (output, data, block_hash),
+                    Err(err) => return vec![Err(err)],
+                };
+            let mut cell_deps = if cell_dep.dep_type == json_types::DepType::DepGroup {
+                let out_points = match packed::OutPointVec::from_slice(data.as_bytes()) {
+                    Ok(out_points) => out_points,
+                    Err(err) => return vec![Err(err.to_string())],
+                };
+                out_points
+                    .into_iter()
+                    .map(json_types::OutPoint::from)
+                    .map(|out_point| {
+                        let (output, data, block_hash) =
+                            load_output_and_data(rpc_client, out_point.clone())?;
+                        Ok(ReprMockCellDep {
+                            cell_dep: json_types::CellDep {
+                                out_point,
+                                dep_type: json_types::DepType::Code,
+                            },
+                            output,
+                            data,
+                            header: Some(block_hash),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            };
+            cell_deps.push(Ok(ReprMockCellDep {
+                cell_dep: cell_dep.clone(),
+                output,
+                data,
+                header: Some(block_hash),
+            }));
+            cell_deps
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let mock_header_deps = src_tx
+        .header_deps
+        .iter()
+        .map(|block_hash| {
+            rpc_client
+                .get_header(block_hash.clone())?
+                .map(HeaderView::from)
+                .map(json_types::HeaderView::from)
+                .ok_or_else(|| format!("header not exists: {:x}", block_hash))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut repr_tx = ReprMockTransaction {
+        mock_info: ReprMockInfo {
+            inputs: mock_inputs,
+            cell_deps: mock_cell_deps,
+            header_deps: mock_header_deps,
+            extensions: vec![],
+        },
+        tx: src_tx,
+    };
+    if with_input_headers {
+        for input in &repr_tx.mock_info.inputs {
+            let block_hash = match input.header.as_ref() {
+                Some(block_hash) => block_hash,
+                None => continue,
+            };
+            if repr_tx
+                .mock_info
+                .header_deps
+                .iter()
+                .any(|header| &header.hash == block_hash)
+            {
+                continue;
+            }
+            let header = rpc_client
+                .get_header(block_hash.clone())?
+                .map(HeaderView::from)
+                .map(json_types::HeaderView::from)
+                .ok_or_else(|| format!("header not exists: {:x}", block_hash))?;
+            repr_tx.mock_info.header_deps.push(header);
+        }
+    }
+    Ok(repr_tx)
+}
+
 fn load_output_and_data(
     rpc_client: &mut HttpRpcClient,
     out_point: json_types::OutPoint,
@@ -441,8 +473,28 @@ fn load_output_and_data(
     Ok((output, data, block_hash))
 }
 
-struct Loader<'a> {
-    rpc_client: &'a mut HttpRpcClient,
+/// Load the cells and headers missing from the mock info by rpc
+/// Verify each script group of the transaction locally, the input cells, cell
+/// deps and headers are loaded by rpc.
+pub(crate) fn verify_script_groups(
+    rpc_client: &mut HttpRpcClient,
+    tx: &TransactionView,
+    max_cycles: Cycle,
+) -> Result<Vec<ScriptGroupCycles>, String> {
+    let mut mock_tx = MockTransaction {
+        mock_info: MockInfo {
+            inputs: vec![],
+            cell_deps: vec![],
+            header_deps: vec![],
+            extensions: vec![],
+        },
+        tx: tx.data(),
+    };
+    MockTransactionHelper::new(&mut mock_tx).verify_groups(max_cycles, Loader { rpc_client })
+}
+
+pub(crate) struct Loader<'a> {
+    pub(crate) rpc_client: &'a mut HttpRpcClient,
 }
 
 impl<'a> MockResourceLoader for Loader<'a> {
@@ -1,24 +1,29 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use clap::{App, Arg, ArgMatches};
 
-use ckb_jsonrpc_types as json_types;
+use ckb_jsonrpc_types::{self as json_types, Status};
 use ckb_sdk::{
     constants::SIGHASH_TYPE_HASH,
+    rpc::ckb_indexer::{CellType, Order, ScriptType, SearchKey, SearchKeyFilter, SearchMode},
     traits::{
         default_impls::{
             DefaultCellCollector, DefaultCellDepResolver, DefaultHeaderDepResolver,
             DefaultTransactionDependencyProvider,
         },
-        CellCollector, CellQueryOptions, Signer,
+        CellCollector, CellDepResolver, CellQueryOptions, HeaderDepResolver, Signer,
+        TransactionDependencyProvider,
     },
     tx_builder::{
         cheque::{ChequeClaimBuilder, ChequeWithdrawBuilder},
         transfer::CapacityTransferBuilder,
         udt::{UdtIssueBuilder, UdtTargetReceiver, UdtTransferBuilder, UdtType},
-        CapacityBalancer, CapacityProvider, TransferAction, TxBuilder,
+        CapacityBalancer, CapacityProvider, TransferAction, TxBuilder, TxBuilderError,
     },
-    types::ScriptId,
+    types::{xudt_rce_mol::ScriptVec, ScriptId},
     unlock::{
         AcpScriptSigner, AcpUnlocker, ChequeAction, ChequeScriptSigner, ChequeUnlocker,
         ScriptUnlocker, SecpSighashScriptSigner, SecpSighashUnlocker,
@@ -28,7 +33,7 @@ use ckb_sdk::{
 use ckb_types::{
     bytes::Bytes,
     core::{Capacity, FeeRate, ScriptHashType, TransactionView},
-    packed::{CellInput, CellOutput, Script, WitnessArgs},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script, Transaction, WitnessArgs},
     prelude::*,
     H160, H256,
 };
@@ -37,16 +42,21 @@ use crate::{
     plugin::PluginManager,
     subcommands::{CliSubCommand, Output},
     utils::{
+        airdrop::{parse_airdrop_rows, AirdropJournal, AirdropTransaction},
         arg,
         arg_parser::{
-            AddressParser, ArgParser, CellDepsParser, FromStrParser, PrivkeyPathParser,
-            PrivkeyWrapper, UdtTargetParser,
+            AddressParser, ArgParser, CellDepsParser, FilePathParser, FromStrParser,
+            PrivkeyPathParser, PrivkeyWrapper, ScriptParser, UdtAmountParser, UdtTargetParser,
+            UDT_MAX_DECIMALS,
         },
         cell_dep::{CellDepName, CellDeps},
+        dry_run::{dry_run_report, provider_cell_getter},
         genesis_info::GenesisInfo,
         other::{get_network_type, map_tx_builder_error_2_str, read_password},
-        rpc::HttpRpcClient,
-        signer::{CommonSigner, KeyStoreHandlerSigner, PrivkeySigner},
+        rpc::{Cell, HttpRpcClient, Tx},
+        signer::{CommonSigner, ExternalSigner, KeyStoreHandlerSigner, PrivkeySigner},
+        token_registry::{format_udt_amount, TokenInfo, TokenRegistry},
+        tx_chunks::{TxChunker, TxLimits},
     },
 };
 
@@ -57,10 +67,23 @@ pub struct SudtSubCommand<'a> {
     cell_dep_resolver: DefaultCellDepResolver,
     header_dep_resolver: DefaultHeaderDepResolver,
     tx_dep_provider: DefaultTransactionDependencyProvider,
+    token_registry_path: PathBuf,
 }
 
+// xUDT flags (RFC 0052): the extension scripts are stored in the args, and
+// the owner mode bits
+const XUDT_FLAGS_EXTENSIONS_IN_ARGS: u32 = 0x1;
+const XUDT_OWNER_MODE_INPUT_TYPE: u32 = 0x8000_0000;
+const XUDT_OWNER_MODE_OUTPUT_TYPE: u32 = 0x4000_0000;
+const XUDT_OWNER_MODE_NO_INPUT_LOCK: u32 = 0x2000_0000;
+
+const BALANCES_SEARCH_LIMIT: u32 = 500;
+const HOLDERS_SEARCH_LIMIT: u32 = 500;
+
 struct SudtCommonArgs {
+    udt_args: UdtArgs,
     privkeys: Vec<PrivkeyWrapper>,
+    external_signer: Option<ExternalSigner>,
     cell_deps: CellDeps,
     fee_rate: u64,
     force_small_change_as_fee: Option<u64>,
@@ -72,6 +95,7 @@ impl<'a> SudtSubCommand<'a> {
         rpc_client: &'a mut HttpRpcClient,
         plugin_mgr: &'a mut PluginManager,
         genesis_info: GenesisInfo,
+        ckb_cli_dir: &Path,
     ) -> Self {
         let tx_dep_provider = DefaultTransactionDependencyProvider::new(rpc_client.url(), 10);
         let cell_collector = DefaultCellCollector::new(rpc_client.url());
@@ -84,6 +108,7 @@ impl<'a> SudtSubCommand<'a> {
             cell_dep_resolver,
             header_dep_resolver,
             tx_dep_provider,
+            token_registry_path: TokenRegistry::path(ckb_cli_dir),
         }
     }
 
@@ -93,7 +118,11 @@ impl<'a> SudtSubCommand<'a> {
             .takes_value(true)
             .multiple(true)
             .required(true)
-            .validator(|input| UdtTargetParser::new(AddressParser::default()).validate(input));
+            // The decimals of the token is unknown here, the amount is parsed
+            // with the decimals of the token later
+            .validator(|input| {
+                UdtTargetParser::new(AddressParser::default()).validate_format(input)
+            });
         let arg_to_cheque_address = Arg::with_name("to-cheque-address").long("to-cheque-address");
         let arg_receiver = Arg::with_name("receiver")
             .long("receiver")
@@ -102,16 +131,20 @@ impl<'a> SudtSubCommand<'a> {
             .validator(|input| AddressParser::new_sighash().validate(input));
 
         App::new(name)
-            .about("SUDT issue/transfer operations (currently only support sudt)")
+            .about("SUDT/xUDT issue/transfer operations (the udt type is selected by <udt-type>)")
             .subcommands(vec![
                 App::new("issue")
                     .about("Issue SUDT to multiple addresses")
-                    .arg(arg_owner())
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
                     .arg(
                         arg_udt_to.clone()
-                            .about("The issue target, format: {address}:{amount}, the address type can be: [acp, sighash]")
+                            .about("The issue target, format: {address}:{amount}, the address type can be: [acp, sighash], the amount is in base units unless <token> is given")
                     )
-                    .arg(arg_cell_deps())
+                    .arg(arg_cell_deps().required_unless("token"))
                     .arg(arg_to_acp_address())
                     .arg(
                         arg_to_cheque_address
@@ -119,17 +152,22 @@ impl<'a> SudtSubCommand<'a> {
                             .about("Treat all addresses in <udt-to> as cheque receiver (sighash address, and the cheque sender is the <owner>), otherwise the address will be used as the lock script of the SUDT cell")
                     )
                     .arg(arg::privkey_path().multiple(true))
+                    .arg(arg::signer_cmd())
                     .arg(arg::fee_rate())
                     .arg(arg::max_tx_fee()),
                 App::new("transfer")
                     .about("Transfer SUDT to multiple addresses (all target addresses must have same lock script id)")
-                    .arg(arg_owner())
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
                     .arg(arg_sender().about("SUDT sender address, the address type can be: [acp, sighash], when address type is `acp` this address will be used to build a sighash lock script for build cheque address or provide capacity, if <capacity-provider> is not given <sender> will also use as capacity provider."))
                     .arg(
                         arg_udt_to
-                         .about("The transfer target, format: {address}:{amount}, the address type can be: [acp, sighash]")
+                         .about("The transfer target, format: {address}:{amount}, the address type can be: [acp, sighash], the amount is in base units unless <token> is given")
                     )
-                    .arg(arg_cell_deps())
+                    .arg(arg_cell_deps().required_unless("token"))
                     .arg(arg_to_acp_address())
                     .arg(
                         arg_to_cheque_address
@@ -138,12 +176,18 @@ impl<'a> SudtSubCommand<'a> {
                     )
                     .arg(arg_capacity_provider())
                     .arg(arg::privkey_path().multiple(true))
+                    .arg(arg::signer_cmd())
                     .arg(arg::fee_rate())
-                    .arg(arg::max_tx_fee()),
+                    .arg(arg::max_tx_fee())
+                    .arg(arg::dry_run()),
                 App::new("get-amount")
                     .about("Get SUDT total amount of an address")
-                    .arg(arg_owner())
-                    .arg(arg_cell_deps())
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
+                    .arg(arg_cell_deps().required_unless("token"))
                     .arg(
                         Arg::with_name("address")
                             .long("address")
@@ -152,9 +196,50 @@ impl<'a> SudtSubCommand<'a> {
                             .validator(|input| AddressParser::default().validate(input))
                             .about("The target address of those SUDT cells"),
                     ),
+                App::new("balances")
+                    .about("List all the SUDT/xUDT balances of an address, the anyone-can-pay cells and the cheque cells (as receiver or sender) of the address are included")
+                    .arg(
+                        Arg::with_name("address")
+                            .long("address")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| AddressParser::default().validate(input))
+                            .about("The address to list balances"),
+                    )
+                    .arg(
+                        arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information (for sudt/xudt/acp/cheque script id), the script ids of the registered tokens are also used")
+                    ),
+                App::new("holders")
+                    .about("Export the holders (aggregated by lock script) of a SUDT/xUDT at a block, the total amount is reconciled against the supply computed from the transaction history")
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
+                    .arg(arg_cell_deps().required_unless("token"))
+                    .arg(
+                        Arg::with_name("at-block")
+                            .long("at-block")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .about("The snapshot block number (default: the indexer tip)"),
+                    )
+                    .arg(
+                        Arg::with_name("export")
+                            .long("export")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Export the holders to a file (format: csv if the file extension is .csv, otherwise json)"),
+                    ),
                 App::new("new-empty-acp")
                     .about("Create a SUDT cell with 0 amount and an acp lock script")
-                    .arg(arg_owner())
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
                     .arg(arg_capacity_provider())
                     .arg(
                         Arg::with_name("to")
@@ -164,13 +249,18 @@ impl<'a> SudtSubCommand<'a> {
                             .validator(|input| AddressParser::new_sighash().validate(input))
                             .about("The target address (sighash), used to create anyone-can-pay address, if <capacity-provider> is not given <to> will also use as capacity provider"),
                     )
-                    .arg(arg_cell_deps())
+                    .arg(arg_cell_deps().required_unless("token"))
                     .arg(arg::privkey_path().multiple(true))
+                    .arg(arg::signer_cmd())
                     .arg(arg::fee_rate())
                     .arg(arg::max_tx_fee()),
                 App::new("cheque-claim")
                     .about("Claim all cheque cells identified by given lock script and type script")
-                    .arg(arg_owner())
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
                     .arg(arg_sender().about("The cheque sender address (sighash)"))
                     .arg(
                         arg_receiver
@@ -178,21 +268,104 @@ impl<'a> SudtSubCommand<'a> {
                             .about("The cheque receiver address (sighash), for searching an input to save the claimed amount, this address will be used to build anyone-can-pay address, if <capacity-provider> not given <receiver> will also be used as capacity provider")
                     )
                     .arg(arg_capacity_provider())
-                    .arg(arg_cell_deps())
+                    .arg(arg_cell_deps().required_unless("token"))
                     .arg(arg::privkey_path().multiple(true))
+                    .arg(arg::signer_cmd())
                     .arg(arg::fee_rate())
                     .arg(arg::max_tx_fee()),
                 App::new("cheque-withdraw")
                     .about("Withdraw all cheque cells identified by given lock script and type script")
-                    .arg(arg_owner())
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
                     .arg(arg_sender().about("The cheque sender address (sighash), if <capacity-provider> not given <sender> will use as capacity provider"))
                     .arg(arg_receiver.clone().about("The cheque receiver address (sighash)"))
                     .arg(arg_capacity_provider())
                     .arg(arg_to_acp_address().about("Withdraw to anyone-can-pay address, will use <sender> to build the anyone-can-pay address, the cell must be already exists"))
-                    .arg(arg_cell_deps())
+                    .arg(arg_cell_deps().required_unless("token"))
+                    .arg(arg::privkey_path().multiple(true))
+                    .arg(arg::signer_cmd())
+                    .arg(arg::fee_rate())
+                    .arg(arg::max_tx_fee()),
+                App::new("airdrop")
+                    .about("Airdrop SUDT/xUDT to the recipients of a csv file, the recipients are split into multiple transactions by the size and cycles limit. The progress is saved in a journal file, run the same command again to resume an interrupted airdrop")
+                    .arg(arg_owner().required_unless("token"))
+                    .arg(arg_token())
+                    .arg(arg_udt_type())
+                    .arg(arg_xudt_owner_mode())
+                    .arg(arg_xudt_extension())
+                    .arg(arg_cell_deps().required_unless("token"))
+                    .arg(
+                        Arg::with_name("file")
+                            .long("file")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .about("The recipients csv file (columns: address,amount[,kind]), the kind can be: [sighash, acp, cheque] (default: acp for an anyone-can-pay address, otherwise sighash), the cheque sender is the <sender> or <owner>, the amount is in base units unless <token> is given"),
+                    )
+                    .arg(
+                        Arg::with_name("journal")
+                            .long("journal")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("The journal file to save the progress (default: {file}.journal.json)"),
+                    )
+                    .arg(
+                        arg_sender()
+                            .required(false)
+                            .validator(|input| AddressParser::new_sighash().validate(input))
+                            .about("Airdrop by transferring the udt of the sender address (sighash), otherwise the udt is issued by <owner>")
+                    )
+                    .arg(
+                        Arg::with_name("max-recipients")
+                            .long("max-recipients")
+                            .takes_value(true)
+                            .default_value("100")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("Max recipients in one transaction"),
+                    )
                     .arg(arg::privkey_path().multiple(true))
+                    .arg(arg::signer_cmd())
                     .arg(arg::fee_rate())
                     .arg(arg::max_tx_fee()),
+                App::new("token")
+                    .about("Manage the local token registry (symbol, decimals, owner and cell deps of udt tokens)")
+                    .subcommands(vec![
+                        App::new("add")
+                            .about("Register a token")
+                            .arg(
+                                Arg::with_name("symbol")
+                                    .long("symbol")
+                                    .takes_value(true)
+                                    .required(true)
+                                    .about("The symbol of the token (unique in the registry)"),
+                            )
+                            .arg(
+                                Arg::with_name("decimals")
+                                    .long("decimals")
+                                    .takes_value(true)
+                                    .required(true)
+                                    .validator(|input| FromStrParser::<u32>::default().validate(input))
+                                    .about("The decimals of the token amount"),
+                            )
+                            .arg(arg_owner().validator(|input| AddressParser::default().validate(input)).about("The owner address of the token"))
+                            .arg(arg_cell_deps())
+                            .arg(arg_udt_type())
+                            .arg(arg_xudt_owner_mode())
+                            .arg(arg_xudt_extension()),
+                        App::new("list").about("List the registered tokens"),
+                        App::new("remove")
+                            .about("Remove a registered token")
+                            .arg(
+                                Arg::with_name("symbol")
+                                    .long("symbol")
+                                    .takes_value(true)
+                                    .required(true)
+                                    .about("The symbol of the token"),
+                            ),
+                    ]),
                 // TODO: move this subcommand to `util`
                 App::new("build-acp-address")
                     .about("Build an anyone-can-pay address by sighash address and anyone-can-pay script id.")
@@ -213,6 +386,37 @@ impl<'a> SudtSubCommand<'a> {
             ])
     }
 
+    /// The udt from the registered token (`--token`) or from `--owner`,
+    /// `--cell-deps` and the udt type arguments
+    fn udt_from_matches(
+        &self,
+        m: &ArgMatches,
+        network: NetworkType,
+        mut owner_parser: AddressParser,
+    ) -> Result<UdtInfo, String> {
+        owner_parser.set_network(network);
+        if let Some(symbol) = m.value_of("token") {
+            let registry = TokenRegistry::load(&self.token_registry_path)?;
+            let token = registry
+                .get(symbol)
+                .cloned()
+                .ok_or_else(|| format!("Token not found in registry: {}", symbol))?;
+            Ok(UdtInfo {
+                owner: owner_parser.parse(&token.owner)?,
+                cell_deps: token.cell_deps.clone(),
+                udt_args: UdtArgs::from_token(&token)?,
+                token: Some(token),
+            })
+        } else {
+            Ok(UdtInfo {
+                owner: owner_parser.from_matches(m, "owner")?,
+                cell_deps: CellDepsParser.from_matches(m, "cell-deps")?,
+                udt_args: UdtArgs::from_matches(m)?,
+                token: None,
+            })
+        }
+    }
+
     fn issue(
         &mut self,
         args: IssueArgs,
@@ -226,14 +430,16 @@ impl<'a> SudtSubCommand<'a> {
             to_acp_address,
         } = args;
         let SudtCommonArgs {
+            udt_args,
             privkeys,
+            external_signer,
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
             debug,
         } = common_args;
-        let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
-        let udt_type = UdtType::Sudt;
+        let udt_script_id = udt_args.script_id(&cell_deps)?;
+        let udt_type = udt_args.udt_type.clone();
         let acp_script_id = if to_acp_address {
             Some(get_script_id(&cell_deps, CellDepName::Acp)?)
         } else {
@@ -301,10 +507,12 @@ impl<'a> SudtSubCommand<'a> {
             header_dep_resolver: &self.header_dep_resolver,
             tx_dep_provider: &self.tx_dep_provider,
             builder: &builder,
+            extension_scripts: &udt_args.extension_scripts,
         };
         let tx = udt_builder.build(
             vec![("owner".to_string(), owner_account)],
             privkeys,
+            external_signer,
             &cell_deps,
             owner_script,
             acp_script_id,
@@ -350,16 +558,19 @@ impl<'a> SudtSubCommand<'a> {
             to_cheque_address,
             to_acp_address,
             capacity_provider,
+            dry_run,
         } = args;
         let SudtCommonArgs {
+            udt_args,
             privkeys,
+            external_signer,
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
             debug,
         } = common_args;
-        let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
-        let udt_type = UdtType::Sudt;
+        let udt_script_id = udt_args.script_id(&cell_deps)?;
+        let udt_type = udt_args.udt_type.clone();
         let acp_script_id = get_script_id(&cell_deps, CellDepName::Acp)?;
         let cheque_script_id = if to_cheque_address {
             Some(get_script_id(&cell_deps, CellDepName::Cheque)?)
@@ -444,10 +655,31 @@ impl<'a> SudtSubCommand<'a> {
             header_dep_resolver: &self.header_dep_resolver,
             tx_dep_provider: &self.tx_dep_provider,
             builder: &builder,
+            extension_scripts: &udt_args.extension_scripts,
         };
+        if dry_run {
+            let (tx, change_index) = udt_builder.build_unsigned(
+                &cell_deps,
+                capacity_provider,
+                Some(acp_script_id),
+                None,
+                fee_rate,
+                force_small_change_as_fee,
+            )?;
+            let report = dry_run_report(
+                self.rpc_client,
+                network,
+                &tx,
+                change_index,
+                Some(json_types::OutputsValidator::Passthrough),
+                provider_cell_getter(&self.tx_dep_provider),
+            )?;
+            return Ok(Output::new_output(report));
+        }
         let tx = udt_builder.build(
             accounts,
             privkeys,
+            external_signer,
             &cell_deps,
             capacity_provider,
             Some(acp_script_id),
@@ -485,11 +717,10 @@ impl<'a> SudtSubCommand<'a> {
         owner: Address,
         address: Address,
         cell_deps: CellDeps,
+        udt_args: UdtArgs,
+        token: Option<TokenInfo>,
     ) -> Result<Output, String> {
-        let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
-        let udt_type = UdtType::Sudt;
-        let owner_script_hash = Script::from(&owner).calc_script_hash();
-        let type_script = udt_type.build_script(&udt_script_id, &owner_script_hash);
+        let type_script = udt_args.build_type_script(&cell_deps, &owner)?;
 
         let mut query = CellQueryOptions::new_lock(Script::from(&address));
         query.secondary_script = Some(type_script);
@@ -517,12 +748,794 @@ impl<'a> SudtSubCommand<'a> {
                 "amount": amount.to_string(),
             }));
         }
-        let resp = serde_json::json!({
+        let mut resp = serde_json::json!({
             "cell_count": infos.len(),
             "cells": infos,
             // u128 is too large for json
             "total_amount": total_amount.to_string(),
         });
+        if let Some(token) = token {
+            resp["total_amount_display"] = serde_json::json!(format!(
+                "{} {}",
+                format_udt_amount(total_amount, token.decimals)?,
+                token.symbol
+            ));
+        }
+        Ok(Output::new_output(resp))
+    }
+
+    fn balances(
+        &mut self,
+        address: Address,
+        cell_deps: Option<CellDeps>,
+        network: NetworkType,
+    ) -> Result<Output, String> {
+        let registry = TokenRegistry::load(&self.token_registry_path)?;
+        let all_cell_deps: Vec<&CellDeps> = cell_deps
+            .iter()
+            .chain(registry.tokens.iter().map(|token| &token.cell_deps))
+            .collect();
+        let script_ids = |name: CellDepName| {
+            all_cell_deps
+                .iter()
+                .filter_map(|cell_deps| get_script_id(cell_deps, name).ok())
+                .collect::<HashSet<ScriptId>>()
+        };
+        let sudt_script_ids = script_ids(CellDepName::Sudt);
+        let xudt_script_ids = script_ids(CellDepName::Xudt);
+        if sudt_script_ids.is_empty() && xudt_script_ids.is_empty() {
+            return Err(
+                "No sudt/xudt script id found, <cell-deps> is required when there is no registered token"
+                    .to_string(),
+            );
+        }
+        let mut known_tokens: HashMap<H256, &TokenInfo> = HashMap::default();
+        for token in &registry.tokens {
+            let owner = Address::from_str(&token.owner).map_err(|err| err.to_string())?;
+            let type_script =
+                UdtArgs::from_token(token)?.build_type_script(&token.cell_deps, &owner)?;
+            known_tokens.insert(type_script.calc_script_hash().unpack(), token);
+        }
+
+        let lock_script = Script::from(&address);
+        let lock_hash = lock_script.calc_script_hash();
+        // (kind, lock script, search mode)
+        let mut searches = vec![(BalanceKind::Address, lock_script.clone(), None)];
+        let payload = address.payload();
+        if payload.code_hash(Some(network)) == SIGHASH_TYPE_HASH.pack()
+            && payload.hash_type() == ScriptHashType::Type
+        {
+            // The acp lock args may have the minimal amounts after the sighash args
+            for acp_script_id in script_ids(CellDepName::Acp) {
+                let acp_script = Script::new_builder()
+                    .code_hash(acp_script_id.code_hash.pack())
+                    .hash_type(acp_script_id.hash_type.into())
+                    .args(lock_script.args())
+                    .build();
+                searches.push((BalanceKind::Acp, acp_script, Some(SearchMode::Prefix)));
+            }
+        }
+        // The cheque lock args is receiver lock hash[0..20] + sender lock hash[0..20],
+        // the receiver cells are searched by the args prefix, the sender cells
+        // are searched by partial args then checked by the args suffix.
+        let lock_hash_prefix = Bytes::from(lock_hash.as_slice()[0..20].to_vec());
+        for cheque_script_id in script_ids(CellDepName::Cheque) {
+            let cheque_script = Script::new_builder()
+                .code_hash(cheque_script_id.code_hash.pack())
+                .hash_type(cheque_script_id.hash_type.into())
+                .args(lock_hash_prefix.pack())
+                .build();
+            searches.push((
+                BalanceKind::ChequeReceiver,
+                cheque_script.clone(),
+                Some(SearchMode::Prefix),
+            ));
+            searches.push((
+                BalanceKind::ChequeSender,
+                cheque_script,
+                Some(SearchMode::Partial),
+            ));
+        }
+
+        // type script hash => balance
+        let mut balances: BTreeMap<H256, UdtBalance> = BTreeMap::default();
+        for (kind, script, search_mode) in searches {
+            for cell in self.search_typed_cells(script, search_mode)? {
+                if kind.is_cheque() {
+                    let args = cell.output.lock.args.as_bytes();
+                    if args.len() != 40 {
+                        continue;
+                    }
+                    let is_receiver = args[0..20] == lock_hash_prefix[..];
+                    let is_sender = args[20..40] == lock_hash_prefix[..];
+                    // A cheque to the address itself is counted as the receiver's
+                    if (kind == BalanceKind::ChequeReceiver && !is_receiver)
+                        || (kind == BalanceKind::ChequeSender && (is_receiver || !is_sender))
+                    {
+                        continue;
+                    }
+                }
+                let type_script: Script = match cell.output.type_ {
+                    Some(type_script) => type_script.into(),
+                    None => continue,
+                };
+                let script_id = ScriptId::from(&type_script);
+                let udt_type = if sudt_script_ids.contains(&script_id) {
+                    "sudt"
+                } else if xudt_script_ids.contains(&script_id) {
+                    "xudt"
+                } else {
+                    continue;
+                };
+                let data = cell
+                    .output_data
+                    .map(|data| data.into_bytes())
+                    .unwrap_or_default();
+                // Not a valid udt cell
+                if data.len() < 16 {
+                    continue;
+                }
+                let mut amount_bytes = [0u8; 16];
+                amount_bytes.copy_from_slice(&data[0..16]);
+                let amount = u128::from_le_bytes(amount_bytes);
+                let balance = balances
+                    .entry(type_script.calc_script_hash().unpack())
+                    .or_insert_with(|| UdtBalance {
+                        type_script: type_script.clone(),
+                        udt_type,
+                        cell_count: 0,
+                        amounts: BTreeMap::default(),
+                    });
+                balance.cell_count += 1;
+                let kind_amount = balance.amounts.entry(kind).or_default();
+                *kind_amount = kind_amount.checked_add(amount).ok_or_else(|| {
+                    format!(
+                        "The {} amount of udt {:#x} overflows u128",
+                        kind.as_str(),
+                        type_script.calc_script_hash()
+                    )
+                })?;
+            }
+        }
+
+        let items = balances
+            .into_iter()
+            .map(|(type_script_hash, balance)| {
+                let total_amount = balance
+                    .amounts
+                    .values()
+                    .try_fold(0u128, |total, amount| total.checked_add(*amount))
+                    .ok_or_else(|| {
+                        format!(
+                            "The total amount of udt {:#x} overflows u128",
+                            type_script_hash
+                        )
+                    })?;
+                let amounts: BTreeMap<&str, String> = balance
+                    .amounts
+                    .iter()
+                    .map(|(kind, amount)| (kind.as_str(), amount.to_string()))
+                    .collect();
+                let mut item = serde_json::json!({
+                    "type_script_hash": type_script_hash,
+                    "type_script": json_types::Script::from(balance.type_script),
+                    "udt_type": balance.udt_type,
+                    "cell_count": balance.cell_count,
+                    // u128 is too large for json
+                    "total_amount": total_amount.to_string(),
+                    "amounts": amounts,
+                });
+                if let Some(token) = known_tokens.get(&type_script_hash) {
+                    item["symbol"] = serde_json::json!(token.symbol);
+                    item["total_amount_display"] = serde_json::json!(format!(
+                        "{} {}",
+                        format_udt_amount(total_amount, token.decimals)?,
+                        token.symbol
+                    ));
+                }
+                Ok(item)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let resp = serde_json::json!({
+            "address": address.to_string(),
+            "balances": items,
+        });
+        Ok(Output::new_output(resp))
+    }
+
+    /// Search all the live cells (with a type script) of the lock script by
+    /// the indexer
+    fn search_typed_cells(
+        &mut self,
+        lock_script: Script,
+        search_mode: Option<SearchMode>,
+    ) -> Result<Vec<Cell>, String> {
+        let search_key = SearchKey {
+            script: lock_script.into(),
+            script_type: ScriptType::Lock,
+            script_search_mode: search_mode,
+            filter: Some(SearchKeyFilter {
+                script_len_range: Some([1.into(), u64::max_value().into()]),
+                ..Default::default()
+            }),
+            with_data: Some(true),
+            group_by_transaction: None,
+        };
+        let mut cells = Vec::new();
+        let mut after = None;
+        loop {
+            let cells_page = self.rpc_client.get_cells(
+                search_key.clone(),
+                Order::Asc,
+                BALANCES_SEARCH_LIMIT.into(),
+                after,
+            )?;
+            let page_size = cells_page.objects.len();
+            cells.extend(cells_page.objects);
+            if page_size < BALANCES_SEARCH_LIMIT as usize {
+                break;
+            }
+            after = Some(cells_page.last_cursor);
+        }
+        Ok(cells)
+    }
+
+    fn holders(
+        &mut self,
+        type_script: Script,
+        at_block: Option<u64>,
+        token: Option<TokenInfo>,
+        export_path: Option<PathBuf>,
+        network: NetworkType,
+    ) -> Result<Output, String> {
+        let tip_number = self
+            .rpc_client
+            .get_indexer_tip()?
+            .ok_or_else(|| "The indexer has no tip yet".to_string())?
+            .block_number;
+        let at_block = at_block.unwrap_or(tip_number);
+        if at_block > tip_number {
+            return Err(format!(
+                "The block number {} is greater than the indexer tip {}",
+                at_block, tip_number
+            ));
+        }
+
+        // lock script hash => holder
+        let mut holders: HashMap<H256, UdtHolder> = HashMap::default();
+        let mut cell_count = 0;
+        for (lock_script, amount) in self.udt_cells_at(&type_script, at_block)? {
+            cell_count += 1;
+            let lock_script_hash: H256 = lock_script.calc_script_hash().unpack();
+            let holder = holders
+                .entry(lock_script_hash.clone())
+                .or_insert_with(|| UdtHolder {
+                    address: Address::new(network, AddressPayload::from(lock_script), true)
+                        .to_string(),
+                    lock_script_hash,
+                    amount: 0,
+                    cell_count: 0,
+                });
+            holder.amount = holder
+                .amount
+                .checked_add(amount)
+                .ok_or_else(|| format!("The amount of holder {} overflows u128", holder.address))?;
+            holder.cell_count += 1;
+        }
+        let mut holders: Vec<UdtHolder> = holders.into_values().collect();
+        holders.sort_by(|a, b| {
+            b.amount
+                .cmp(&a.amount)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        let total_amount = holders
+            .iter()
+            .try_fold(0u128, |total, holder| total.checked_add(holder.amount))
+            .ok_or_else(|| "The total amount of the holders overflows u128".to_string())?;
+
+        let (minted, burned) = self.udt_supply_history(&type_script, at_block)?;
+        let supply = minted.checked_sub(burned);
+
+        if let Some(path) = export_path {
+            let content = if path.extension().map(|ext| ext == "csv").unwrap_or(false) {
+                holders_to_csv(&holders)
+            } else {
+                let values = holders.iter().map(UdtHolder::to_json).collect::<Vec<_>>();
+                serde_json::to_string_pretty(&values).map_err(|err| err.to_string())?
+            };
+            fs::write(path, content).map_err(|err| err.to_string())?;
+        }
+        let mut resp = serde_json::json!({
+            "type_script": json_types::Script::from(type_script),
+            "block_number": at_block,
+            "holder_count": holders.len(),
+            "cell_count": cell_count,
+            // u128 is too large for json
+            "total_amount": total_amount.to_string(),
+            "supply": {
+                "minted": minted.to_string(),
+                "burned": burned.to_string(),
+                "issued_supply": supply.map(|supply| supply.to_string()),
+                "reconciled": supply == Some(total_amount),
+            },
+            "holders": holders.iter().map(UdtHolder::to_json).collect::<Vec<_>>(),
+        });
+        if let Some(token) = token {
+            resp["total_amount_display"] = serde_json::json!(format!(
+                "{} {}",
+                format_udt_amount(total_amount, token.decimals)?,
+                token.symbol
+            ));
+        }
+        Ok(Output::new_output(resp))
+    }
+
+    /// The (lock script, amount) of the live udt cells at the block: the
+    /// current live cells created in or before the block, and the cells
+    /// created in or before the block but consumed after it.
+    fn udt_cells_at(
+        &mut self,
+        type_script: &Script,
+        at_block: u64,
+    ) -> Result<Vec<(Script, u128)>, String> {
+        let search_key = SearchKey {
+            script: type_script.clone().into(),
+            script_type: ScriptType::Type,
+            script_search_mode: None,
+            filter: Some(SearchKeyFilter {
+                block_range: Some([0.into(), (at_block + 1).into()]),
+                ..Default::default()
+            }),
+            with_data: Some(true),
+            group_by_transaction: None,
+        };
+        let mut cells: HashMap<OutPoint, (Script, u128)> = HashMap::default();
+        let mut after = None;
+        loop {
+            let cells_page = self.rpc_client.get_cells(
+                search_key.clone(),
+                Order::Asc,
+                HOLDERS_SEARCH_LIMIT.into(),
+                after,
+            )?;
+            let page_size = cells_page.objects.len();
+            for cell in cells_page.objects {
+                let data = cell
+                    .output_data
+                    .map(|data| data.into_bytes())
+                    .unwrap_or_default();
+                cells.insert(
+                    cell.out_point.into(),
+                    (cell.output.lock.into(), parse_udt_amount(&data)?),
+                );
+            }
+            if page_size < HOLDERS_SEARCH_LIMIT as usize {
+                break;
+            }
+            after = Some(cells_page.last_cursor);
+        }
+
+        // The transactions after the block, a cell consumed after the live
+        // cells are searched may also be in `cells`.
+        let mut created_after = HashSet::new();
+        let mut consumed_after = Vec::new();
+        for (tx_hash, io_cells) in
+            self.search_udt_transactions(type_script, at_block + 1, u64::max_value())?
+        {
+            let tx = self
+                .tx_dep_provider
+                .get_transaction(&tx_hash.pack())
+                .map_err(|err| err.to_string())?;
+            for (cell_type, io_index) in io_cells {
+                match cell_type {
+                    CellType::Output => {
+                        created_after.insert(OutPoint::new(tx_hash.pack(), io_index));
+                    }
+                    CellType::Input => {
+                        let input = tx
+                            .inputs()
+                            .get(io_index as usize)
+                            .ok_or_else(|| format!("Invalid input index: {}", io_index))?;
+                        consumed_after.push(input.previous_output());
+                    }
+                }
+            }
+        }
+        for out_point in consumed_after {
+            if created_after.contains(&out_point) || cells.contains_key(&out_point) {
+                continue;
+            }
+            let output = self
+                .tx_dep_provider
+                .get_cell(&out_point)
+                .map_err(|err| err.to_string())?;
+            let data = self
+                .tx_dep_provider
+                .get_cell_data(&out_point)
+                .map_err(|err| err.to_string())?;
+            cells.insert(out_point, (output.lock(), parse_udt_amount(&data)?));
+        }
+        Ok(cells.into_values().collect())
+    }
+
+    /// The total minted and burned amount of the udt in the blocks
+    /// [0, at_block], by the input/output udt amount of every transaction.
+    fn udt_supply_history(
+        &mut self,
+        type_script: &Script,
+        at_block: u64,
+    ) -> Result<(u128, u128), String> {
+        let mut minted: u128 = 0;
+        let mut burned: u128 = 0;
+        for (tx_hash, io_cells) in self.search_udt_transactions(type_script, 0, at_block + 1)? {
+            let tx = self
+                .tx_dep_provider
+                .get_transaction(&tx_hash.pack())
+                .map_err(|err| err.to_string())?;
+            let mut input_amount: u128 = 0;
+            let mut output_amount: u128 = 0;
+            for (cell_type, io_index) in io_cells {
+                match cell_type {
+                    CellType::Output => {
+                        let data = tx
+                            .outputs_data()
+                            .get(io_index as usize)
+                            .ok_or_else(|| format!("Invalid output index: {}", io_index))?;
+                        output_amount = output_amount
+                            .checked_add(parse_udt_amount(&data.raw_data())?)
+                            .ok_or_else(|| {
+                                format!("The output amount of {:#x} overflows u128", tx_hash)
+                            })?;
+                    }
+                    CellType::Input => {
+                        let input = tx
+                            .inputs()
+                            .get(io_index as usize)
+                            .ok_or_else(|| format!("Invalid input index: {}", io_index))?;
+                        let data = self
+                            .tx_dep_provider
+                            .get_cell_data(&input.previous_output())
+                            .map_err(|err| err.to_string())?;
+                        input_amount = input_amount
+                            .checked_add(parse_udt_amount(&data)?)
+                            .ok_or_else(|| {
+                                format!("The input amount of {:#x} overflows u128", tx_hash)
+                            })?;
+                    }
+                }
+            }
+            if output_amount > input_amount {
+                minted = minted
+                    .checked_add(output_amount - input_amount)
+                    .ok_or_else(|| "The minted amount overflows u128".to_string())?;
+            } else {
+                burned = burned
+                    .checked_add(input_amount - output_amount)
+                    .ok_or_else(|| "The burned amount overflows u128".to_string())?;
+            }
+        }
+        Ok((minted, burned))
+    }
+
+    /// Search the transactions of the udt in the blocks [from, to) by the
+    /// indexer, return the transaction hash and the (cell type, io index) of
+    /// the udt cells in it.
+    fn search_udt_transactions(
+        &mut self,
+        type_script: &Script,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<UdtTxCells>, String> {
+        let search_key = SearchKey {
+            script: type_script.clone().into(),
+            script_type: ScriptType::Type,
+            script_search_mode: None,
+            filter: Some(SearchKeyFilter {
+                block_range: Some([from.into(), to.into()]),
+                ..Default::default()
+            }),
+            with_data: None,
+            group_by_transaction: Some(true),
+        };
+        let mut txs = Vec::new();
+        let mut after = None;
+        loop {
+            let txs_page = self.rpc_client.get_transactions(
+                search_key.clone(),
+                Order::Asc,
+                HOLDERS_SEARCH_LIMIT.into(),
+                after,
+            )?;
+            let page_size = txs_page.objects.len();
+            for tx in txs_page.objects {
+                txs.push(match tx {
+                    Tx::Grouped(tx) => (tx.tx_hash, tx.cells),
+                    Tx::Ungrouped(tx) => (tx.tx_hash, vec![(tx.io_type, tx.io_index)]),
+                });
+            }
+            if page_size < HOLDERS_SEARCH_LIMIT as usize {
+                break;
+            }
+            after = Some(txs_page.last_cursor);
+        }
+        Ok(txs)
+    }
+
+    fn airdrop(
+        &mut self,
+        args: AirdropArgs,
+        common_args: SudtCommonArgs,
+        network: NetworkType,
+    ) -> Result<Output, String> {
+        let AirdropArgs {
+            owner,
+            sender,
+            recipients_path,
+            journal_path,
+            decimals,
+            max_recipients,
+        } = args;
+        let SudtCommonArgs {
+            udt_args,
+            privkeys,
+            external_signer,
+            cell_deps,
+            fee_rate,
+            force_small_change_as_fee,
+            debug,
+        } = common_args;
+        if max_recipients == 0 {
+            return Err("<max-recipients> must be greater than 0".to_string());
+        }
+        let recipients_content = fs::read(&recipients_path).map_err(|err| err.to_string())?;
+        let rows = parse_airdrop_rows(&String::from_utf8_lossy(&recipients_content))?;
+        if rows.is_empty() {
+            return Err(format!("No recipient found in {:?}", recipients_path));
+        }
+
+        let udt_script_id = udt_args.script_id(&cell_deps)?;
+        let owner_script = Script::from(&owner);
+        let type_script = udt_args
+            .udt_type
+            .build_script(&udt_script_id, &owner_script.calc_script_hash());
+        let acp_script_id = get_script_id(&cell_deps, CellDepName::Acp).ok();
+        let cheque_script_id = get_script_id(&cell_deps, CellDepName::Cheque).ok();
+        // The udt is issued by the owner or transferred from the sender, the
+        // payer also provides the capacity and is the cheque sender.
+        let is_transfer = sender.is_some();
+        let (payer_name, payer) = match sender {
+            Some(sender) => ("sender", sender),
+            None => ("owner", owner),
+        };
+        let payer_script = Script::from(&payer);
+        let payer_script_hash = payer_script.calc_script_hash();
+        let payer_account = H160::from_slice(payer.payload().args().as_ref()).unwrap();
+
+        let mut receivers = Vec::with_capacity(rows.len());
+        let mut acp_receivers = HashSet::new();
+        for (index, row) in rows.iter().enumerate() {
+            let row_error = |err: String| format!("Invalid recipient #{}: {}", index, err);
+            let address: Address = AddressParser::default()
+                .set_network(network)
+                .parse(&row.address)
+                .map_err(row_error)?;
+            let amount: u128 = UdtAmountParser::new(decimals)
+                .parse(&row.amount)
+                .map_err(row_error)?;
+            let payload = address.payload();
+            let code_hash = payload.code_hash(Some(network));
+            let hash_type = payload.hash_type();
+            let is_sighash =
+                code_hash == SIGHASH_TYPE_HASH.pack() && hash_type == ScriptHashType::Type;
+            let is_acp = acp_script_id
+                .as_ref()
+                .map(|script_id| {
+                    code_hash == script_id.code_hash.pack() && hash_type == script_id.hash_type
+                })
+                .unwrap_or(false);
+            let receiver_script = Script::from(&address);
+            let kind = row
+                .kind
+                .as_deref()
+                .unwrap_or(if is_acp { "acp" } else { "sighash" });
+            let (action, lock_script) = match kind {
+                "acp" => {
+                    if !is_acp {
+                        return Err(row_error(format!(
+                            "{} is not an anyone-can-pay address (by the <acp> cell_dep item)",
+                            address
+                        )));
+                    }
+                    // The acp cell can only be updated once in a transaction
+                    if !acp_receivers.insert(address.to_string()) {
+                        return Err(row_error(format!(
+                            "duplicated anyone-can-pay address: {}",
+                            address
+                        )));
+                    }
+                    (TransferAction::Update, receiver_script)
+                }
+                "cheque" => {
+                    if !is_sighash {
+                        return Err(row_error(format!(
+                            "the cheque receiver {} is not a sighash address",
+                            address
+                        )));
+                    }
+                    let script_id = cheque_script_id.as_ref().ok_or_else(|| {
+                        row_error("no cheque cell_dep item in cell_deps".to_string())
+                    })?;
+                    let receiver_script_hash = receiver_script.calc_script_hash();
+                    let mut script_args = vec![0u8; 40];
+                    script_args[0..20].copy_from_slice(&receiver_script_hash.as_slice()[0..20]);
+                    script_args[20..40].copy_from_slice(&payer_script_hash.as_slice()[0..20]);
+                    let script = Script::new_builder()
+                        .code_hash(script_id.code_hash.pack())
+                        .hash_type(script_id.hash_type.into())
+                        .args(Bytes::from(script_args).pack())
+                        .build();
+                    (TransferAction::Create, script)
+                }
+                _ => {
+                    if !is_sighash {
+                        return Err(row_error(format!("{} is not a sighash address", address)));
+                    }
+                    (TransferAction::Create, receiver_script)
+                }
+            };
+            receivers.push(UdtTargetReceiver {
+                action,
+                lock_script,
+                capacity: None,
+                amount,
+                extra_data: None,
+            });
+        }
+
+        // Check the recorded transactions, the recipients of a sent (pending,
+        // proposed or committed) transaction are paid, the transaction not
+        // found by the node is never sent or dropped, its recipients are paid
+        // again.
+        let mut journal = AirdropJournal::load_or_new(
+            &journal_path,
+            &recipients_content,
+            type_script.calc_script_hash().unpack(),
+        )?;
+        let mut paid = vec![false; receivers.len()];
+        let mut reports = Vec::new();
+        let tip_number = self.rpc_client.get_tip_block_number()?;
+        for record in std::mem::take(&mut journal.transactions) {
+            let (status, tx) = match self.rpc_client.get_transaction(record.tx_hash.clone())? {
+                Some(tx) => (tx.tx_status.status, tx.transaction),
+                None => (Status::Unknown, None),
+            };
+            match (&status, tx) {
+                (Status::Committed, _) => {}
+                // The inputs of the transaction in the tx pool are still live
+                // in the indexer, and its change cell is spent by the next one
+                (Status::Pending | Status::Proposed, Some(tx)) => {
+                    self.cell_collector
+                        .apply_tx(Transaction::from(tx.inner), tip_number)
+                        .map_err(|err| err.to_string())?;
+                }
+                _ => continue,
+            }
+            for index in &record.recipients {
+                *paid
+                    .get_mut(*index)
+                    .ok_or_else(|| format!("Invalid recipient index in journal: {}", index))? =
+                    true;
+            }
+            reports.push(serde_json::json!({
+                "tx_hash": record.tx_hash,
+                "recipients": record.recipients,
+                "status": status,
+            }));
+            journal.transactions.push(record);
+        }
+        journal.save(&journal_path)?;
+        let paid_before = paid.iter().filter(|paid| **paid).count();
+        let pending = (0..receivers.len())
+            .filter(|index| !paid[*index])
+            .collect::<Vec<_>>();
+
+        let limits = if pending.is_empty() {
+            None
+        } else {
+            Some(TxLimits::from_consensus(self.rpc_client)?)
+        };
+        let outputs_validator = Some(json_types::OutputsValidator::Passthrough);
+        let chunker = TxChunker {
+            len: pending.len(),
+            max_chunk_len: max_recipients,
+            limits,
+            check_cycles: true,
+            outputs_validator: outputs_validator.clone(),
+        };
+        let mut passwords = HashMap::new();
+        chunker.run(
+            self.rpc_client,
+            &mut self.cell_collector,
+            |index| format!("recipient #{}", pending[index]),
+            |rpc_client, chunk_cell_collector, range| {
+                let chunk_receivers = pending[range]
+                    .iter()
+                    .map(|index| receivers[*index].clone())
+                    .collect::<Vec<_>>();
+                let builder: Box<dyn TxBuilder> = if is_transfer {
+                    Box::new(UdtTransferBuilder {
+                        type_script: type_script.clone(),
+                        sender: payer_script.clone(),
+                        receivers: chunk_receivers,
+                    })
+                } else {
+                    Box::new(UdtIssueBuilder {
+                        udt_type: udt_args.udt_type.clone(),
+                        script_id: udt_script_id.clone(),
+                        owner: owner_script.clone(),
+                        receivers: chunk_receivers,
+                    })
+                };
+                let mut udt_builder = UdtTxBuilder {
+                    plugin_mgr: self.plugin_mgr,
+                    rpc_client,
+                    cell_collector: chunk_cell_collector,
+                    cell_dep_resolver: &mut self.cell_dep_resolver,
+                    header_dep_resolver: &self.header_dep_resolver,
+                    tx_dep_provider: &self.tx_dep_provider,
+                    builder: builder.as_ref(),
+                    extension_scripts: &udt_args.extension_scripts,
+                };
+                udt_builder.build_with_passwords(
+                    vec![(payer_name.to_string(), payer_account.clone())],
+                    privkeys.clone(),
+                    external_signer.clone(),
+                    &cell_deps,
+                    payer_script.clone(),
+                    acp_script_id.clone(),
+                    None,
+                    fee_rate,
+                    force_small_change_as_fee,
+                    &mut passwords,
+                )
+            },
+            |rpc_client, tx, range| {
+                // Record the transaction before sending it, so an interrupted
+                // airdrop never pays the recipients twice.
+                let tx_hash: H256 = tx.hash().unpack();
+                let recipients = pending[range].to_vec();
+                journal.transactions.push(AirdropTransaction {
+                    tx_hash: tx_hash.clone(),
+                    recipients: recipients.clone(),
+                });
+                journal.save(&journal_path)?;
+                rpc_client
+                    .send_transaction(tx.data(), outputs_validator.clone())
+                    .map_err(|err| format!("Send transaction error: {}", err))?;
+                if debug {
+                    reports.push(serde_json::json!({
+                        "transaction": json_types::TransactionView::from(tx.clone()),
+                        "recipients": recipients,
+                        "status": "sent",
+                    }));
+                } else {
+                    reports.push(serde_json::json!({
+                        "tx_hash": tx_hash,
+                        "recipients": recipients,
+                        "status": "sent",
+                    }));
+                }
+                Ok(())
+            },
+        )?;
+
+        let resp = serde_json::json!({
+            "journal": journal_path,
+            "recipients": receivers.len(),
+            "paid_before": paid_before,
+            "paid_now": pending.len(),
+            "transactions": reports,
+        });
         Ok(Output::new_output(resp))
     }
 
@@ -538,14 +1551,16 @@ impl<'a> SudtSubCommand<'a> {
             capacity_provider,
         } = args;
         let SudtCommonArgs {
+            udt_args,
             privkeys,
+            external_signer,
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
             debug,
         } = common_args;
-        let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
-        let udt_type = UdtType::Sudt;
+        let udt_script_id = udt_args.script_id(&cell_deps)?;
+        let udt_type = udt_args.udt_type.clone();
         let acp_script_id = get_script_id(&cell_deps, CellDepName::Acp)?;
         let owner_script_hash = Script::from(&owner).calc_script_hash();
         let capacity_provider = capacity_provider.unwrap_or_else(|| to.clone());
@@ -588,10 +1603,12 @@ impl<'a> SudtSubCommand<'a> {
             header_dep_resolver: &self.header_dep_resolver,
             tx_dep_provider: &self.tx_dep_provider,
             builder: &builder,
+            extension_scripts: &udt_args.extension_scripts,
         };
         let tx = udt_builder.build(
             vec![("capacity provider".to_string(), capacity_provider_account)],
             privkeys,
+            external_signer,
             &cell_deps,
             Script::from(&capacity_provider),
             None,
@@ -635,16 +1652,16 @@ impl<'a> SudtSubCommand<'a> {
             capacity_provider,
         } = args;
         let SudtCommonArgs {
+            udt_args,
             privkeys,
+            external_signer,
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
             debug,
         } = common_args;
-        let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
         let cheque_script_id = get_script_id(&cell_deps, CellDepName::Cheque)?;
         let acp_script_id = get_script_id(&cell_deps, CellDepName::Acp)?;
-        let owner_script = Script::from(&owner);
         let sender_script = Script::from(&sender);
         let receiver_script = Script::from(&receiver);
         let cheque_script = {
@@ -664,11 +1681,7 @@ impl<'a> SudtSubCommand<'a> {
             .hash_type(acp_script_id.hash_type.into())
             .args(receiver_script.args())
             .build();
-        let type_script = Script::new_builder()
-            .code_hash(udt_script_id.code_hash.pack())
-            .hash_type(udt_script_id.hash_type.into())
-            .args(owner_script.calc_script_hash().as_bytes().pack())
-            .build();
+        let type_script = udt_args.build_type_script(&cell_deps, &owner)?;
 
         let mut cheque_query = CellQueryOptions::new_lock(cheque_script);
         cheque_query.secondary_script = Some(type_script.clone());
@@ -723,10 +1736,12 @@ impl<'a> SudtSubCommand<'a> {
             header_dep_resolver: &self.header_dep_resolver,
             tx_dep_provider: &self.tx_dep_provider,
             builder: &builder,
+            extension_scripts: &udt_args.extension_scripts,
         };
         let tx = udt_builder.build(
             accounts,
             privkeys,
+            external_signer,
             &cell_deps,
             capacity_provider,
             Some(acp_script_id),
@@ -766,20 +1781,20 @@ impl<'a> SudtSubCommand<'a> {
             to_acp_address,
         } = args;
         let SudtCommonArgs {
+            udt_args,
             privkeys,
+            external_signer,
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
             debug,
         } = common_args;
-        let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
         let cheque_script_id = get_script_id(&cell_deps, CellDepName::Cheque)?;
         let acp_script_id = if to_acp_address {
             Some(get_script_id(&cell_deps, CellDepName::Acp)?)
         } else {
             None
         };
-        let owner_script = Script::from(&owner);
         let sender_script = Script::from(&sender);
         let receiver_script = Script::from(&receiver);
         let cheque_script = {
@@ -794,11 +1809,7 @@ impl<'a> SudtSubCommand<'a> {
                 .args(Bytes::from(script_args).pack())
                 .build()
         };
-        let type_script = Script::new_builder()
-            .code_hash(udt_script_id.code_hash.pack())
-            .hash_type(udt_script_id.hash_type.into())
-            .args(owner_script.calc_script_hash().as_bytes().pack())
-            .build();
+        let type_script = udt_args.build_type_script(&cell_deps, &owner)?;
 
         let mut cheque_query = CellQueryOptions::new_lock(cheque_script);
         cheque_query.secondary_script = Some(type_script);
@@ -838,10 +1849,12 @@ impl<'a> SudtSubCommand<'a> {
             header_dep_resolver: &self.header_dep_resolver,
             tx_dep_provider: &self.tx_dep_provider,
             builder: &builder,
+            extension_scripts: &udt_args.extension_scripts,
         };
         let tx = udt_builder.build(
             accounts,
             privkeys,
+            external_signer,
             &cell_deps,
             capacity_provider,
             acp_script_id,
@@ -874,17 +1887,22 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
         let network = get_network_type(self.rpc_client)?;
         match matches.subcommand() {
             ("issue", Some(m)) => {
-                let owner: Address = AddressParser::new_sighash()
-                    .set_network(network)
-                    .from_matches(m, "owner")?;
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    token,
+                } = self.udt_from_matches(m, network, AddressParser::new_sighash())?;
                 let udt_to_vec: Vec<(Address, u128)> = {
                     let mut address_parser = AddressParser::default();
                     address_parser.set_network(network);
-                    UdtTargetParser::new(address_parser).from_matches_vec(m, "udt-to")?
+                    UdtTargetParser::new(address_parser)
+                        .set_decimals(token.map(|token| token.decimals).unwrap_or(0))
+                        .from_matches_vec(m, "udt-to")?
                 };
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
-                let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                let external_signer = external_signer_from_matches(m)?;
                 let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
@@ -907,7 +1925,9 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         to_acp_address,
                     },
                     SudtCommonArgs {
+                        udt_args,
                         privkeys,
+                        external_signer,
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
@@ -917,23 +1937,28 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 )
             }
             ("transfer", Some(m)) => {
-                let owner: Address = AddressParser::default()
-                    .set_network(network)
-                    .from_matches(m, "owner")?;
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    token,
+                } = self.udt_from_matches(m, network, AddressParser::default())?;
                 let sender: Address = AddressParser::default()
                     .set_network(network)
                     .from_matches(m, "sender")?;
                 let udt_to_vec: Vec<(Address, u128)> = {
                     let mut address_parser = AddressParser::default();
                     address_parser.set_network(network);
-                    UdtTargetParser::new(address_parser).from_matches_vec(m, "udt-to")?
+                    UdtTargetParser::new(address_parser)
+                        .set_decimals(token.map(|token| token.decimals).unwrap_or(0))
+                        .from_matches_vec(m, "udt-to")?
                 };
                 let capacity_provider: Option<Address> = AddressParser::new_sighash()
                     .set_network(network)
                     .from_matches_opt(m, "capacity-provider")?;
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
-                let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                let external_signer = external_signer_from_matches(m)?;
                 let to_cheque_address = m.is_present("to-cheque-address");
                 let to_acp_address = m.is_present("to-acp-address");
                 let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
@@ -956,9 +1981,12 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         to_cheque_address,
                         to_acp_address,
                         capacity_provider,
+                        dry_run: m.is_present("dry-run"),
                     },
                     SudtCommonArgs {
+                        udt_args,
                         privkeys,
+                        external_signer,
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
@@ -968,19 +1996,46 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 )
             }
             ("get-amount", Some(m)) => {
-                let owner: Address = AddressParser::default()
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    token,
+                } = self.udt_from_matches(m, network, AddressParser::default())?;
+                let address: Address = AddressParser::default()
                     .set_network(network)
-                    .from_matches(m, "owner")?;
-                let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                    .from_matches(m, "address")?;
+                self.get_amount(owner, address, cell_deps, udt_args, token)
+            }
+            ("balances", Some(m)) => {
                 let address: Address = AddressParser::default()
                     .set_network(network)
                     .from_matches(m, "address")?;
-                self.get_amount(owner, address, cell_deps)
+                let cell_deps: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
+                self.balances(address, cell_deps, network)
+            }
+            ("holders", Some(m)) => {
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    token,
+                } = self.udt_from_matches(m, network, AddressParser::default())?;
+                let type_script = udt_args.build_type_script(&cell_deps, &owner)?;
+                let at_block: Option<u64> =
+                    FromStrParser::<u64>::default().from_matches_opt(m, "at-block")?;
+                let export_path: Option<PathBuf> =
+                    FilePathParser::new(false).from_matches_opt(m, "export")?;
+                self.holders(type_script, at_block, token, export_path, network)
             }
             ("new-empty-acp", Some(m)) => {
-                let owner: Address = AddressParser::default()
-                    .set_network(network)
-                    .from_matches(m, "owner")?;
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    ..
+                } = self.udt_from_matches(m, network, AddressParser::default())?;
                 let to: Address = AddressParser::new_sighash()
                     .set_network(network)
                     .from_matches(m, "to")?;
@@ -989,7 +2044,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                     .from_matches_opt(m, "capacity-provider")?;
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
-                let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                let external_signer = external_signer_from_matches(m)?;
                 let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
@@ -1000,7 +2055,9 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         capacity_provider,
                     },
                     SudtCommonArgs {
+                        udt_args,
                         privkeys,
+                        external_signer,
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
@@ -1010,9 +2067,12 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 )
             }
             ("cheque-claim", Some(m)) => {
-                let owner: Address = AddressParser::new_sighash()
-                    .set_network(network)
-                    .from_matches(m, "owner")?;
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    ..
+                } = self.udt_from_matches(m, network, AddressParser::new_sighash())?;
                 let sender: Address = AddressParser::new_sighash()
                     .set_network(network)
                     .from_matches(m, "sender")?;
@@ -1024,7 +2084,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                     .from_matches_opt(m, "capacity-provider")?;
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
-                let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                let external_signer = external_signer_from_matches(m)?;
                 let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
@@ -1040,7 +2100,9 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         capacity_provider,
                     },
                     SudtCommonArgs {
+                        udt_args,
                         privkeys,
+                        external_signer,
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
@@ -1049,9 +2111,12 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 )
             }
             ("cheque-withdraw", Some(m)) => {
-                let owner: Address = AddressParser::new_sighash()
-                    .set_network(network)
-                    .from_matches(m, "owner")?;
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    ..
+                } = self.udt_from_matches(m, network, AddressParser::new_sighash())?;
                 let sender: Address = AddressParser::new_sighash()
                     .set_network(network)
                     .from_matches(m, "sender")?;
@@ -1064,7 +2129,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 let to_acp_address = m.is_present("to-acp-address");
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
-                let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                let external_signer = external_signer_from_matches(m)?;
                 let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
@@ -1077,7 +2142,9 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         to_acp_address,
                     },
                     SudtCommonArgs {
+                        udt_args,
                         privkeys,
+                        external_signer,
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
@@ -1085,6 +2152,106 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                     },
                 )
             }
+            ("airdrop", Some(m)) => {
+                let UdtInfo {
+                    owner,
+                    cell_deps,
+                    udt_args,
+                    token,
+                } = self.udt_from_matches(m, network, AddressParser::new_sighash())?;
+                let sender: Option<Address> = AddressParser::new_sighash()
+                    .set_network(network)
+                    .from_matches_opt(m, "sender")?;
+                let recipients_path: PathBuf = FilePathParser::new(true).from_matches(m, "file")?;
+                let journal_path: PathBuf = FilePathParser::new(false)
+                    .from_matches_opt(m, "journal")?
+                    .unwrap_or_else(|| AirdropJournal::default_path(&recipients_path));
+                let max_recipients: usize =
+                    FromStrParser::<usize>::default().from_matches(m, "max-recipients")?;
+                let privkeys: Vec<PrivkeyWrapper> =
+                    PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
+                let external_signer = external_signer_from_matches(m)?;
+                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let force_small_change_as_fee =
+                    FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+
+                self.airdrop(
+                    AirdropArgs {
+                        owner,
+                        sender,
+                        recipients_path,
+                        journal_path,
+                        decimals: token.map(|token| token.decimals).unwrap_or(0),
+                        max_recipients,
+                    },
+                    SudtCommonArgs {
+                        udt_args,
+                        privkeys,
+                        external_signer,
+                        cell_deps,
+                        fee_rate,
+                        force_small_change_as_fee,
+                        debug,
+                    },
+                    network,
+                )
+            }
+            ("token", Some(m)) => match m.subcommand() {
+                ("add", Some(m)) => {
+                    let owner: Address = AddressParser::default()
+                        .set_network(network)
+                        .from_matches(m, "owner")?;
+                    let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                    let decimals: u32 =
+                        FromStrParser::<u32>::default().from_matches(m, "decimals")?;
+                    if decimals > UDT_MAX_DECIMALS {
+                        return Err(format!(
+                            "The decimals must be less than or equal to {}",
+                            UDT_MAX_DECIMALS
+                        ));
+                    }
+                    // Check the type script can be built from the arguments
+                    UdtArgs::from_matches(m)?.build_type_script(&cell_deps, &owner)?;
+                    let xudt_extensions: Vec<Script> =
+                        ScriptParser.from_matches_vec(m, "xudt-extension")?;
+                    let token = TokenInfo {
+                        symbol: m.value_of("symbol").unwrap().to_string(),
+                        decimals,
+                        owner: owner.to_string(),
+                        udt_type: m.value_of("udt-type").unwrap_or("sudt").to_string(),
+                        xudt_owner_mode: m.values_of_lossy("xudt-owner-mode").unwrap_or_default(),
+                        xudt_extensions: xudt_extensions
+                            .into_iter()
+                            .map(json_types::Script::from)
+                            .collect(),
+                        cell_deps,
+                    };
+                    let resp = token_json(&token)?;
+                    let mut registry = TokenRegistry::load(&self.token_registry_path)?;
+                    registry.add(token)?;
+                    registry.save(&self.token_registry_path)?;
+                    Ok(Output::new_output(resp))
+                }
+                ("list", _) => {
+                    let registry = TokenRegistry::load(&self.token_registry_path)?;
+                    let tokens = registry
+                        .tokens
+                        .iter()
+                        .map(token_json)
+                        .collect::<Result<Vec<_>, String>>()?;
+                    Ok(Output::new_output(tokens))
+                }
+                ("remove", Some(m)) => {
+                    let symbol = m.value_of("symbol").unwrap();
+                    let mut registry = TokenRegistry::load(&self.token_registry_path)?;
+                    registry
+                        .remove(symbol)
+                        .ok_or_else(|| format!("Token not found in registry: {}", symbol))?;
+                    registry.save(&self.token_registry_path)?;
+                    Ok(Output::new_success())
+                }
+                _ => Err(Self::subcommand("sudt").generate_usage()),
+            },
             ("build-acp-address", Some(m)) => {
                 let sighash_addr: Address = AddressParser::new_sighash()
                     .set_network(network)
@@ -1129,6 +2296,13 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
     }
 }
 
+fn external_signer_from_matches(m: &ArgMatches) -> Result<Option<ExternalSigner>, String> {
+    FilePathParser::new(true)
+        .from_matches_opt(m, "signer-cmd")?
+        .map(ExternalSigner::new)
+        .transpose()
+}
+
 pub fn get_script_id(cell_deps: &CellDeps, name: CellDepName) -> Result<ScriptId, String> {
     cell_deps
         .get_item(name)
@@ -1170,6 +2344,113 @@ fn check_udt_args(
     Ok(())
 }
 
+fn token_json(token: &TokenInfo) -> Result<serde_json::Value, String> {
+    let owner = Address::from_str(&token.owner).map_err(|err| err.to_string())?;
+    let type_script = UdtArgs::from_token(token)?.build_type_script(&token.cell_deps, &owner)?;
+    Ok(serde_json::json!({
+        "symbol": token.symbol,
+        "decimals": token.decimals,
+        "owner": token.owner,
+        "udt_type": token.udt_type,
+        "type_script": json_types::Script::from(type_script),
+    }))
+}
+
+/// The udt identified by the command arguments
+struct UdtInfo {
+    owner: Address,
+    cell_deps: CellDeps,
+    udt_args: UdtArgs,
+    token: Option<TokenInfo>,
+}
+
+/// The udt balance of an address (`sudt balances`)
+struct UdtBalance {
+    type_script: Script,
+    udt_type: &'static str,
+    cell_count: usize,
+    // kind of the lock script => amount
+    amounts: BTreeMap<BalanceKind, u128>,
+}
+
+/// The kind of the lock script which holds the udt cells of an address
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum BalanceKind {
+    /// The lock script of the address
+    Address,
+    /// The acp lock script with the same args
+    Acp,
+    /// The cheque cells to the address
+    ChequeReceiver,
+    /// The cheque cells sent by the address (not claimed yet)
+    ChequeSender,
+}
+
+impl BalanceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BalanceKind::Address => "address",
+            BalanceKind::Acp => "acp",
+            BalanceKind::ChequeReceiver => "cheque_receiver",
+            BalanceKind::ChequeSender => "cheque_sender",
+        }
+    }
+
+    fn is_cheque(&self) -> bool {
+        matches!(
+            self,
+            BalanceKind::ChequeReceiver | BalanceKind::ChequeSender
+        )
+    }
+}
+
+/// A transaction hash and the (cell type, io index) of the udt cells in it
+type UdtTxCells = (H256, Vec<(CellType, u32)>);
+
+/// The udt amount of a lock script (`sudt holders`)
+struct UdtHolder {
+    address: String,
+    lock_script_hash: H256,
+    amount: u128,
+    cell_count: usize,
+}
+
+impl UdtHolder {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "address": self.address,
+            "lock_script_hash": self.lock_script_hash,
+            // u128 is too large for json
+            "amount": self.amount.to_string(),
+            "cell_count": self.cell_count,
+        })
+    }
+}
+
+fn holders_to_csv(holders: &[UdtHolder]) -> String {
+    let mut content = String::from("address,lock_script_hash,amount,cell_count\n");
+    for holder in holders {
+        content.push_str(&format!(
+            "{},{:#x},{},{}\n",
+            holder.address, holder.lock_script_hash, holder.amount, holder.cell_count,
+        ));
+    }
+    content
+}
+
+/// The udt amount is the first 16 bytes (u128 little endian) of the cell data
+fn parse_udt_amount(data: &[u8]) -> Result<u128, String> {
+    if data.len() < 16 {
+        return Err(format!(
+            "invalid cell data length: {}, expected: >= 16",
+            data.len()
+        ));
+    }
+    let mut amount_bytes = [0u8; 16];
+    amount_bytes.copy_from_slice(&data[0..16]);
+    Ok(u128::from_le_bytes(amount_bytes))
+}
+
 struct IssueArgs {
     owner: Address,
     udt_to_vec: Vec<(Address, u128)>,
@@ -1183,6 +2464,7 @@ struct TransferArgs {
     to_cheque_address: bool,
     to_acp_address: bool,
     capacity_provider: Option<Address>,
+    dry_run: bool,
 }
 struct NewAcpArgs {
     owner: Address,
@@ -1190,6 +2472,15 @@ struct NewAcpArgs {
     capacity_provider: Option<Address>,
 }
 
+struct AirdropArgs {
+    owner: Address,
+    sender: Option<Address>,
+    recipients_path: PathBuf,
+    journal_path: PathBuf,
+    decimals: u32,
+    max_recipients: usize,
+}
+
 struct ClaimArgs {
     owner: Address,
     sender: Address,
@@ -1240,6 +2531,181 @@ pub fn arg_cell_deps<'a>() -> Arg<'a> {
         .validator(|input| CellDepsParser.validate(input))
         .about("The cell deps information (for resolve cell_dep by script id or build lock/type script)")
 }
+pub fn arg_udt_type<'a>() -> Arg<'a> {
+    Arg::with_name("udt-type")
+        .long("udt-type")
+        .takes_value(true)
+        .possible_values(&["sudt", "xudt"])
+        .about("The udt type (default: sudt), the cell dep item of the type (<sudt> or <xudt>) must be given in <cell-deps>")
+}
+pub fn arg_xudt_owner_mode<'a>() -> Arg<'a> {
+    Arg::with_name("xudt-owner-mode")
+        .long("xudt-owner-mode")
+        .takes_value(true)
+        .multiple(true)
+        .possible_values(&["input-type", "output-type", "no-input-lock"])
+        .about("The owner mode flags of xUDT: also enter owner mode when an input type script (input-type) or an output type script (output-type) is the owner, or disable the owner mode by input lock script (no-input-lock)")
+}
+pub fn arg_xudt_extension<'a>() -> Arg<'a> {
+    Arg::with_name("xudt-extension")
+        .long("xudt-extension")
+        .takes_value(true)
+        .multiple(true)
+        .validator(|input| ScriptParser.validate(input))
+        .about("The extension script of xUDT (stored in the args), format: {code_hash}-{hash_type}-{args}, the cell dep of the script is resolved by the <extensions> items of <cell-deps>")
+}
+
+pub fn arg_token<'a>() -> Arg<'a> {
+    Arg::with_name("token")
+        .long("token")
+        .takes_value(true)
+        .conflicts_with_all(&[
+            "owner",
+            "cell-deps",
+            "udt-type",
+            "xudt-owner-mode",
+            "xudt-extension",
+        ])
+        .about("The symbol of a registered token (see `sudt token add`), replaces <owner>, <cell-deps> and the udt type arguments, the udt amounts are in the decimals of the token")
+}
+
+/// The udt type from `--udt-type`, the owner mode flags and the extension
+/// scripts are only for xUDT.
+#[derive(Clone)]
+struct UdtArgs {
+    udt_type: UdtType,
+    extension_scripts: Vec<Script>,
+}
+
+impl UdtArgs {
+    fn from_matches(m: &ArgMatches) -> Result<UdtArgs, String> {
+        let owner_modes = m.values_of_lossy("xudt-owner-mode").unwrap_or_default();
+        let extension_scripts: Vec<Script> = ScriptParser.from_matches_vec(m, "xudt-extension")?;
+        UdtArgs::new(
+            m.value_of("udt-type").unwrap_or("sudt"),
+            &owner_modes,
+            extension_scripts,
+        )
+    }
+
+    fn from_token(token: &TokenInfo) -> Result<UdtArgs, String> {
+        let extension_scripts = token
+            .xudt_extensions
+            .iter()
+            .cloned()
+            .map(Script::from)
+            .collect();
+        UdtArgs::new(&token.udt_type, &token.xudt_owner_mode, extension_scripts)
+    }
+
+    fn new(
+        udt_type: &str,
+        owner_modes: &[String],
+        extension_scripts: Vec<Script>,
+    ) -> Result<UdtArgs, String> {
+        match udt_type {
+            "xudt" => {
+                let mut owner_mode = 0;
+                for mode in owner_modes {
+                    owner_mode |= match mode.as_str() {
+                        "input-type" => XUDT_OWNER_MODE_INPUT_TYPE,
+                        "output-type" => XUDT_OWNER_MODE_OUTPUT_TYPE,
+                        "no-input-lock" => XUDT_OWNER_MODE_NO_INPUT_LOCK,
+                        _ => return Err(format!("Invalid xudt owner mode: {}", mode)),
+                    };
+                }
+                let extra_args = build_xudt_extra_args(owner_mode, &extension_scripts);
+                Ok(UdtArgs {
+                    udt_type: UdtType::Xudt(extra_args),
+                    extension_scripts,
+                })
+            }
+            "sudt" => {
+                if !owner_modes.is_empty() || !extension_scripts.is_empty() {
+                    return Err(
+                        "<xudt-owner-mode> and <xudt-extension> require <udt-type> to be xudt"
+                            .to_string(),
+                    );
+                }
+                Ok(UdtArgs {
+                    udt_type: UdtType::Sudt,
+                    extension_scripts,
+                })
+            }
+            _ => Err(format!("Invalid udt type: {}", udt_type)),
+        }
+    }
+
+    fn script_id(&self, cell_deps: &CellDeps) -> Result<ScriptId, String> {
+        let name = match self.udt_type {
+            UdtType::Sudt => CellDepName::Sudt,
+            UdtType::Xudt(_) => CellDepName::Xudt,
+        };
+        get_script_id(cell_deps, name)
+    }
+
+    fn build_type_script(&self, cell_deps: &CellDeps, owner: &Address) -> Result<Script, String> {
+        let owner_script_hash = Script::from(owner).calc_script_hash();
+        Ok(self
+            .udt_type
+            .build_script(&self.script_id(cell_deps)?, &owner_script_hash))
+    }
+}
+
+/// Build the xUDT args after the owner lock hash: empty when there is no flag,
+/// otherwise the flags (u32 little endian) and the extension scripts (molecule
+/// `ScriptVec`).
+fn build_xudt_extra_args(owner_mode: u32, extension_scripts: &[Script]) -> Bytes {
+    if owner_mode == 0 && extension_scripts.is_empty() {
+        return Bytes::new();
+    }
+    let mut flags = owner_mode;
+    if !extension_scripts.is_empty() {
+        flags |= XUDT_FLAGS_EXTENSIONS_IN_ARGS;
+    }
+    let mut args = flags.to_le_bytes().to_vec();
+    if !extension_scripts.is_empty() {
+        let scripts = ScriptVec::new_builder()
+            .set(extension_scripts.to_vec())
+            .build();
+        args.extend_from_slice(scripts.as_slice());
+    }
+    Bytes::from(args)
+}
+
+/// Add the cell deps of the xUDT extension scripts, they are not the lock or
+/// type script of any cell so can not be resolved by the inner builder.
+struct ExtensionCellDepsBuilder<'a> {
+    builder: &'a dyn TxBuilder,
+    extension_scripts: &'a [Script],
+}
+
+impl<'a> TxBuilder for ExtensionCellDepsBuilder<'a> {
+    fn build_base(
+        &self,
+        cell_collector: &mut dyn CellCollector,
+        cell_dep_resolver: &dyn CellDepResolver,
+        header_dep_resolver: &dyn HeaderDepResolver,
+        tx_dep_provider: &dyn TransactionDependencyProvider,
+    ) -> Result<TransactionView, TxBuilderError> {
+        let tx = self.builder.build_base(
+            cell_collector,
+            cell_dep_resolver,
+            header_dep_resolver,
+            tx_dep_provider,
+        )?;
+        let mut cell_deps: Vec<CellDep> = tx.cell_deps().into_iter().collect();
+        for script in self.extension_scripts {
+            let cell_dep = cell_dep_resolver
+                .resolve(script)
+                .ok_or_else(|| TxBuilderError::ResolveCellDepFailed(script.clone()))?;
+            if !cell_deps.contains(&cell_dep) {
+                cell_deps.push(cell_dep);
+            }
+        }
+        Ok(tx.as_advanced_builder().set_cell_deps(cell_deps).build())
+    }
+}
 
 pub struct UdtTxBuilder<'a> {
     pub plugin_mgr: &'a mut PluginManager,
@@ -1249,22 +2715,127 @@ pub struct UdtTxBuilder<'a> {
     pub header_dep_resolver: &'a DefaultHeaderDepResolver,
     pub tx_dep_provider: &'a DefaultTransactionDependencyProvider,
     pub builder: &'a dyn TxBuilder,
+    pub extension_scripts: &'a [Script],
 }
 
 impl<'a> UdtTxBuilder<'a> {
+    /// Build the balanced transaction without signing it (for `--dry-run`),
+    /// return the transaction and the index of the change output.
+    pub fn build_unsigned(
+        &mut self,
+        cell_deps: &CellDeps,
+        capacity_provider: Script,
+        acp_script_id: Option<ScriptId>,
+        cheque_script_id: Option<(ScriptId, ChequeAction)>,
+        fee_rate: u64,
+        force_small_change_as_fee: Option<u64>,
+    ) -> Result<(TransactionView, Option<usize>), String> {
+        // Only the placeholder witnesses are required
+        let get_signer = || Box::new(PrivkeySigner::new(Vec::new()));
+        let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
+        unlockers.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+            Box::new(SecpSighashUnlocker::new(SecpSighashScriptSigner::new(
+                get_signer(),
+            ))),
+        );
+        if let Some(script_id) = acp_script_id {
+            let acp_unlocker = AcpUnlocker::new(AcpScriptSigner::new(get_signer()));
+            unlockers.insert(script_id, Box::new(acp_unlocker));
+        }
+        if let Some((script_id, action)) = cheque_script_id {
+            let cheque_unlocker =
+                ChequeUnlocker::new(ChequeScriptSigner::new(get_signer(), action));
+            unlockers.insert(script_id, Box::new(cheque_unlocker));
+        }
+        let balancer = CapacityBalancer {
+            fee_rate: FeeRate::from_u64(fee_rate),
+            change_lock_script: None,
+            capacity_provider: CapacityProvider::new_simple(vec![(
+                capacity_provider,
+                WitnessArgs::new_builder()
+                    .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                    .build(),
+            )]),
+            force_small_change_as_fee,
+        };
+
+        cell_deps.apply_to_resolver(self.cell_dep_resolver)?;
+
+        let builder = ExtensionCellDepsBuilder {
+            builder: self.builder,
+            extension_scripts: self.extension_scripts,
+        };
+        // The change output is appended after the outputs of the base transaction
+        let base_tx = builder
+            .build_base(
+                &mut self.cell_collector.clone(),
+                self.cell_dep_resolver,
+                self.header_dep_resolver,
+                self.tx_dep_provider,
+            )
+            .map_err(|err| err.to_string())?;
+        let tx = builder
+            .build_balanced(
+                &mut self.cell_collector.clone(),
+                self.cell_dep_resolver,
+                self.header_dep_resolver,
+                self.tx_dep_provider,
+                &balancer,
+                &unlockers,
+            )
+            .map_err(|err| {
+                map_tx_builder_error_2_str(balancer.force_small_change_as_fee.is_none(), err)
+            })?;
+        let change_index =
+            Some(base_tx.outputs().len()).filter(|index| *index < tx.outputs().len());
+        Ok((tx, change_index))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn build(
         &mut self,
         accounts: Vec<(String, H160)>,
         privkeys: Vec<PrivkeyWrapper>,
+        external_signer: Option<ExternalSigner>,
+        cell_deps: &CellDeps,
+        capacity_provider: Script,
+        acp_script_id: Option<ScriptId>,
+        cheque_script_id: Option<(ScriptId, ChequeAction)>,
+        fee_rate: u64,
+        force_small_change_as_fee: Option<u64>,
+    ) -> Result<TransactionView, String> {
+        let mut passwords = HashMap::with_capacity(accounts.len());
+        self.build_with_passwords(
+            accounts,
+            privkeys,
+            external_signer,
+            cell_deps,
+            capacity_provider,
+            acp_script_id,
+            cheque_script_id,
+            fee_rate,
+            force_small_change_as_fee,
+            &mut passwords,
+        )
+    }
+
+    /// Same as `build`, the keystore passwords are cached in `passwords` for
+    /// building multiple transactions.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_with_passwords(
+        &mut self,
+        accounts: Vec<(String, H160)>,
+        privkeys: Vec<PrivkeyWrapper>,
+        external_signer: Option<ExternalSigner>,
         cell_deps: &CellDeps,
         capacity_provider: Script,
         acp_script_id: Option<ScriptId>,
         cheque_script_id: Option<(ScriptId, ChequeAction)>,
         fee_rate: u64,
         force_small_change_as_fee: Option<u64>,
+        passwords: &mut HashMap<H160, String>,
     ) -> Result<TransactionView, String> {
-        let mut passwords: HashMap<H160, String> = HashMap::with_capacity(accounts.len());
         let sighash_script_id = ScriptId::new_type(SIGHASH_TYPE_HASH.clone());
         let mut get_signer = || -> Result<Box<dyn Signer>, String> {
             let handler = self.plugin_mgr.keystore_handler();
@@ -1276,12 +2847,20 @@ impl<'a> UdtTxBuilder<'a> {
                 )),
             );
             let mut privkey_signer = PrivkeySigner::new(privkeys.clone());
+            let mut external_signer = external_signer.clone();
             for (name, account) in accounts.clone() {
                 if privkey_signer.has_account(&account) {
                     if cheque_script_id.is_some() {
                         let _ = privkey_signer
                             .cache_account_lock_hash160(account.clone(), &sighash_script_id);
                     }
+                } else if let Some(signer) = external_signer
+                    .as_mut()
+                    .filter(|signer| signer.has_account(&account))
+                {
+                    if cheque_script_id.is_some() {
+                        let _ = signer.cache_account_lock_hash160(account, &sighash_script_id);
+                    }
                 } else {
                     if !handler.has_account(account.clone()).unwrap_or_default() {
                         return Err(format!("no such account in keystore: {}", name));
@@ -1306,10 +2885,12 @@ impl<'a> UdtTxBuilder<'a> {
                     keystore_signer.set_change_path(account, change_path.to_string());
                 }
             }
-            Ok(Box::new(CommonSigner::new(vec![
-                Box::new(privkey_signer),
-                Box::new(keystore_signer),
-            ])))
+            let mut signers: Vec<Box<dyn Signer>> =
+                vec![Box::new(privkey_signer), Box::new(keystore_signer)];
+            if let Some(signer) = external_signer {
+                signers.push(Box::new(signer));
+            }
+            Ok(Box::new(CommonSigner::new(signers)))
         };
 
         let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
@@ -1340,8 +2921,11 @@ impl<'a> UdtTxBuilder<'a> {
 
         cell_deps.apply_to_resolver(self.cell_dep_resolver)?;
 
-        let (tx, still_locked_groups) = self
-            .builder
+        let builder = ExtensionCellDepsBuilder {
+            builder: self.builder,
+            extension_scripts: self.extension_scripts,
+        };
+        let (tx, still_locked_groups) = builder
             .build_unlocked(
                 self.cell_collector,
                 self.cell_dep_resolver,
@@ -1363,3 +2947,28 @@ impl<'a> UdtTxBuilder<'a> {
         Ok(tx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_xudt_extra_args() {
+        assert!(build_xudt_extra_args(0, &[]).is_empty());
+        assert_eq!(
+            build_xudt_extra_args(XUDT_OWNER_MODE_INPUT_TYPE, &[]).as_ref(),
+            &[0x00, 0x00, 0x00, 0x80]
+        );
+
+        let extension = Script::new_builder()
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(vec![0u8; 20]).pack())
+            .build();
+        let args = build_xudt_extra_args(XUDT_OWNER_MODE_NO_INPUT_LOCK, &[extension.clone()]);
+        assert_eq!(&args[0..4], &[0x01, 0x00, 0x00, 0x20]);
+        let scripts = ScriptVec::from_slice(&args[4..]).unwrap();
+        assert_eq!(scripts.len(), 1);
+        assert_eq!(scripts.get(0).unwrap(), extension);
+    }
+}
@@ -1,3 +1,4 @@
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fs;
@@ -5,18 +6,29 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use ckb_hash::blake2b_256;
 use ckb_jsonrpc_types as json_types;
 use ckb_jsonrpc_types::JsonBytes;
+use ckb_mock_tx_types::{MockTransaction, ReprMockTransaction};
 use ckb_sdk::{
-    constants::{MULTISIG_TYPE_HASH, SECP_SIGNATURE_SIZE},
-    unlock::MultisigConfig,
-    Address, AddressPayload, HumanCapacity, NetworkType,
+    constants::{MULTISIG_TYPE_HASH, SECP_SIGNATURE_SIZE, SIGHASH_TYPE_HASH, TYPE_ID_CODE_HASH},
+    traits::{
+        CellCollector, CellQueryOptions, DefaultCellCollector, MaturityOption, Signer,
+        TransactionDependencyProvider, ValueRangeOption,
+    },
+    tx_builder::unlock_tx,
+    types::ScriptId,
+    unlock::{
+        MultisigConfig, ScriptUnlocker, SecpMultisigScriptSigner, SecpMultisigUnlocker,
+        SecpSighashScriptSigner, SecpSighashUnlocker,
+    },
+    Address, AddressPayload, HumanCapacity, NetworkType, Since, SinceType, SECP256K1,
 };
 use ckb_types::{
     bytes::Bytes,
-    core::Capacity,
+    core::{self, Capacity, DepType, EpochNumberWithFraction, FeeRate, ScriptHashType},
     h256,
-    packed::{self, CellOutput, OutPoint, Script},
+    packed::{self, CellDep, CellOutput, OutPoint, Script},
     prelude::*,
     H160, H256,
 };
@@ -24,23 +36,41 @@ use clap::{App, Arg, ArgMatches};
 use faster_hex::hex_string;
 use serde_derive::{Deserialize, Serialize};
 
-use super::{CliSubCommand, Output};
-use crate::plugin::{KeyStoreHandler, PluginManager, SignTarget};
+use super::{
+    mock_tx::verify_script_groups, sudt::arg_cell_deps, wallet::DERIVE_CHANGE_ADDRESS_MAX_LEN,
+    CliSubCommand, Output,
+};
+use crate::plugin::{KeyStoreHandler, PluginManager, SignTarget, UnlockerRequest};
 use crate::utils::{
     arg,
     arg_parser::{
-        AddressParser, ArgParser, CapacityParser, FilePathParser, FixedHashParser, FromStrParser,
-        HexParser, PrivkeyPathParser, PrivkeyWrapper,
+        AddressParser, ArgParser, CapacityParser, CellDepsParser, FilePathParser, FixedHashParser,
+        FromStrParser, HexParser, OutPointParser, PrivkeyPathParser, PrivkeyWrapper, ScriptParser,
+        SinceParser,
     },
+    cell_dep::{CellDepName, CellDeps},
     genesis_info::GenesisInfo,
+    mock_tx_helper::MockTransactionDependencyProvider,
     other::{
-        check_capacity, get_genesis_info, get_live_cell, get_live_cell_with_cache,
-        get_network_type, get_privkey_signer, get_to_data, read_password,
+        calculate_type_id, get_external_signer, get_genesis_info, get_live_cell,
+        get_live_cell_with_cache, get_network_type, get_privkey_signer, get_to_data, read_password,
+        since_json,
     },
     rpc::HttpRpcClient,
-    tx_helper::{SignerFn, TxHelper},
+    signer::{ExternalSigner, KeyStoreHandlerSigner},
+    tx_chunks::MAX_TX_VERIFY_CYCLES,
+    tx_explain::{explain_tx, ResolvedCell},
+    tx_helper::{LockGroupStatus, SignerFn, TxHelper},
+    tx_template::TxTemplate,
 };
 
+/// Version of the transaction file format:
+///   * 0: the transaction, multisig configs and signatures
+///   * 1: also embed the input cells (with block headers) and the signing status
+pub(crate) const TX_FILE_VERSION: u32 = 1;
+// Warn when the transaction size or cycles reach this percent of the limit
+const BUDGET_WARNING_PERCENT: u64 = 90;
+
 pub struct TxSubCommand<'a> {
     rpc_client: &'a mut HttpRpcClient,
     plugin_mgr: &'a mut PluginManager,
@@ -114,7 +144,13 @@ impl<'a> TxSubCommand<'a> {
                             .long("field")
                             .takes_value(true)
                             .required(true)
-                            .possible_values(&["inputs", "outputs", "signatures"])
+                            .possible_values(&[
+                                "inputs",
+                                "outputs",
+                                "signatures",
+                                "cell-deps",
+                                "header-deps",
+                            ])
                             .about("The transaction field"),
                     )
                     .arg(arg_tx_file.clone()),
@@ -136,7 +172,20 @@ impl<'a> TxSubCommand<'a> {
                             .required(true)
                             .about("Transaction output index"),
                     )
-                    .arg(arg_since_absolute_epoch.clone())
+                    .arg(
+                        arg_since_absolute_epoch
+                            .clone()
+                            .conflicts_with("since"),
+                    )
+                    .arg(
+                        Arg::with_name("since")
+                            .long("since")
+                            .takes_value(true)
+                            .validator(|input| SinceParser.validate(input))
+                            .about(
+                                "The since of the input, format: 0x{raw hex} or [absolute:|relative:]{epoch:number[,index,length]|block:number|timestamp:seconds} (absolute by default), see `util since`",
+                            ),
+                    )
                     .arg(arg_tx_file.clone())
                     .arg(arg_skip_check.clone()),
                 App::new("add-output")
@@ -147,6 +196,7 @@ impl<'a> TxSubCommand<'a> {
                             .conflicts_with_all(&[
                                 "to-short-multisig-address",
                                 "to-long-multisig-address",
+                                "to-address",
                             ])
                             .takes_value(true)
                             .validator(|input| AddressParser::new_sighash().validate(input))
@@ -155,7 +205,7 @@ impl<'a> TxSubCommand<'a> {
                     .arg(
                         Arg::with_name("to-short-multisig-address")
                             .long("to-short-multisig-address")
-                            .conflicts_with("to-long-multisig-address")
+                            .conflicts_with_all(&["to-long-multisig-address", "to-address"])
                             .takes_value(true)
                             .validator(|input| AddressParser::new_multisig().validate(input))
                             .about("To short multisig address"),
@@ -163,14 +213,83 @@ impl<'a> TxSubCommand<'a> {
                     .arg(
                         Arg::with_name("to-long-multisig-address")
                             .long("to-long-multisig-address")
+                            .conflicts_with("to-address")
                             .takes_value(true)
                             .validator(|input| AddressParser::new_multisig().validate(input))
                             .about("To long multisig address (special case, include since)"),
                     )
+                    .arg(arg::to_address().about("To any address (include full format address with any lock script)"))
+                    .arg(
+                        Arg::with_name("type-script")
+                            .long("type-script")
+                            .takes_value(true)
+                            .validator(|input| ScriptParser.validate(input))
+                            .about("The type script of the output, format: json or {code_hash}-{hash_type}-{args}, `hash_type` can be: [type, data, data1, data2]"),
+                    )
+                    .arg(
+                        Arg::with_name("type-id")
+                            .long("type-id")
+                            .conflicts_with("type-script")
+                            .about("Add type id type script to the output (calculated from the first input, so add the inputs first)"),
+                    )
                     .arg(arg::capacity().required(true))
                     .arg(arg::to_data())
                     .arg(arg::to_data_path())
                     .arg(arg_tx_file.clone()),
+                App::new("balance")
+                    .about("Add or adjust the change output to pay the transaction fee by fee rate, more inputs are collected from the change address when the capacity is not enough")
+                    .arg(
+                        Arg::with_name("change-address")
+                            .long("change-address")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| AddressParser::default().validate(input))
+                            .about("The change address (sighash/multisig), the last plain capacity output of it is the change output"),
+                    )
+                    .arg(arg::fee_rate())
+                    .arg(arg_tx_file.clone()),
+                App::new("add-cell-dep")
+                    .about("Add cell dep by out point or by name from the cell deps file")
+                    .arg(
+                        arg::out_point()
+                            .required_unless("name")
+                            .conflicts_with("name")
+                            .about("The out point of the cell dep"),
+                    )
+                    .arg(
+                        Arg::with_name("dep-type")
+                            .long("dep-type")
+                            .takes_value(true)
+                            .possible_values(&["code", "dep_group"])
+                            .default_value("code")
+                            .about("The dep type of <out-point>"),
+                    )
+                    .arg(
+                        Arg::with_name("name")
+                            .long("name")
+                            .takes_value(true)
+                            .possible_values(&CellDepName::NAMES)
+                            .requires("cell-deps")
+                            .about("The name of the cell dep item in <cell-deps> file"),
+                    )
+                    .arg(arg_cell_deps().required(false))
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("skip-check")
+                            .long("skip-check")
+                            .about("Do not check the cell dep is live"),
+                    ),
+                App::new("add-header-dep")
+                    .about("Add header dep by block hash")
+                    .arg(
+                        Arg::with_name("block-hash")
+                            .long("block-hash")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .about("The block hash"),
+                    )
+                    .arg(arg_tx_file.clone()),
                 App::new("add-signature")
                     .about("Add signature")
                     .arg(
@@ -199,19 +318,91 @@ impl<'a> TxSubCommand<'a> {
                     )
                     .arg(arg_tx_file.clone()),
                 App::new("info")
-                    .about("Show detail of this multisig transaction (capacity, tx-fee, etc.)")
+                    .about("Show detail of this multisig transaction (capacity, tx-fee, size, cycles of each script group, etc.)")
                     .arg(arg_tx_file.clone()),
+                App::new("status")
+                    .about("Show the signing requirements of each input lock and what is still missing before the transaction can be sent")
+                    .arg(arg_tx_file.clone()),
+                App::new("explain")
+                    .about("Explain any transaction: the input/output cells, the well known scripts (sighash, multisig, dao, sudt, acp, cheque, type_id), the balance changes of each lock and the fee")
+                    .arg(
+                        Arg::with_name("tx-hash")
+                            .long("tx-hash")
+                            .takes_value(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .required_unless("tx-file")
+                            .conflicts_with("tx-file")
+                            .about("Transaction hash (pending or committed)"),
+                    )
+                    .arg(
+                        arg_tx_file
+                            .clone()
+                            .required(false)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .about("Transaction file (format: json), the transaction file of `tx` subcommand, the mock transaction file or the transaction in json"),
+                    )
+                    .arg(
+                        arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information (for naming sudt/acp/cheque scripts)"),
+                    ),
+                App::new("template")
+                    .about("Parameterized transaction templates (format: toml)")
+                    .subcommands(vec![App::new("render")
+                        .about("Render the transaction template into a transaction file ready for signing, the inputs are collected from the `from` address of the template")
+                        .arg(
+                            Arg::with_name("template")
+                                .long("template")
+                                .takes_value(true)
+                                .required(true)
+                                .validator(|input| FilePathParser::new(true).validate(input))
+                                .about("The transaction template file (format: toml), the `${name}` variables are replaced by <var>"),
+                        )
+                        .arg(
+                            Arg::with_name("var")
+                                .long("var")
+                                .takes_value(true)
+                                .multiple(true)
+                                .validator(|input| parse_template_var(input).map(|_| ()))
+                                .about("The template variable (format: {name}={value}), can be given multiple times"),
+                        )
+                        .arg(arg_tx_file.clone().about("The output transaction file (format: json)"))]),
+                App::new("combine")
+                    .about("Combine the signatures of the multisig transaction files signed independently")
+                    .arg(
+                        arg_tx_file
+                            .clone()
+                            .multiple(true)
+                            .about("Multisig transaction data files of the same transaction (format: json)"),
+                    )
+                    .arg(
+                        Arg::with_name("output-file")
+                            .long("output-file")
+                            .alias("output")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Save the combined transaction to this file"),
+                    ),
                 App::new("sign-inputs")
-                    .about("Sign all sighash/multisig inputs in this transaction")
-                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
-                    .arg(arg::from_account().required_unless(arg::privkey_path().get_name()))
+                    .about("Sign all sighash/multisig inputs in this transaction, other inputs are unlocked by the unlocker plugins")
+                    .arg(arg::privkey_path().required_unless_one(&["from-account", "signer-cmd"]))
+                    .arg(arg::from_account().required_unless_one(&["privkey-path", "signer-cmd"]))
+                    .arg(
+                        arg::signer_cmd()
+                            .conflicts_with_all(&["privkey-path", "from-account"]),
+                    )
                     .arg(arg_tx_file.clone())
                     .arg(
                         Arg::with_name("add-signatures")
                             .long("add-signatures")
                             .about("Sign and add signatures"),
                     )
-                    .arg(arg_skip_check.clone()),
+                    .arg(
+                        Arg::with_name("skip-check")
+                            .long("skip-check")
+                            .about("Skip the inputs of other lock scripts without an unlocker plugin"),
+                    ),
                 App::new("send")
                     .about("Send multisig transaction")
                     .arg(arg_tx_file.clone())
@@ -223,7 +414,7 @@ impl<'a> TxSubCommand<'a> {
                             .validator(|input| CapacityParser.validate(input))
                             .about("Max transaction fee (unit: CKB)"),
                     )
-                    .arg(arg_skip_check),
+                    .arg(arg_skip_check.clone()),
                 App::new("build-multisig-address")
                     .about(
                         "Build multisig address with multisig config and since(optional) argument",
@@ -232,13 +423,58 @@ impl<'a> TxSubCommand<'a> {
                     .arg(arg_require_first_n.clone())
                     .arg(arg_threshold.clone())
                     .arg(arg_since_absolute_epoch.clone()),
+                App::new("sign-file")
+                    .about("Sign an unsigned transaction file built by `wallet transfer --build-only` (offline, only keystore is required)")
+                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .required_unless(arg::privkey_path().get_name())
+                            .conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(arg::derive_receiving_address_length())
+                    .arg(
+                        arg::derive_change_address().conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(
+                        arg_tx_file
+                            .clone()
+                            .about("Unsigned transaction file (format: json)"),
+                    )
+                    .arg(
+                        Arg::with_name("output-file")
+                            .long("output-file")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Save the signed transaction to this file (default: overwrite <tx-file>)"),
+                    ),
+                App::new("broadcast")
+                    .about("Send a signed transaction file built by `wallet transfer --build-only` (only RPC is required)")
+                    .arg(
+                        arg_tx_file
+                            .clone()
+                            .about("Signed transaction file (format: json)"),
+                    )
+                    .arg(
+                        Arg::with_name("max-tx-fee")
+                            .long("max-tx-fee")
+                            .takes_value(true)
+                            .default_value("1.0")
+                            .validator(|input| CapacityParser.validate(input))
+                            .about("Max transaction fee (unit: CKB)"),
+                    )
+                    .arg(arg_skip_check),
             ])
     }
 }
 
 impl<'a> CliSubCommand for TxSubCommand<'a> {
     fn process(&mut self, matches: &ArgMatches, debug: bool) -> Result<Output, String> {
+        // Signing a transaction file is an offline operation
+        if let ("sign-file", Some(m)) = matches.subcommand() {
+            return self.sign_file(m);
+        }
         let network = get_network_type(self.rpc_client)?;
+        let rpc_url = self.rpc_client.url().to_owned();
 
         match matches.subcommand() {
             ("init", Some(m)) => {
@@ -261,11 +497,13 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
             ("clear-field", Some(m)) => {
                 let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
                 let field = m.value_of("field").unwrap();
-                modify_tx_file(&tx_file, network, |helper| {
+                modify_tx_file(&tx_file, network, self.rpc_client, |_, helper| {
                     match field {
                         "inputs" => helper.clear_inputs(),
                         "outputs" => helper.clear_outputs(),
                         "signatures" => helper.clear_signatures(),
+                        "cell-deps" => helper.clear_cell_deps(),
+                        "header-deps" => helper.clear_header_deps(),
                         _ => panic!("Invalid clear field: {}", field),
                     }
                     Ok(())
@@ -279,6 +517,10 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let index: u32 = FromStrParser::<u32>::default().from_matches(m, "index")?;
                 let since_absolute_epoch_opt: Option<u64> =
                     FromStrParser::<u64>::default().from_matches_opt(m, "since-absolute-epoch")?;
+                let since_opt: Option<u64> = SinceParser.from_matches_opt(m, "since")?;
+                let since_opt = since_opt.or_else(|| {
+                    since_absolute_epoch_opt.map(|number| Since::new_absolute_epoch(number).value())
+                });
 
                 let skip_check: bool = m.is_present("skip-check");
                 let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
@@ -286,13 +528,13 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .tx_hash(tx_hash.pack())
                     .index(index.pack())
                     .build();
-                let get_live_cell = |out_point, with_data| {
-                    get_live_cell(self.rpc_client, out_point, with_data).map(|(output, _)| output)
-                };
-                modify_tx_file(&tx_file, network, |helper| {
+                modify_tx_file(&tx_file, network, self.rpc_client, |rpc_client, helper| {
+                    let get_live_cell = |out_point, with_data| {
+                        get_live_cell(rpc_client, out_point, with_data).map(|(output, _)| output)
+                    };
                     helper.add_input(
                         out_point,
-                        since_absolute_epoch_opt,
+                        since_opt,
                         get_live_cell,
                         &genesis_info,
                         skip_check,
@@ -311,9 +553,13 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let to_long_multisig_address_opt: Option<Address> =
                     AddressParser::new_multisig()
                         .from_matches_opt(m, "to-long-multisig-address")?;
+                let to_address_opt: Option<Address> =
+                    AddressParser::default().from_matches_opt(m, "to-address")?;
+                let type_script_opt: Option<Script> =
+                    ScriptParser.from_matches_opt(m, "type-script")?;
+                let is_type_id = m.is_present("type-id");
 
                 let to_data = get_to_data(m)?;
-                check_capacity(capacity, to_data.len())?;
                 if let Some(address) = to_long_multisig_address_opt.as_ref() {
                     let payload = address.payload();
                     if payload.args().len() != 28 {
@@ -326,6 +572,7 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let lock_script = to_sighash_address_opt
                     .or(to_short_multisig_address_opt)
                     .or(to_long_multisig_address_opt)
+                    .or(to_address_opt)
                     .map(|address| Script::from(address.payload()))
                     .ok_or_else(|| "missing target address".to_string())?;
                 let output = CellOutput::new_builder()
@@ -333,19 +580,131 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .lock(lock_script)
                     .build();
 
-                modify_tx_file(&tx_file, network, |helper| {
+                modify_tx_file(&tx_file, network, self.rpc_client, |_, helper| {
+                    let type_script_opt = if is_type_id {
+                        let tx = helper.transaction();
+                        let first_input = tx.inputs().get(0).ok_or_else(|| {
+                            "Type id requires at least one input, add the inputs first".to_string()
+                        })?;
+                        let type_id = calculate_type_id(&first_input, tx.outputs().len() as u64);
+                        Some(
+                            Script::new_builder()
+                                .code_hash(TYPE_ID_CODE_HASH.pack())
+                                .hash_type(ScriptHashType::Type.into())
+                                .args(Bytes::from(type_id.to_vec()).pack())
+                                .build(),
+                        )
+                    } else {
+                        type_script_opt
+                    };
+                    let output = output.as_builder().type_(type_script_opt.pack()).build();
+                    let occupied_capacity = output
+                        .occupied_capacity(Capacity::bytes(to_data.len()).unwrap())
+                        .map_err(|err| err.to_string())?
+                        .as_u64();
+                    if capacity < occupied_capacity {
+                        return Err(format!(
+                            "Capacity {:#} is less than the occupied capacity {:#}",
+                            HumanCapacity(capacity),
+                            HumanCapacity(occupied_capacity),
+                        ));
+                    }
                     helper.add_output(output, to_data);
                     Ok(())
                 })?;
 
                 Ok(Output::new_success())
             }
+            ("balance", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let change_address: Address = AddressParser::default()
+                    .set_network(network)
+                    .from_matches(m, "change-address")?;
+                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+
+                let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+                let change_lock = Script::from(change_address.payload());
+                let mut cell_collector = DefaultCellCollector::new(&rpc_url);
+                let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
+                    Default::default();
+                let resp =
+                    modify_tx_file(&tx_file, network, self.rpc_client, |rpc_client, helper| {
+                        let get_live_cell = |out_point: OutPoint, with_data: bool| {
+                            get_live_cell_with_cache(
+                                &mut live_cell_cache,
+                                rpc_client,
+                                out_point,
+                                with_data,
+                            )
+                            .map(|(output, _)| output)
+                        };
+                        balance_tx(
+                            helper,
+                            &change_lock,
+                            fee_rate,
+                            &mut cell_collector,
+                            &genesis_info,
+                            get_live_cell,
+                        )
+                    })?;
+                Ok(Output::new_output(resp))
+            }
+            ("add-cell-dep", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let skip_check: bool = m.is_present("skip-check");
+                let cell_dep = if let Some(name) = m.value_of("name") {
+                    let name = CellDepName::from_str(name)?;
+                    let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
+                    let item = cell_deps.get_item(name).ok_or_else(|| {
+                        format!("Cell dep item not found in <cell-deps> file: {}", name)
+                    })?;
+                    CellDep::from(item.cell_dep.clone())
+                } else {
+                    let out_point: OutPoint = OutPointParser.from_matches(m, "out-point")?;
+                    let dep_type = match m.value_of("dep-type") {
+                        Some("dep_group") => DepType::DepGroup,
+                        _ => DepType::Code,
+                    };
+                    CellDep::new_builder()
+                        .out_point(out_point)
+                        .dep_type(dep_type.into())
+                        .build()
+                };
+                if !skip_check {
+                    let status = cell_status(self.rpc_client, cell_dep.out_point())?;
+                    if status != "live" {
+                        return Err(format!(
+                            "Invalid cell dep status: {}, out_point: {}",
+                            status,
+                            cell_dep.out_point()
+                        ));
+                    }
+                }
+                modify_tx_file(&tx_file, network, self.rpc_client, |_, helper| {
+                    helper.add_cell_dep(cell_dep);
+                    Ok(())
+                })?;
+                Ok(Output::new_success())
+            }
+            ("add-header-dep", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let block_hash: H256 =
+                    FixedHashParser::<H256>::default().from_matches(m, "block-hash")?;
+                if self.rpc_client.get_header(block_hash.clone())?.is_none() {
+                    return Err(format!("Block not found: {:#x}", block_hash));
+                }
+                modify_tx_file(&tx_file, network, self.rpc_client, |_, helper| {
+                    helper.add_header_dep(block_hash.pack());
+                    Ok(())
+                })?;
+                Ok(Output::new_success())
+            }
             ("add-signature", Some(m)) => {
                 let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
                 let lock_arg: Bytes = HexParser.from_matches(m, "lock-arg")?;
                 let signature: Bytes = HexParser.from_matches(m, "signature")?;
 
-                modify_tx_file(&tx_file, network, |helper| {
+                modify_tx_file(&tx_file, network, self.rpc_client, |_, helper| {
                     helper.add_signature(lock_arg, signature)
                 })?;
                 Ok(Output::new_success())
@@ -365,7 +724,7 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .collect::<Vec<_>>();
                 let cfg = MultisigConfig::new_with(sighash_addresses, require_first_n, threshold)
                     .map_err(|err| err.to_string())?;
-                modify_tx_file(&tx_file, network, |helper| {
+                modify_tx_file(&tx_file, network, self.rpc_client, |_, helper| {
                     helper.add_multisig_config(cfg);
                     Ok(())
                 })?;
@@ -388,6 +747,7 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
                 let repr: ReprTxHelper =
                     serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+                let mut input_cells = repr.input_cells();
                 let helper = TxHelper::try_from(repr)?;
                 let tx = helper.transaction();
 
@@ -416,6 +776,36 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     );
                 }
 
+                // Check the since of the inputs against the tip block, the
+                // header of the input cell is required by relative since
+                let tip_header: core::HeaderView = self.rpc_client.get_tip_header()?.into();
+                let mut inputs = Vec::new();
+                for input in tx.inputs().into_iter() {
+                    let since: u64 = input.since().unpack();
+                    let out_point = input.previous_output();
+                    let input_header: Option<core::HeaderView> =
+                        if since != 0 && Since::from_raw_value(since).is_relative() {
+                            let cell = match input_cells.remove(&out_point) {
+                                Some(cell) => cell,
+                                None => load_input_cell(self.rpc_client, out_point.clone())?,
+                            };
+                            cell.header.map(Into::into)
+                        } else {
+                            None
+                        };
+                    let mut since_info = since_json(since);
+                    since_info["spendable"] = serde_json::json!(since_satisfied(
+                        self.rpc_client,
+                        since,
+                        input_header.as_ref(),
+                        &tip_header
+                    )?);
+                    inputs.push(serde_json::json!({
+                        "out_point": json_types::OutPoint::from(out_point),
+                        "since": since_info,
+                    }));
+                }
+
                 let mut output_total = 0;
                 for (output, data) in tx.outputs().into_iter().zip(tx.outputs_data().into_iter()) {
                     let capacity: u64 = output.capacity().unpack();
@@ -437,10 +827,359 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     format!("-{:#}", HumanCapacity(output_total - input_total))
                 };
 
+                let mut cell_deps = Vec::new();
+                for cell_dep in tx.cell_deps().into_iter() {
+                    let status = cell_status(self.rpc_client, cell_dep.out_point())?;
+                    if status != "live" {
+                        eprintln!(
+                            "[cell-dep] {} is not live (status: {})",
+                            cell_dep.out_point(),
+                            status
+                        );
+                    }
+                    let cell_dep = json_types::CellDep::from(cell_dep);
+                    cell_deps.push(serde_json::json!({
+                        "out_point": cell_dep.out_point,
+                        "dep_type": cell_dep.dep_type,
+                        "status": status,
+                    }));
+                }
+                let mut header_deps = Vec::new();
+                for block_hash in tx.header_deps().into_iter() {
+                    let block_hash: H256 = block_hash.unpack();
+                    let header_opt = self.rpc_client.get_header(block_hash.clone())?;
+                    if header_opt.is_none() {
+                        eprintln!("[header-dep] {:#x} is not found", block_hash);
+                    }
+                    header_deps.push(serde_json::json!({
+                        "block_hash": block_hash,
+                        "block_number": header_opt.map(|header| header.inner.number),
+                    }));
+                }
+
+                // The unsigned lock groups are kept empty, the scripts of them
+                // are expected to fail
+                let mut get_input_cell = |out_point: OutPoint, with_data: bool| {
+                    get_live_cell_with_cache(
+                        &mut live_cell_cache,
+                        self.rpc_client,
+                        out_point,
+                        with_data,
+                    )
+                    .map(|(output, _)| output)
+                };
+                let signed_tx = helper.build_tx(&mut get_input_cell, true)?;
+                let (placeholder_tx, unknown_witness_inputs) =
+                    helper.build_placeholder_tx(&mut get_input_cell)?;
+                let budget = tx_budget(
+                    self.rpc_client,
+                    &signed_tx,
+                    &placeholder_tx,
+                    &unknown_witness_inputs,
+                )?;
+
                 let resp = serde_json::json!({
                     "input_total": format!("{:#}", HumanCapacity(input_total)),
                     "output_total": format!("{:#}", HumanCapacity(output_total)),
                     "tx_fee": tx_fee_string,
+                    "inputs": inputs,
+                    "cell_deps": cell_deps,
+                    "header_deps": header_deps,
+                    "size": budget["size"],
+                    "cycles": budget["cycles"],
+                });
+                Ok(Output::new_output(resp))
+            }
+            ("status", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+
+                let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
+                let repr: ReprTxHelper =
+                    serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+                let version = repr.version;
+                let input_cells = repr.input_cells();
+                let helper = TxHelper::try_from(repr)?;
+                let tx = helper.transaction();
+                let (inputs, embedded_inputs) = load_input_cells(self.rpc_client, tx, input_cells)?;
+                let cells = ReprInputCell::cell_map(&inputs);
+                let lock_groups = helper.signing_status(|out_point, _with_data| {
+                    cells
+                        .get(&out_point)
+                        .cloned()
+                        .ok_or_else(|| format!("Input cell not found: {}", out_point))
+                })?;
+                let tip_header: core::HeaderView = self.rpc_client.get_tip_header()?.into();
+
+                let mut missing = Vec::new();
+                if tx.inputs().is_empty() {
+                    missing.push("No input".to_string());
+                }
+                if tx.outputs().is_empty() {
+                    missing.push("No output".to_string());
+                }
+                let input_total: u64 = inputs.iter().map(|cell| cell.output.capacity.value()).sum();
+                let output_total = tx
+                    .outputs_capacity()
+                    .map_err(|err| err.to_string())?
+                    .as_u64();
+                if input_total < output_total {
+                    missing.push(format!(
+                        "Input capacity not enough: input={:#}, output={:#}",
+                        HumanCapacity(input_total),
+                        HumanCapacity(output_total),
+                    ));
+                }
+                for status in &lock_groups {
+                    missing.extend(lock_group_missing(status, network));
+                    for (idx, since) in status.input_idxs.iter().zip(status.since.iter()) {
+                        let input_header: Option<core::HeaderView> =
+                            inputs[*idx].header.clone().map(Into::into);
+                        if since_satisfied(
+                            self.rpc_client,
+                            *since,
+                            input_header.as_ref(),
+                            &tip_header,
+                        )? == Some(false)
+                        {
+                            missing.push(format!(
+                                "Input(no.{}) since {:#x} is not satisfied yet",
+                                idx + 1,
+                                since
+                            ));
+                        }
+                    }
+                }
+
+                let tx_hash: H256 = tx.hash().unpack();
+                let resp = serde_json::json!({
+                    "version": version,
+                    "tx_hash": tx_hash,
+                    "embedded_inputs": embedded_inputs,
+                    "lock_groups": lock_groups
+                        .iter()
+                        .map(|status| ReprLockGroupStatus::new(status, network))
+                        .collect::<Vec<_>>(),
+                    "ready": missing.is_empty(),
+                    "missing": missing,
+                });
+                Ok(Output::new_output(resp))
+            }
+            ("explain", Some(m)) => {
+                let tx_hash_opt: Option<H256> =
+                    FixedHashParser::<H256>::default().from_matches_opt(m, "tx-hash")?;
+                let cell_deps_opt: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
+
+                let (tx, resolved_inputs, tx_status) = if let Some(tx_hash) = tx_hash_opt {
+                    let tx_with_status = self
+                        .rpc_client
+                        .get_transaction(tx_hash.clone())?
+                        .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?;
+                    let tx: packed::Transaction = tx_with_status
+                        .transaction
+                        .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?
+                        .inner
+                        .into();
+                    (
+                        tx.into_view(),
+                        HashMap::new(),
+                        Some(tx_with_status.tx_status),
+                    )
+                } else {
+                    let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                    let (tx, resolved_inputs) = load_explain_tx_file(&tx_file)?;
+                    (tx, resolved_inputs, None)
+                };
+                let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+                let mut resp = explain_tx(
+                    self.rpc_client,
+                    network,
+                    &genesis_info,
+                    cell_deps_opt.as_ref(),
+                    &tx,
+                    resolved_inputs,
+                )?;
+                if let Some(tx_status) = tx_status {
+                    resp["tx_status"] = serde_json::json!(tx_status);
+                }
+                Ok(Output::new_output(resp))
+            }
+            ("template", Some(m)) => match m.subcommand() {
+                ("render", Some(m)) => {
+                    let template_path: PathBuf =
+                        FilePathParser::new(true).from_matches(m, "template")?;
+                    let tx_file: PathBuf = FilePathParser::new(false).from_matches(m, "tx-file")?;
+                    let vars: HashMap<String, String> = m
+                        .values_of("var")
+                        .map(|values| values.map(parse_template_var).collect())
+                        .unwrap_or_else(|| Ok(HashMap::new()))?;
+
+                    let content =
+                        fs::read_to_string(template_path).map_err(|err| err.to_string())?;
+                    let template = TxTemplate::render(&content, &vars)?;
+                    let from_lock = template.sender_lock(network)?;
+                    let mut helper = TxHelper::default();
+                    for cell_dep in &template.cell_deps {
+                        helper.add_cell_dep(cell_dep.to_cell_dep()?);
+                    }
+                    for block_hash in &template.header_deps {
+                        let block_hash: H256 =
+                            FixedHashParser::<H256>::default().parse(block_hash)?;
+                        helper.add_header_dep(block_hash.pack());
+                    }
+                    for output in &template.outputs {
+                        let (output, data) = output.to_output(network)?;
+                        helper.add_output(output, data);
+                    }
+
+                    let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+                    let mut cell_collector = DefaultCellCollector::new(&rpc_url);
+                    let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
+                        Default::default();
+                    let get_live_cell = |out_point: OutPoint, with_data: bool| {
+                        get_live_cell_with_cache(
+                            &mut live_cell_cache,
+                            self.rpc_client,
+                            out_point,
+                            with_data,
+                        )
+                        .map(|(output, _)| output)
+                    };
+                    let resp = balance_tx(
+                        &mut helper,
+                        &from_lock,
+                        template.fee_rate,
+                        &mut cell_collector,
+                        &genesis_info,
+                        get_live_cell,
+                    )?;
+                    let (inputs, _) =
+                        load_input_cells(self.rpc_client, helper.transaction(), HashMap::new())?;
+                    let repr = ReprTxHelper::new_with_inputs(helper, network, inputs)?;
+                    save_tx_file(&tx_file, &repr)?;
+                    Ok(Output::new_output(resp))
+                }
+                _ => Err(Self::subcommand("tx").generate_usage()),
+            },
+            ("combine", Some(m)) => {
+                let tx_files: Vec<PathBuf> =
+                    FilePathParser::new(true).from_matches_vec(m, "tx-file")?;
+                let output_file: PathBuf =
+                    FilePathParser::new(false).from_matches(m, "output-file")?;
+
+                let mut input_cells = HashMap::default();
+                let mut base_tx: Option<(&PathBuf, core::TransactionView)> = None;
+                let mut multisig_configs = Vec::new();
+                let mut candidates: Vec<(Bytes, Bytes)> = Vec::new();
+                for tx_file in &tx_files {
+                    let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
+                    let repr: ReprTxHelper = serde_json::from_reader(&file)
+                        .map_err(|err| format!("{}: {}", tx_file.display(), err))?;
+                    input_cells.extend(repr.input_cells());
+                    let helper = TxHelper::try_from(repr)?;
+                    if let Some((base_file, tx)) = base_tx.as_ref() {
+                        let base_hash: H256 = tx.hash().unpack();
+                        let tx_hash: H256 = helper.transaction().hash().unpack();
+                        if tx_hash != base_hash {
+                            return Err(format!(
+                                "Transaction conflict: {} (tx_hash: {:#x}) and {} (tx_hash: {:#x}) are different transactions",
+                                base_file.display(),
+                                base_hash,
+                                tx_file.display(),
+                                tx_hash,
+                            ));
+                        }
+                    } else {
+                        base_tx = Some((tx_file, helper.transaction().clone()));
+                    }
+                    multisig_configs.extend(helper.multisig_configs().values().cloned());
+                    for (lock_arg, signatures) in helper.signatures() {
+                        for signature in signatures {
+                            candidates.push((lock_arg.clone(), signature.clone()));
+                        }
+                    }
+                }
+                let (_, tx) = base_tx.ok_or_else(|| "Missing <tx-file>".to_string())?;
+
+                let mut helper = TxHelper::new(tx);
+                for cfg in multisig_configs {
+                    helper.add_multisig_config(cfg);
+                }
+                for (lock_arg, signature) in candidates {
+                    helper.add_signature(lock_arg, signature)?;
+                }
+                let (inputs, _) =
+                    load_input_cells(self.rpc_client, helper.transaction(), input_cells)?;
+                let cells = ReprInputCell::cell_map(&inputs);
+                let lock_groups = helper.signing_status(|out_point, _with_data| {
+                    cells
+                        .get(&out_point)
+                        .cloned()
+                        .ok_or_else(|| format!("Input cell not found: {}", out_point))
+                })?;
+
+                // Only accept one valid signature of each signer
+                let mut rejected = Vec::new();
+                for (lock_arg, signatures) in helper.signatures().clone() {
+                    let status_opt = lock_groups
+                        .iter()
+                        .find(|status| status.lock.args().raw_data() == lock_arg);
+                    let mut signatures = signatures.into_iter().collect::<Vec<_>>();
+                    signatures.sort();
+                    let mut signed_by = HashSet::new();
+                    for signature in signatures {
+                        let reason = match status_opt {
+                            None => Some("No input with this lock_arg".to_string()),
+                            Some(status)
+                                if status.is_multisig() && status.multisig_config.is_none() =>
+                            {
+                                Some("Missing multisig config".to_string())
+                            }
+                            Some(status) => {
+                                let signer = status
+                                    .signatures
+                                    .iter()
+                                    .find(|(sig, _)| sig == &signature)
+                                    .and_then(|(_, signer)| signer.clone());
+                                match signer {
+                                    Some(signer) if status.signers().contains(&signer) => {
+                                        if signed_by.insert(signer.clone()) {
+                                            None
+                                        } else {
+                                            let payload = AddressPayload::from_pubkey_hash(signer);
+                                            Some(format!(
+                                                "Duplicated signature of {}",
+                                                Address::new(network, payload, false)
+                                            ))
+                                        }
+                                    }
+                                    _ => Some(
+                                        "Not signed by any signer of the lock script".to_string(),
+                                    ),
+                                }
+                            }
+                        };
+                        if let Some(reason) = reason {
+                            helper.remove_signature(&lock_arg, &signature);
+                            rejected.push(serde_json::json!({
+                                "lock-arg": format!("0x{}", hex_string(&lock_arg)),
+                                "signature": format!("0x{}", hex_string(&signature)),
+                                "reason": reason,
+                            }));
+                        }
+                    }
+                }
+
+                let tx_hash: H256 = helper.transaction().hash().unpack();
+                let signatures: usize = helper.signatures().values().map(HashSet::len).sum();
+                let repr = ReprTxHelper::new_with_inputs(helper, network, inputs)?;
+                save_tx_file(&output_file, &repr)?;
+                let resp = serde_json::json!({
+                    "tx_hash": tx_hash,
+                    "signatures": signatures,
+                    "rejected": rejected,
+                    "ready": !repr.signing_status.is_empty()
+                        && repr.signing_status.iter().all(|status| status.ready),
                 });
                 Ok(Output::new_output(resp))
             }
@@ -465,10 +1204,13 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                             })
                     })
                     .transpose()?;
-                let skip_check: bool = m.is_present("skip-check");
+                let signer_cmd: Option<PathBuf> =
+                    FilePathParser::new(true).from_matches_opt(m, "signer-cmd")?;
 
                 let mut signer = if let Some(privkey) = privkey_opt {
                     get_privkey_signer(privkey)
+                } else if let Some(cmd) = signer_cmd {
+                    get_external_signer(ExternalSigner::new(cmd)?)
                 } else {
                     let password = if self.plugin_mgr.keystore_require_password() {
                         Some(read_password(false, None)?)
@@ -481,28 +1223,35 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     get_keystore_signer(keystore, new_client, account, password)
                 };
 
+                let skip_check: bool = m.is_present("skip-check");
+                let mut unlocker =
+                    get_plugin_unlocker(self.plugin_mgr, "sign-inputs", false, !skip_check);
+
                 let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
                     Default::default();
-                let get_live_cell = |out_point: OutPoint, with_data: bool| {
-                    get_live_cell_with_cache(
-                        &mut live_cell_cache,
-                        self.rpc_client,
-                        out_point,
-                        with_data,
-                    )
-                    .map(|(output, _)| output)
-                };
-
-                let signatures = modify_tx_file(&tx_file, network, |helper| {
-                    let signatures = helper.sign_inputs(&mut signer, get_live_cell, skip_check)?;
-                    if m.is_present("add-signatures") {
-                        for (lock_arg, signature) in signatures.clone() {
-                            helper.add_signature(lock_arg, signature)?;
+                let (signatures, unlocked) =
+                    modify_tx_file(&tx_file, network, self.rpc_client, |rpc_client, helper| {
+                        let mut get_live_cell = |out_point: OutPoint, with_data: bool| {
+                            get_live_cell_with_cache(
+                                &mut live_cell_cache,
+                                rpc_client,
+                                out_point,
+                                with_data,
+                            )
+                            .map(|(output, _)| output)
+                        };
+                        // The other lock scripts are checked by the unlocker
+                        let signatures =
+                            helper.sign_inputs(&mut signer, &mut get_live_cell, true)?;
+                        if m.is_present("add-signatures") {
+                            for (lock_arg, signature) in signatures.clone() {
+                                helper.add_signature(lock_arg, signature)?;
+                            }
                         }
-                    }
-                    Ok(signatures)
-                })?;
-                let resp = signatures
+                        let unlocked = helper.unlock_inputs(&mut unlocker, &mut get_live_cell)?;
+                        Ok((signatures, unlocked))
+                    })?;
+                let mut resp = signatures
                     .into_iter()
                     .map(|(lock_arg, signature)| {
                         serde_json::json!({
@@ -511,6 +1260,13 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                         })
                     })
                     .collect::<Vec<_>>();
+                resp.extend(unlocked.into_iter().map(|lock| {
+                    let code_hash: H256 = lock.code_hash().unpack();
+                    serde_json::json!({
+                        "lock-hash": format!("{:#x}", lock.calc_script_hash()),
+                        "unlocked-by": self.plugin_mgr.unlockers().get(&code_hash),
+                    })
+                }));
                 Ok(Output::new_output(resp))
             }
             ("send", Some(m)) => {
@@ -533,17 +1289,30 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
                 let repr: ReprTxHelper =
                     serde_json::from_reader(&file).map_err(|err| err.to_string())?;
-                let helper = TxHelper::try_from(repr)?;
+                let mut helper = TxHelper::try_from(repr)?;
+                let mut unlocker =
+                    get_plugin_unlocker(self.plugin_mgr, "send", !skip_check, !skip_check);
+                helper.unlock_inputs(&mut unlocker, &mut get_live_cell)?;
 
                 if !skip_check {
-                    let (input_total, output_total) = helper.check_tx(&mut get_live_cell)?;
-                    let tx_fee = input_total - output_total;
-                    if tx_fee > max_tx_fee {
-                        return Err(format!(
-                            "Too much transaction fee: {:#}, max: {:#}",
-                            HumanCapacity(tx_fee),
-                            HumanCapacity(max_tx_fee),
-                        ));
+                    let (input_total_opt, output_total) = helper.check_tx(&mut get_live_cell)?;
+                    // The capacity of the inputs can not be interpreted
+                    if let Some(input_total) = input_total_opt {
+                        if input_total < output_total {
+                            return Err(format!(
+                                "Input capacity not enough: input={:#}, output={:#}",
+                                HumanCapacity(input_total),
+                                HumanCapacity(output_total),
+                            ));
+                        }
+                        let tx_fee = input_total - output_total;
+                        if tx_fee > max_tx_fee {
+                            return Err(format!(
+                                "Too much transaction fee: {:#}, max: {:#}",
+                                HumanCapacity(tx_fee),
+                                HumanCapacity(max_tx_fee),
+                            ));
+                        }
                     }
                 }
                 let tx = helper.build_tx(&mut get_live_cell, skip_check)?;
@@ -586,11 +1355,219 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 });
                 Ok(Output::new_output(resp))
             }
+            ("broadcast", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let max_tx_fee: u64 = CapacityParser.from_matches(m, "max-tx-fee")?;
+                let skip_check: bool = m.is_present("skip-check");
+
+                let mock_tx = load_mock_tx(&tx_file)?;
+                let tx = mock_tx.core_transaction();
+                if !skip_check {
+                    let tx_dep_provider =
+                        MockTransactionDependencyProvider::new(&mock_tx.mock_info);
+                    let mut input_total: u64 = 0;
+                    for input in tx.inputs() {
+                        let output = tx_dep_provider
+                            .get_cell(&input.previous_output())
+                            .map_err(|err| err.to_string())?;
+                        let capacity: u64 = output.capacity().unpack();
+                        input_total += capacity;
+                    }
+                    let output_total = tx
+                        .outputs_capacity()
+                        .map_err(|err| err.to_string())?
+                        .as_u64();
+                    if input_total < output_total {
+                        return Err(format!(
+                            "Input capacity not enough: input={:#}, output={:#}",
+                            HumanCapacity(input_total),
+                            HumanCapacity(output_total),
+                        ));
+                    }
+                    let tx_fee = input_total - output_total;
+                    if tx_fee > max_tx_fee {
+                        return Err(format!(
+                            "Too much transaction fee: {:#}, max: {:#}",
+                            HumanCapacity(tx_fee),
+                            HumanCapacity(max_tx_fee),
+                        ));
+                    }
+                }
+                if debug {
+                    let rpc_tx = json_types::Transaction::from(tx.data());
+                    eprintln!(
+                        "[send transaction]:\n{}",
+                        serde_json::to_string_pretty(&rpc_tx).unwrap()
+                    );
+                }
+                let outputs_validator = if skip_check {
+                    Some(json_types::OutputsValidator::Passthrough)
+                } else {
+                    None
+                };
+                let resp = self
+                    .rpc_client
+                    .send_transaction(tx.data(), outputs_validator)
+                    .map_err(|err| format!("Send transaction error: {}", err))?;
+                Ok(Output::new_output(resp))
+            }
             _ => Err(Self::subcommand("tx").generate_usage()),
         }
     }
 }
 
+impl<'a> TxSubCommand<'a> {
+    fn sign_file(&mut self, m: &ArgMatches) -> Result<Output, String> {
+        let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+        let output_file: PathBuf = FilePathParser::new(false)
+            .from_matches_opt(m, "output-file")?
+            .unwrap_or_else(|| tx_file.clone());
+        let privkey_opt: Option<PrivkeyWrapper> =
+            PrivkeyPathParser.from_matches_opt(m, "privkey-path")?;
+        let account_opt: Option<H160> = m
+            .value_of("from-account")
+            .map(|input| {
+                FixedHashParser::<H160>::default()
+                    .parse(input)
+                    .or_else(|err| {
+                        let result: Result<Address, String> =
+                            AddressParser::new_sighash().parse(input);
+                        result
+                            .map(|address| H160::from_slice(&address.payload().args()).unwrap())
+                            .map_err(|_| err)
+                    })
+            })
+            .transpose()?;
+        let receiving_address_length: u32 =
+            FromStrParser::<u32>::default().from_matches(m, "derive-receiving-address-length")?;
+        let last_change_address_opt: Option<Address> =
+            AddressParser::new_sighash().from_matches_opt(m, "derive-change-address")?;
+
+        let mut mock_tx = load_mock_tx(&tx_file)?;
+        let tx_dep_provider = MockTransactionDependencyProvider::new(&mock_tx.mock_info);
+
+        // All the lock args can be signed, used to find the multisig config of time locked cells
+        let mut lock_args = Vec::new();
+        let mut password = None;
+        let mut change_path = String::new();
+        if let Some(privkey) = privkey_opt.as_ref() {
+            let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, privkey);
+            lock_args.push(H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).unwrap());
+        } else {
+            let account = account_opt.clone().unwrap();
+            if self.plugin_mgr.keystore_require_password() {
+                password = Some(read_password(false, None)?);
+            }
+            lock_args.push(account.clone());
+            if let Some(last_change_address) = last_change_address_opt.as_ref() {
+                let change_last =
+                    H160::from_slice(last_change_address.payload().args().as_ref()).unwrap();
+                let key_set = self.plugin_mgr.keystore_handler().derived_key_set(
+                    account.clone(),
+                    receiving_address_length,
+                    change_last.clone(),
+                    DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                    password.clone(),
+                )?;
+                for (path, hash160) in key_set
+                    .external
+                    .into_iter()
+                    .chain(key_set.change.into_iter())
+                {
+                    if hash160 == change_last {
+                        change_path = path.to_string();
+                    }
+                    if hash160 != account {
+                        lock_args.push(hash160);
+                    }
+                }
+            } else {
+                change_path = self.plugin_mgr.root_key_path(account)?.to_string();
+            }
+        }
+        let get_signer = || -> Result<Box<dyn Signer>, String> {
+            if let Some(privkey) = privkey_opt.as_ref() {
+                return Ok(Box::new(privkey.clone()));
+            }
+            let account = account_opt.clone().unwrap();
+            let mut signer = KeyStoreHandlerSigner::new(
+                self.plugin_mgr.keystore_handler(),
+                Box::new(tx_dep_provider.clone()),
+            );
+            if let Some(password) = password.as_ref() {
+                signer.set_password(account.clone(), password.clone());
+            }
+            if let Some(last_change_address) = last_change_address_opt.as_ref() {
+                let change_last =
+                    H160::from_slice(last_change_address.payload().args().as_ref()).unwrap();
+                signer.cache_key_set(
+                    account.clone(),
+                    receiving_address_length,
+                    change_last,
+                    DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                )?;
+            }
+            signer.set_change_path(account, change_path.clone());
+            Ok(Box::new(signer))
+        };
+
+        let mut unlockers: HashMap<ScriptId, Box<dyn ScriptUnlocker>> = HashMap::new();
+        let sighash_unlocker =
+            SecpSighashUnlocker::new(SecpSighashScriptSigner::new(get_signer()?));
+        unlockers.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+            Box::new(sighash_unlocker),
+        );
+        // The time locked cells (created by `util to-multisig-addr`) use 1 of 1 multisig config
+        let multisig_config_opt = mock_tx
+            .mock_info
+            .inputs
+            .iter()
+            .map(|input| input.output.lock())
+            .filter(|lock| lock.code_hash().as_slice() == MULTISIG_TYPE_HASH.as_bytes())
+            .find_map(|lock| {
+                lock_args.iter().find_map(|lock_arg| {
+                    let config = MultisigConfig::new_with(vec![lock_arg.clone()], 0, 1).ok()?;
+                    if lock.args().raw_data().get(0..20) == Some(config.hash160().as_bytes()) {
+                        Some(config)
+                    } else {
+                        None
+                    }
+                })
+            });
+        if let Some(config) = multisig_config_opt {
+            let multisig_unlocker =
+                SecpMultisigUnlocker::new(SecpMultisigScriptSigner::new(get_signer()?, config));
+            unlockers.insert(
+                ScriptId::new_type(MULTISIG_TYPE_HASH.clone()),
+                Box::new(multisig_unlocker),
+            );
+        }
+
+        let (tx, still_locked_groups) =
+            unlock_tx(mock_tx.core_transaction(), &tx_dep_provider, &unlockers)
+                .map_err(|err| err.to_string())?;
+        mock_tx.tx = tx.data();
+        let repr_tx = ReprMockTransaction::from(mock_tx);
+        let content = serde_json::to_string_pretty(&repr_tx).map_err(|err| err.to_string())?;
+        fs::write(output_file, content).map_err(|err| err.to_string())?;
+
+        let tx_hash: H256 = tx.hash().unpack();
+        let resp = serde_json::json!({
+            "tx-hash": tx_hash,
+            "still-locked-groups": still_locked_groups.len(),
+        });
+        Ok(Output::new_output(resp))
+    }
+}
+
+fn load_mock_tx(path: &Path) -> Result<MockTransaction, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let repr_tx: ReprMockTransaction =
+        serde_json::from_str(&content).map_err(|err| err.to_string())?;
+    Ok(repr_tx.into())
+}
+
 fn print_cell_info(
     prefix: &str,
     network: NetworkType,
@@ -600,14 +1577,17 @@ fn print_cell_info(
     type_script_empty: bool,
 ) {
     let address_payload = AddressPayload::from(lock);
-    let lock_kind = if address_payload.code_hash(Some(network)) == MULTISIG_TYPE_HASH.pack() {
+    let code_hash = address_payload.code_hash(Some(network));
+    let lock_kind = if code_hash == MULTISIG_TYPE_HASH.pack() {
         if address_payload.args().len() == 20 {
             "multisig without since"
         } else {
             "multisig with since"
         }
-    } else {
+    } else if code_hash == SIGHASH_TYPE_HASH.pack() {
         "sighash(secp)"
+    } else {
+        "other"
     };
     let address = Address::new(network, address_payload, true);
     let type_script_status = if type_script_empty { "none" } else { "some" };
@@ -686,36 +1666,543 @@ fn get_keystore_signer(
     )
 }
 
-fn modify_tx_file<T, F: FnOnce(&mut TxHelper) -> Result<T, String>>(
+/// Add or adjust the change output (the last plain capacity output of the change
+/// lock script) to pay the fee by fee rate, more plain capacity cells of the
+/// change lock script are added as inputs when the capacity is not enough.
+fn balance_tx<F>(
+    helper: &mut TxHelper,
+    change_lock: &Script,
+    fee_rate: u64,
+    cell_collector: &mut DefaultCellCollector,
+    genesis_info: &GenesisInfo,
+    mut get_live_cell: F,
+) -> Result<serde_json::Value, String>
+where
+    F: FnMut(OutPoint, bool) -> Result<CellOutput, String>,
+{
+    let old_tx_hash = helper.transaction().hash();
+    let change_index_opt = helper
+        .transaction()
+        .outputs_with_data_iter()
+        .enumerate()
+        .filter(|(_, (output, data))| {
+            &output.lock() == change_lock && output.type_().is_none() && data.is_empty()
+        })
+        .map(|(index, _)| index)
+        .last();
+    let change_index = match change_index_opt {
+        Some(index) => index,
+        None => {
+            let output = CellOutput::new_builder().lock(change_lock.clone()).build();
+            helper.add_output(output, Bytes::new());
+            helper.transaction().outputs().len() - 1
+        }
+    };
+    let change_output = helper.transaction().output(change_index).unwrap();
+    let min_change_capacity = change_output
+        .occupied_capacity(Capacity::zero())
+        .map_err(|err| err.to_string())?
+        .as_u64();
+
+    let mut new_inputs = Vec::new();
+    loop {
+        let mut input_total: u64 = 0;
+        for input in helper.transaction().inputs().into_iter() {
+            let capacity: u64 = get_live_cell(input.previous_output(), false)?
+                .capacity()
+                .unpack();
+            input_total += capacity;
+        }
+        let output_total: u64 = helper
+            .transaction()
+            .outputs()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| *index != change_index)
+            .map(|(_, output)| {
+                let capacity: u64 = output.capacity().unpack();
+                capacity
+            })
+            .sum();
+        // The capacity of the change output does not change the transaction size
+        let (tx, unknown_idxs) = helper.build_placeholder_tx(&mut get_live_cell)?;
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        let fee = FeeRate::from_u64(fee_rate).fee(tx_size).as_u64();
+        let required = output_total + fee + min_change_capacity;
+        if input_total >= required {
+            for idx in unknown_idxs {
+                eprintln!(
+                    "[warning] The witness of input(no.{}) is empty, the size of it is not included",
+                    idx + 1
+                );
+            }
+            let change_capacity = input_total - output_total - fee;
+            helper.update_output(
+                change_index,
+                change_output
+                    .as_builder()
+                    .capacity(Capacity::shannons(change_capacity).pack())
+                    .build(),
+            )?;
+            if helper.transaction().hash() != old_tx_hash && !helper.signatures().is_empty() {
+                eprintln!("[warning] The transaction is changed, the signatures are invalid");
+            }
+            let tx_hash: H256 = helper.transaction().hash().unpack();
+            return Ok(serde_json::json!({
+                "tx_hash": tx_hash,
+                "size": tx_size,
+                "fee": format!("{:#}", HumanCapacity(fee)),
+                "fee_rate": FeeRate::calculate(Capacity::shannons(fee), tx_size).as_u64(),
+                "change": {
+                    "index": change_index,
+                    "capacity": format!("{:#}", HumanCapacity(change_capacity)),
+                },
+                "new_inputs": new_inputs,
+            }));
+        }
+
+        let mut query = CellQueryOptions::new_lock(change_lock.clone());
+        query.secondary_script_len_range = Some(ValueRangeOption::new_exact(0));
+        query.data_len_range = Some(ValueRangeOption::new_exact(0));
+        query.maturity = MaturityOption::Mature;
+        query.min_total_capacity = required - input_total;
+        let (cells, _) = cell_collector
+            .collect_live_cells(&query, true)
+            .map_err(|err| err.to_string())?;
+        let used_inputs: HashSet<OutPoint> = helper
+            .transaction()
+            .input_pts_iter()
+            .collect::<HashSet<_>>();
+        let cells = cells
+            .into_iter()
+            .filter(|cell| !used_inputs.contains(&cell.out_point))
+            .collect::<Vec<_>>();
+        if cells.is_empty() {
+            return Err(format!(
+                "Capacity not enough: input={:#}, required={:#} (outputs + fee + change), no more live cells of the change address",
+                HumanCapacity(input_total),
+                HumanCapacity(required),
+            ));
+        }
+        for cell in cells {
+            helper.add_input(
+                cell.out_point.clone(),
+                None,
+                &mut get_live_cell,
+                genesis_info,
+                false,
+            )?;
+            let tx_hash: H256 = cell.out_point.tx_hash().unpack();
+            let index: u32 = cell.out_point.index().unpack();
+            new_inputs.push(format!("{:#x}-{}", tx_hash, index));
+        }
+    }
+}
+
+/// Unlock the inputs by the unlocker plugin of the lock script code hash.
+///
+/// When `skip_unlocked`, the inputs already unlocked (the witness is not
+/// empty) are skipped. When `require_unlocker`, an input can not be unlocked
+/// is an error.
+fn get_plugin_unlocker<'a>(
+    plugin_mgr: &'a PluginManager,
+    sub_command: &'a str,
+    skip_unlocked: bool,
+    require_unlocker: bool,
+) -> impl FnMut(
+    &Script,
+    &[usize],
+    &core::TransactionView,
+) -> Result<Option<Vec<packed::Bytes>>, String>
+       + 'a {
+    move |lock: &Script, idxs: &[usize], tx: &core::TransactionView| {
+        let code_hash: H256 = lock.code_hash().unpack();
+        let unlocked = tx
+            .witnesses()
+            .get(idxs[0])
+            .map(|witness| !witness.raw_data().is_empty())
+            .unwrap_or(false);
+        if skip_unlocked && unlocked {
+            return Ok(None);
+        }
+        if !plugin_mgr.unlockers().contains_key(&code_hash) {
+            if require_unlocker {
+                return Err(format!(
+                    "No unlocker plugin found for lock script code_hash: {:#x}, inputs: {:?}",
+                    code_hash, idxs
+                ));
+            }
+            return Ok(None);
+        }
+        let request = UnlockerRequest::Unlock {
+            tx: json_types::Transaction::from(tx.data()),
+            lock_script: json_types::Script::from(lock.clone()),
+            input_indices: idxs.iter().map(|idx| *idx as u32).collect(),
+            sub_command: sub_command.to_string(),
+        };
+        let witnesses = plugin_mgr.unlock(&code_hash, request)?;
+        Ok(Some(
+            witnesses
+                .into_iter()
+                .map(|witness| witness.into_bytes().pack())
+                .collect(),
+        ))
+    }
+}
+
+fn modify_tx_file<T, F>(
     path: &Path,
     network: NetworkType,
+    rpc_client: &mut HttpRpcClient,
     func: F,
-) -> Result<T, String> {
+) -> Result<T, String>
+where
+    F: FnOnce(&mut HttpRpcClient, &mut TxHelper) -> Result<T, String>,
+{
     let file = fs::File::open(path).map_err(|err| err.to_string())?;
     let repr: ReprTxHelper = serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+    let embedded_inputs = repr.inputs.clone();
+    let input_cells = repr.input_cells();
     let mut helper = TxHelper::try_from(repr)?;
+    let old_inputs = helper.transaction().inputs();
 
-    let result = func(&mut helper)?;
+    let result = func(rpc_client, &mut helper)?;
 
-    let repr = ReprTxHelper::new(helper, network);
+    // Only resolve the inputs again when they are changed (or not all embedded)
+    let tx = helper.transaction();
+    let inputs = if tx.inputs().as_slice() == old_inputs.as_slice()
+        && embedded_inputs.len() == old_inputs.len()
+    {
+        embedded_inputs
+    } else {
+        load_input_cells(rpc_client, tx, input_cells)?.0
+    };
+    let repr = ReprTxHelper::new_with_inputs(helper, network, inputs)?;
+    save_tx_file(path, &repr)?;
+    Ok(result)
+}
+
+fn save_tx_file(path: &Path, repr: &ReprTxHelper) -> Result<(), String> {
     let mut file = fs::File::create(path).map_err(|err| err.to_string())?;
-    let content = serde_json::to_string_pretty(&repr).map_err(|err| err.to_string())?;
+    let content = serde_json::to_string_pretty(repr).map_err(|err| err.to_string())?;
     file.write_all(content.as_bytes())
-        .map_err(|err| err.to_string())?;
-    Ok(result)
+        .map_err(|err| err.to_string())
+}
+
+// Load the input cells of the transaction, only the cells not embedded yet
+// are loaded from rpc. Also return the number of the embedded cells.
+fn load_input_cells(
+    rpc_client: &mut HttpRpcClient,
+    tx: &core::TransactionView,
+    mut input_cells: HashMap<OutPoint, ReprInputCell>,
+) -> Result<(Vec<ReprInputCell>, usize), String> {
+    let mut embedded = 0;
+    let mut inputs = Vec::new();
+    for input in tx.inputs().into_iter() {
+        let out_point = input.previous_output();
+        if let Some(cell) = input_cells.remove(&out_point) {
+            embedded += 1;
+            inputs.push(cell);
+        } else {
+            inputs.push(load_input_cell(rpc_client, out_point)?);
+        }
+    }
+    Ok((inputs, embedded))
+}
+
+// Parse the template variable: {name}={value}
+fn parse_template_var(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!(
+            "Invalid template variable: {}, format: {{name}}={{value}}",
+            input
+        )),
+    }
+}
+
+/// Load the transaction to explain from a file, the file can be a transaction
+/// file of `tx` subcommand, a mock transaction file or a transaction in json.
+/// The input cells embedded in the file are also returned.
+fn load_explain_tx_file(
+    path: &Path,
+) -> Result<(core::TransactionView, HashMap<OutPoint, ResolvedCell>), String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(|err| err.to_string())?;
+    let mut resolved_inputs = HashMap::new();
+    let tx: json_types::Transaction = if value.get("transaction").is_some() {
+        let repr: ReprTxHelper = serde_json::from_value(value).map_err(|err| err.to_string())?;
+        for cell in repr.inputs {
+            resolved_inputs.insert(
+                cell.previous_output.into(),
+                ResolvedCell {
+                    output: cell.output.into(),
+                    data: cell.output_data.into_bytes(),
+                    block_hash: cell.header.map(|header| header.hash),
+                },
+            );
+        }
+        repr.transaction
+    } else if value.get("mock_info").is_some() {
+        let repr: ReprMockTransaction =
+            serde_json::from_value(value).map_err(|err| err.to_string())?;
+        for input in repr.mock_info.inputs {
+            resolved_inputs.insert(
+                input.input.previous_output.into(),
+                ResolvedCell {
+                    output: input.output.into(),
+                    data: input.data.into_bytes(),
+                    block_hash: input.header,
+                },
+            );
+        }
+        repr.tx
+    } else {
+        serde_json::from_value(value).map_err(|err| err.to_string())?
+    };
+    Ok((packed::Transaction::from(tx).into_view(), resolved_inputs))
+}
+
+/// Check the transaction against the size and cycles limits, the cycles of
+/// each script group are from running the scripts locally (the tx is not
+/// required to be fully signed).
+fn tx_budget(
+    rpc_client: &mut HttpRpcClient,
+    tx: &core::TransactionView,
+    placeholder_tx: &core::TransactionView,
+    unknown_witness_inputs: &[usize],
+) -> Result<serde_json::Value, String> {
+    let consensus = rpc_client.get_consensus()?;
+    let max_tx_size = consensus.max_block_bytes;
+    let max_tx_cycles = cmp::min(MAX_TX_VERIFY_CYCLES, consensus.max_block_cycles);
+
+    let size = tx.data().as_reader().serialized_size_in_block() as u64;
+    let estimated_size = placeholder_tx.data().as_reader().serialized_size_in_block() as u64;
+    if estimated_size * 100 >= max_tx_size * BUDGET_WARNING_PERCENT {
+        eprintln!(
+            "[size] estimated size {} is close to the block size limit {}",
+            estimated_size, max_tx_size
+        );
+    }
+
+    // The transaction can not be resolved (dead input or cell dep etc.)
+    let (groups, resolve_error) = match verify_script_groups(rpc_client, tx, max_tx_cycles) {
+        Ok(groups) => (groups, None),
+        Err(err) => {
+            eprintln!("[cycles] can not run the scripts: {}", err);
+            (Vec::new(), Some(err))
+        }
+    };
+    let mut total_cycles = 0;
+    let mut script_groups = Vec::new();
+    for group in groups {
+        if let Ok(cycles) = group.result {
+            total_cycles += cycles;
+        }
+        script_groups.push(group.to_json());
+    }
+    if total_cycles * 100 >= max_tx_cycles * BUDGET_WARNING_PERCENT {
+        eprintln!(
+            "[cycles] total cycles {} is close to the limit {}",
+            total_cycles, max_tx_cycles
+        );
+    }
+
+    Ok(serde_json::json!({
+        "size": {
+            "current": size,
+            "estimated": estimated_size,
+            "unknown_witness_inputs": unknown_witness_inputs,
+            "limit": max_tx_size,
+        },
+        "cycles": {
+            "total": total_cycles,
+            "limit": max_tx_cycles,
+            "script_groups": script_groups,
+            "error": resolve_error,
+        },
+    }))
+}
+
+// The status of the cell: live, dead or unknown
+fn cell_status(rpc_client: &mut HttpRpcClient, out_point: OutPoint) -> Result<String, String> {
+    rpc_client
+        .get_live_cell(out_point, false, None)
+        .map(|cell| cell.status)
+}
+
+fn load_input_cell(
+    rpc_client: &mut HttpRpcClient,
+    out_point: OutPoint,
+) -> Result<ReprInputCell, String> {
+    let (output, data) = get_live_cell(rpc_client, out_point.clone(), true)?;
+    let block_hash = rpc_client
+        .get_transaction(out_point.tx_hash().unpack())?
+        .and_then(|tx_with_status| tx_with_status.tx_status.block_hash);
+    let header = if let Some(block_hash) = block_hash {
+        rpc_client
+            .get_header(block_hash)?
+            .map(|header| json_types::HeaderView::from(core::HeaderView::from(header)))
+    } else {
+        None
+    };
+    Ok(ReprInputCell {
+        previous_output: out_point.into(),
+        output: output.into(),
+        output_data: JsonBytes::from_bytes(data),
+        header,
+    })
+}
+
+// The problems of the lock group must be solved before the transaction can be sent
+fn lock_group_missing(status: &LockGroupStatus, network: NetworkType) -> Vec<String> {
+    let lock_arg = format!("0x{}", hex_string(&status.lock.args().raw_data()));
+    let to_address = |hash160: &H160| {
+        let payload = AddressPayload::from_pubkey_hash(hash160.clone());
+        Address::new(network, payload, false).to_string()
+    };
+    let mut missing = Vec::new();
+    if !status.is_sighash() && !status.is_multisig() {
+        let code_hash: H256 = status.lock.code_hash().unpack();
+        missing.push(format!(
+            "Lock script with code_hash {:#x} is not supported (lock_arg: {})",
+            code_hash, lock_arg
+        ));
+        return missing;
+    }
+    if status.is_multisig() && status.multisig_config.is_none() {
+        missing.push(format!(
+            "Missing multisig config for lock_arg: {}",
+            lock_arg
+        ));
+        return missing;
+    }
+    let invalid_signatures = status.invalid_signatures();
+    for signature in &invalid_signatures {
+        missing.push(format!(
+            "Invalid signature 0x{} for lock_arg: {}",
+            hex_string(signature),
+            lock_arg
+        ));
+    }
+    let signed_by = status.signed_by();
+    for signer in status.required_signers() {
+        if !signed_by.contains(&signer) {
+            missing.push(format!(
+                "Missing required signature of {} for lock_arg: {}",
+                to_address(&signer),
+                lock_arg
+            ));
+        }
+    }
+    let missing_signatures = status.missing_signatures();
+    if missing_signatures > 0 {
+        let candidates = status
+            .signers()
+            .iter()
+            .filter(|signer| !signed_by.contains(signer))
+            .map(to_address)
+            .collect::<Vec<_>>();
+        missing.push(format!(
+            "Missing {} signature(s) for lock_arg: {}, signers: {}",
+            missing_signatures,
+            lock_arg,
+            candidates.join(", ")
+        ));
+    } else if status.signatures.len() - invalid_signatures.len() > status.threshold() {
+        missing.push(format!(
+            "Too many signatures for lock_arg: {}, got: {}, expected: {}",
+            lock_arg,
+            status.signatures.len() - invalid_signatures.len(),
+            status.threshold()
+        ));
+    }
+    missing
+}
+
+/// Check whether the since of an input is satisfied by the tip block, return
+/// `None` when it can not be decided (the header of the input or the block
+/// median time is unknown).
+///
+/// The timestamp since is compared with the block median time as the consensus
+/// does: the median time of the tip block (the parent of the next block) for
+/// the current time, and the median time of the parent of the input block as
+/// the base of a relative since.
+pub(crate) fn since_satisfied(
+    rpc_client: &mut HttpRpcClient,
+    since: u64,
+    input_header: Option<&core::HeaderView>,
+    tip_header: &core::HeaderView,
+) -> Result<Option<bool>, String> {
+    if since == 0 {
+        return Ok(Some(true));
+    }
+    let since = Since::from_raw_value(since);
+    let (since_type, value) = match since.extract_metric() {
+        Some(metric) => metric,
+        None => return Ok(None),
+    };
+    let input_header = if since.is_absolute() {
+        None
+    } else if let Some(header) = input_header {
+        Some(header)
+    } else {
+        return Ok(None);
+    };
+    let satisfied = match since_type {
+        SinceType::EpochNumberWithFraction => {
+            let base_epoch = input_header
+                .map(|header| header.epoch())
+                .unwrap_or_else(|| EpochNumberWithFraction::new(0, 0, 1));
+            let epoch = EpochNumberWithFraction::from_full_value(value).normalize();
+            base_epoch.to_rational() + epoch.to_rational() <= tip_header.epoch().to_rational()
+        }
+        SinceType::BlockNumber => {
+            let base_number = input_header.map(|header| header.number()).unwrap_or(0);
+            base_number + value <= tip_header.number()
+        }
+        SinceType::Timestamp => {
+            let base_time = match input_header {
+                Some(header) => {
+                    match rpc_client.get_block_median_time(header.parent_hash().unpack())? {
+                        Some(time) => time.0,
+                        None => return Ok(None),
+                    }
+                }
+                None => 0,
+            };
+            let median_time = match rpc_client.get_block_median_time(tip_header.hash().unpack())? {
+                Some(time) => time.0,
+                None => return Ok(None),
+            };
+            base_time + value * 1000 <= median_time
+        }
+    };
+    Ok(Some(satisfied))
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReprTxHelper {
+    #[serde(default)]
+    pub(crate) version: u32,
     pub(crate) transaction: json_types::Transaction,
     pub(crate) multisig_configs: HashMap<H160, ReprMultisigConfig>,
     pub(crate) signatures: HashMap<JsonBytes, Vec<JsonBytes>>,
+    /// The input cells in the order of transaction inputs
+    #[serde(default)]
+    pub(crate) inputs: Vec<ReprInputCell>,
+    /// Only for inspection, it is re-calculated when the file is saved
+    #[serde(default)]
+    pub(crate) signing_status: Vec<ReprLockGroupStatus>,
 }
 
 impl ReprTxHelper {
     pub(crate) fn new(tx: TxHelper, network: NetworkType) -> Self {
         ReprTxHelper {
+            version: TX_FILE_VERSION,
             transaction: tx.transaction().data().into(),
             multisig_configs: tx
                 .multisig_configs()
@@ -741,13 +2228,57 @@ impl ReprTxHelper {
                     )
                 })
                 .collect(),
+            inputs: Vec::new(),
+            signing_status: Vec::new(),
         }
     }
+
+    /// Create with the input cells embedded and the signing status calculated
+    pub(crate) fn new_with_inputs(
+        tx: TxHelper,
+        network: NetworkType,
+        inputs: Vec<ReprInputCell>,
+    ) -> Result<Self, String> {
+        let cells = ReprInputCell::cell_map(&inputs);
+        let signing_status = tx
+            .signing_status(|out_point, _with_data| {
+                cells
+                    .get(&out_point)
+                    .cloned()
+                    .ok_or_else(|| format!("Input cell not found: {}", out_point))
+            })?
+            .iter()
+            .map(|status| ReprLockGroupStatus::new(status, network))
+            .collect();
+        let mut repr = ReprTxHelper::new(tx, network);
+        // The data of a pending cell is not available (see `get_live_cell`),
+        // only the committed cells are embedded
+        repr.inputs = inputs
+            .into_iter()
+            .filter(|cell| cell.header.is_some())
+            .collect();
+        repr.signing_status = signing_status;
+        Ok(repr)
+    }
+
+    /// The embedded input cells
+    pub(crate) fn input_cells(&self) -> HashMap<OutPoint, ReprInputCell> {
+        self.inputs
+            .iter()
+            .map(|cell| (cell.previous_output.clone().into(), cell.clone()))
+            .collect()
+    }
 }
 
 impl TryFrom<ReprTxHelper> for TxHelper {
     type Error = String;
     fn try_from(repr: ReprTxHelper) -> Result<Self, Self::Error> {
+        if repr.version > TX_FILE_VERSION {
+            return Err(format!(
+                "Unsupported transaction file version: {}, max supported: {}",
+                repr.version, TX_FILE_VERSION
+            ));
+        }
         let transaction = packed::Transaction::from(repr.transaction).into_view();
         let multisig_configs = repr
             .multisig_configs
@@ -778,6 +2309,97 @@ impl TryFrom<ReprTxHelper> for TxHelper {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReprInputCell {
+    pub(crate) previous_output: json_types::OutPoint,
+    pub(crate) output: json_types::CellOutput,
+    pub(crate) output_data: JsonBytes,
+    /// The header of the block which the cell committed in (`None` for pending cell)
+    pub(crate) header: Option<json_types::HeaderView>,
+}
+
+impl ReprInputCell {
+    fn cell_map(inputs: &[ReprInputCell]) -> HashMap<OutPoint, CellOutput> {
+        inputs
+            .iter()
+            .map(|cell| {
+                (
+                    cell.previous_output.clone().into(),
+                    cell.output.clone().into(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReprLockGroupStatus {
+    /// sighash, multisig or unknown
+    pub(crate) lock_type: String,
+    pub(crate) lock_arg: JsonBytes,
+    /// The input indexes of this lock script
+    pub(crate) inputs: Vec<usize>,
+    /// The since of each input
+    pub(crate) since: Vec<json_types::Uint64>,
+    pub(crate) threshold: usize,
+    pub(crate) signers: Vec<ReprSignerStatus>,
+    pub(crate) invalid_signatures: Vec<JsonBytes>,
+    pub(crate) missing_signatures: usize,
+    pub(crate) ready: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReprSignerStatus {
+    pub(crate) address: String,
+    /// Required by `require_first_n` of multisig config
+    pub(crate) required: bool,
+    pub(crate) signed: bool,
+}
+
+impl ReprLockGroupStatus {
+    pub(crate) fn new(status: &LockGroupStatus, network: NetworkType) -> Self {
+        let lock_type = if status.is_sighash() {
+            "sighash"
+        } else if status.is_multisig() {
+            "multisig"
+        } else {
+            "unknown"
+        };
+        let required_signers = status.required_signers();
+        let signed_by = status.signed_by();
+        let signers = status
+            .signers()
+            .into_iter()
+            .map(|hash160| {
+                let payload = AddressPayload::from_pubkey_hash(hash160.clone());
+                ReprSignerStatus {
+                    address: Address::new(network, payload, false).to_string(),
+                    required: required_signers.contains(&hash160),
+                    signed: signed_by.contains(&hash160),
+                }
+            })
+            .collect();
+        ReprLockGroupStatus {
+            lock_type: lock_type.to_string(),
+            lock_arg: JsonBytes::from_bytes(status.lock.args().raw_data()),
+            inputs: status.input_idxs.clone(),
+            since: status.since.iter().map(|since| (*since).into()).collect(),
+            threshold: status.threshold(),
+            signers,
+            invalid_signatures: status
+                .invalid_signatures()
+                .into_iter()
+                .map(JsonBytes::from_bytes)
+                .collect(),
+            missing_signatures: status.missing_signatures(),
+            ready: status.is_ready(),
+        }
+    }
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct ReprMultisigConfig {
@@ -33,10 +33,10 @@ use crate::utils::{
     arg,
     arg_parser::{
         AddressParser, ArgParser, FilePathParser, FixedHashParser, FromStrParser, HexParser,
-        PrivkeyPathParser, PrivkeyWrapper, PubkeyHexParser,
+        PrivkeyPathParser, PrivkeyWrapper, PubkeyHexParser, SinceParser,
     },
     genesis_info::GenesisInfo,
-    other::{address_json, get_address, get_network_type, read_password},
+    other::{address_json, get_address, get_network_type, read_password, since_json},
     rpc::{ChainInfo, HttpRpcClient},
 };
 use crate::{build_cli, get_version};
@@ -45,8 +45,8 @@ use crate::{build_cli, get_version};
 const SIGN_MAGIC_BYTES: &[u8] = b"Nervos Message:";
 const FLAG_SINCE_EPOCH_NUMBER: u64 =
     0b010_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000;
-const EPOCH_LENGTH: u64 = 1800;
-const BLOCK_PERIOD: u64 = 8 * 1000; // 8 seconds
+pub(crate) const EPOCH_LENGTH: u64 = 1800;
+pub(crate) const BLOCK_PERIOD: u64 = 8 * 1000; // 8 seconds
 
 pub struct UtilSubCommand<'a> {
     rpc_client: &'a mut HttpRpcClient,
@@ -271,6 +271,16 @@ impl<'a> UtilSubCommand<'a> {
                             .validator(|input| DateTime::parse_from_rfc3339(input).map(|_| ()).map_err(|err| err.to_string()))
                             .about("The locktime in RFC3339 format. Example: 2014-11-28T21:00:00+00:00")
                     ),
+                App::new("since")
+                    .about("Encode a since value or explain a raw since value (see RFC0017)")
+                    .arg(
+                        Arg::with_name("value")
+                            .long("value")
+                            .required(true)
+                            .takes_value(true)
+                            .validator(|input| SinceParser.validate(input))
+                            .about("The since value, format: 0x{raw hex} or [absolute:|relative:]{epoch:number[,index,length]|block:number|timestamp:seconds} (absolute by default)")
+                    ),
                 App::new("cell-meta")
                     .about("Query live cell's metadata")
                     .arg(
@@ -730,6 +740,10 @@ message = "0x"
                 });
                 Ok(Output::new_output(resp))
             }
+            ("since", Some(m)) => {
+                let since: u64 = SinceParser.from_matches(m, "value")?;
+                Ok(Output::new_output(since_json(since)))
+            }
             ("cell-meta", Some(m)) => {
                 let tx_hash: H256 =
                     FixedHashParser::<H256>::default().from_matches(m, "tx-hash")?;
@@ -898,32 +912,52 @@ fn sign_message<P: ?Sized + AsRef<[ChildNumber]>>(
     }
 }
 
-fn gen_multisig_addr(
+pub(crate) fn gen_multisig_addr(
     sighash_address_payload: &AddressPayload,
     tip_epoch_opt: Option<EpochNumberWithFraction>,
     elapsed: u64,
 ) -> (EpochNumberWithFraction, AddressPayload) {
-    let epoch_fraction = {
-        let tip_epoch =
-            tip_epoch_opt.unwrap_or_else(|| EpochNumberWithFraction::new(0, 0, EPOCH_LENGTH));
-        let blocks = tip_epoch.number() * EPOCH_LENGTH
-            + tip_epoch.index() * EPOCH_LENGTH / tip_epoch.length()
-            + elapsed / BLOCK_PERIOD;
-        let epoch_number = blocks / EPOCH_LENGTH;
-        let epoch_index = blocks % EPOCH_LENGTH;
-        EpochNumberWithFraction::new(epoch_number, epoch_index, EPOCH_LENGTH)
-    };
+    let epoch_fraction = estimate_epoch(tip_epoch_opt, elapsed);
     let since = FLAG_SINCE_EPOCH_NUMBER | epoch_fraction.full_value();
+    let payload = gen_since_multisig_addr(sighash_address_payload, since);
+    (epoch_fraction, payload)
+}
 
+/// Estimate the epoch after `elapsed` milliseconds from the tip epoch
+pub(crate) fn estimate_epoch(
+    tip_epoch_opt: Option<EpochNumberWithFraction>,
+    elapsed: u64,
+) -> EpochNumberWithFraction {
+    let tip_epoch =
+        tip_epoch_opt.unwrap_or_else(|| EpochNumberWithFraction::new(0, 0, EPOCH_LENGTH));
+    let blocks = tip_epoch.number() * EPOCH_LENGTH
+        + tip_epoch.index() * EPOCH_LENGTH / tip_epoch.length()
+        + elapsed / BLOCK_PERIOD;
+    let epoch_number = blocks / EPOCH_LENGTH;
+    let epoch_index = blocks % EPOCH_LENGTH;
+    EpochNumberWithFraction::new(epoch_number, epoch_index, EPOCH_LENGTH)
+}
+
+/// The lock args hash of the 1-of-1 multisig script wraps a sighash address
+pub(crate) fn single_multisig_hash(sighash_address_payload: &AddressPayload) -> [u8; 20] {
+    let mut multi_script = vec![0u8, 0, 1, 1]; // [S, R, M, N]
+    multi_script.extend_from_slice(sighash_address_payload.args().as_ref());
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&blake2b_256(multi_script)[..20]);
+    hash
+}
+
+/// Wrap a sighash address into a 1-of-1 multisig address with since
+pub(crate) fn gen_since_multisig_addr(
+    sighash_address_payload: &AddressPayload,
+    since: u64,
+) -> AddressPayload {
     let args = {
-        let mut multi_script = vec![0u8, 0, 1, 1]; // [S, R, M, N]
-        multi_script.extend_from_slice(sighash_address_payload.args().as_ref());
-        let mut data = BytesMut::from(&blake2b_256(multi_script)[..20]);
+        let mut data = BytesMut::from(&single_multisig_hash(sighash_address_payload)[..]);
         data.extend_from_slice(&since.to_le_bytes()[..]);
         data.freeze()
     };
-    let payload = AddressPayload::new_full(ScriptHashType::Type, MULTISIG_TYPE_HASH.pack(), args);
-    (epoch_fraction, payload)
+    AddressPayload::new_full(ScriptHashType::Type, MULTISIG_TYPE_HASH.pack(), args)
 }
 
 fn to_timestamp(input: &str) -> Result<u64, String> {
@@ -1,6 +1,12 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{hash_map::Entry, BTreeSet, HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use bitcoin::bip32::DerivationPath;
+use chrono::prelude::*;
 use clap::{App, Arg, ArgMatches};
 use serde::{Deserialize, Serialize};
 
@@ -9,14 +15,16 @@ use ckb_hash::new_blake2b;
 use ckb_jsonrpc_types as json_types;
 use ckb_sdk::{
     constants::{DAO_TYPE_HASH, MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
+    rpc::ckb_indexer::{CellType, Order, ScriptType, SearchKey, SearchMode},
     traits::{
         CellCollector, CellQueryOptions, DefaultCellCollector, DefaultHeaderDepResolver,
         DefaultTransactionDependencyProvider, MaturityOption, PrimaryScriptType, Signer,
-        ValueRangeOption,
+        TransactionDependencyProvider, ValueRangeOption,
     },
     tx_builder::{
-        transfer::CapacityTransferBuilder, unlock_tx, BalanceTxCapacityError, CapacityBalancer,
-        CapacityProvider, SinceSource, TxBuilder, TxBuilderError,
+        balance_tx_capacity, fill_placeholder_witnesses, transfer::CapacityTransferBuilder,
+        unlock_tx, BalanceTxCapacityError, CapacityBalancer, CapacityProvider, SinceSource,
+        TxBuilder, TxBuilderError,
     },
     types::ScriptId,
     unlock::{
@@ -28,32 +36,49 @@ use ckb_sdk::{
 };
 use ckb_types::{
     bytes::Bytes,
-    core::{Capacity, FeeRate, ScriptHashType, TransactionView},
-    packed::{CellOutput, Script, WitnessArgs},
+    core::{self, Capacity, EpochNumberWithFraction, FeeRate, ScriptHashType, TransactionView},
+    packed::{self, Byte32, CellInput, CellOutput, Script, WitnessArgs},
     prelude::*,
     H160, H256,
 };
 use plugin_protocol::LiveCellInfo;
 
-use super::{CliSubCommand, Output};
+use super::{
+    mock_tx::dump_mock_tx,
+    tx::since_satisfied,
+    util::{
+        estimate_epoch, gen_since_multisig_addr, single_multisig_hash, BLOCK_PERIOD, EPOCH_LENGTH,
+    },
+    CliSubCommand, Output,
+};
 use crate::plugin::PluginManager;
 use crate::utils::{
     arg,
     arg_parser::{
-        AddressParser, ArgParser, CapacityParser, FixedHashParser, FromStrParser,
-        PrivkeyPathParser, PrivkeyWrapper,
+        AddressParser, ArgParser, CapacityParser, FilePathParser, FixedHashParser, FromStrParser,
+        HexParser, OutPointParser, PrivkeyPathParser, PrivkeyWrapper,
+    },
+    coin_selection::{
+        check_use_cells, CoinSelectionCollector, CoinSelectionStrategy, SelectionTarget,
     },
+    dry_run::{dry_run_report, provider_cell_getter},
     genesis_info::GenesisInfo,
     other::{
-        check_capacity, get_address, get_arg_value, get_genesis_info, get_network_type,
-        get_to_data, map_tx_builder_error_2_str, read_password, to_live_cell_info,
+        check_capacity, get_address, get_arg_value, get_arg_values, get_genesis_info,
+        get_network_type, get_to_data, map_tx_builder_error_2_str, read_password,
+        to_live_cell_info,
     },
-    rpc::HttpRpcClient,
-    signer::KeyStoreHandlerSigner,
+    rpc::{parse_order, HeaderView, HttpRpcClient, Tx},
+    signer::{ExternalSigner, KeyStoreHandlerSigner},
+    tx_chunks::{TxChunker, TxLimits},
 };
 
 // Max derived change address to search
-const DERIVE_CHANGE_ADDRESS_MAX_LEN: u32 = 10000;
+pub(crate) const DERIVE_CHANGE_ADDRESS_MAX_LEN: u32 = 10000;
+// Estimated transaction size for calculating the fee when selecting input cells
+const COIN_SELECTION_TX_SIZE: u64 = 1000;
+// Page size of indexer `get_transactions` requests
+const HISTORY_SEARCH_LIMIT: u32 = 500;
 
 pub struct WalletSubCommand<'a> {
     plugin_mgr: &'a mut PluginManager,
@@ -91,13 +116,47 @@ impl<'a> WalletSubCommand<'a> {
                             .required_unless(arg::privkey_path().get_name())
                             .conflicts_with(arg::privkey_path().get_name()),
                     )
+                    .arg(arg::signer_cmd().conflicts_with(arg::privkey_path().get_name()))
                     .arg(arg::from_locked_address())
-                    .arg(arg::to_address().required(true))
+                    .arg(arg::to_address().required_unless("batch-file"))
                     .arg(arg::to_data())
                     .arg(arg::to_data_path())
-                    .arg(arg::capacity().required(true))
+                    .arg(arg::capacity().required_unless("batch-file"))
+                    .arg(
+                        Arg::with_name("batch-file")
+                            .long("batch-file")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .conflicts_with_all(&["to-address", "capacity", "to-data", "to-data-path", "type-id"])
+                            .about("Transfer to multiple targets listed in a csv file (columns: address,capacity[,data]) or a json file (array of {\"address\", \"capacity\", \"data\"}), targets will be split into multiple transactions when exceeding the size or cycles limit")
+                    )
                     .arg(arg::fee_rate())
                     .arg(arg::max_tx_fee())
+                    .arg(
+                        Arg::with_name("coin-selection")
+                            .long("coin-selection")
+                            .takes_value(true)
+                            .possible_values(&CoinSelectionStrategy::NAMES)
+                            .default_value("default")
+                            .requires_if("branch-and-bound", arg::max_tx_fee().get_name())
+                            .about("The strategy to select input cells, `branch-and-bound` searches inputs without change cell (the left capacity not more than <max-tx-fee> is paid as transaction fee), `minimize-change` searches inputs with smallest change cell")
+                    )
+                    .arg(
+                        Arg::with_name("use-cell")
+                            .long("use-cell")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| OutPointParser.validate(input))
+                            .about("The cell must be used as input (format: {tx-hash}-{index}), can be given multiple times")
+                    )
+                    .arg(
+                        Arg::with_name("exclude-cell")
+                            .long("exclude-cell")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| OutPointParser.validate(input))
+                            .about("The cell must not be used as input (format: {tx-hash}-{index}), can be given multiple times")
+                    )
                     .arg(arg::derive_receiving_address_length())
                     .arg(
                         arg::derive_change_address().conflicts_with(arg::privkey_path().get_name()),
@@ -110,12 +169,146 @@ impl<'a> WalletSubCommand<'a> {
                         Arg::with_name("type-id")
                             .long("type-id")
                             .about("Add type id type script to target output cell"),
+                    )
+                    .arg(
+                        Arg::with_name("lock-until")
+                            .long("lock-until")
+                            .takes_value(true)
+                            .conflicts_with("batch-file")
+                            .about("Lock the target cell until the time (wrap the sighash <to-address> in a 1-of-1 multisig address with absolute since), format: epoch:{number}[,{index},{length}] | block:{number} | RFC3339 time (converted to estimated epoch, example: 2014-11-28T21:00:00+00:00) | raw since value in hex")
+                    )
+                    .arg(
+                        Arg::with_name("build-only")
+                            .long("build-only")
+                            .requires("output-file")
+                            .conflicts_with("batch-file")
+                            .about("Only build the unsigned transaction (with input cells and headers embedded) into <output-file>, sign it by `tx sign-file` and send it by `tx broadcast`")
+                    )
+                    .arg(
+                        Arg::with_name("output-file")
+                            .long("output-file")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("The unsigned transaction file (format: json)")
+                    )
+                    .arg(arg::dry_run().conflicts_with("build-only")),
+                App::new("bump-fee")
+                    .about("Replace a pending transaction sent from the wallet by a higher fee rate one (the last output of the sender is used as change)")
+                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .required_unless(arg::privkey_path().get_name())
+                            .conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(arg::signer_cmd().conflicts_with(arg::privkey_path().get_name()))
+                    .arg(
+                        Arg::with_name("tx-hash")
+                            .long("tx-hash")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .about("The hash of the pending transaction to replace"),
+                    )
+                    .arg(
+                        Arg::with_name("fee-rate")
+                            .long("fee-rate")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .about("The new transaction fee rate (unit: shannons/KB)"),
+                    )
+                    .arg(arg::derive_receiving_address_length())
+                    .arg(
+                        arg::derive_change_address().conflicts_with(arg::privkey_path().get_name()),
+                    ),
+                App::new("consolidate")
+                    .about("Merge small cells of an account (including derived addresses) into one cell per transaction")
+                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .required_unless(arg::privkey_path().get_name())
+                            .conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(arg::signer_cmd().conflicts_with(arg::privkey_path().get_name()))
+                    .arg(
+                        arg::to_address()
+                            .about("The address to receive the merged cells (default: the address of the key)"),
+                    )
+                    .arg(
+                        Arg::with_name("max-inputs")
+                            .long("max-inputs")
+                            .takes_value(true)
+                            .default_value("500")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("Max input cells in one transaction"),
+                    )
+                    .arg(
+                        Arg::with_name("min-cell-capacity")
+                            .long("min-cell-capacity")
+                            .takes_value(true)
+                            .default_value("1000")
+                            .validator(|input| CapacityParser.validate(input))
+                            .about("The cells with capacity less than this value (unit: CKB) will be merged"),
+                    )
+                    .arg(arg::fee_rate())
+                    .arg(arg::derive_receiving_address_length())
+                    .arg(arg::derive_change_address_length())
+                    .arg(
+                        Arg::with_name("dry-run")
+                            .long("dry-run")
+                            .about("Only report the transactions and fee cost, do not sign and send them"),
+                    ),
+                App::new("history")
+                    .about("List the transactions of an address or an HD account (including derived addresses)")
+                    .arg(arg::address().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .conflicts_with(arg::address().get_name())
+                            .about("The account's lock-arg or sighash address (search all the derived addresses)"),
+                    )
+                    .arg(arg::derive_receiving_address_length())
+                    .arg(arg::derive_change_address_length())
+                    .arg(
+                        Arg::with_name("order")
+                            .long("order")
+                            .takes_value(true)
+                            .possible_values(&["asc", "desc"])
+                            .default_value("desc")
+                            .about("Order by block number"),
+                    )
+                    .arg(
+                        Arg::with_name("page")
+                            .long("page")
+                            .takes_value(true)
+                            .default_value("0")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("Page number (start from 0)"),
+                    )
+                    .arg(
+                        Arg::with_name("limit")
+                            .long("limit")
+                            .takes_value(true)
+                            .default_value("20")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("Max transactions in one page"),
+                    )
+                    .arg(
+                        Arg::with_name("export")
+                            .long("export")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Export the transactions of the page to a file (format: csv if the file extension is .csv, otherwise json)"),
                     ),
                 App::new("get-capacity")
                     .about("Get capacity address or lock arg or pubkey")
                     .arg(arg::address())
                     .arg(arg::pubkey())
                     .arg(arg::lock_arg())
+                    .arg(
+                        arg::from_account()
+                            .conflicts_with_all(&["address", "pubkey", "lock-arg"])
+                            .about("The account's lock-arg or sighash address (include the derived addresses)"),
+                    )
                     .arg(arg::derive_receiving_address_length())
                     .arg(arg::derive_change_address_length())
                     .arg(arg::derived()),
@@ -133,6 +326,25 @@ impl<'a> WalletSubCommand<'a> {
         args: TransferArgs,
         skip_check: bool,
     ) -> Result<TransactionView, String> {
+        let target = TransferTarget {
+            address: args.to_address.clone(),
+            capacity: args.capacity.clone(),
+            data: args.to_data.clone(),
+        };
+        let mut batches = self.transfer_batch(args, vec![target], skip_check)?;
+        Ok(batches.remove(0).tx)
+    }
+
+    /// Transfer to multiple targets, the targets will be split into multiple
+    /// transactions when one transaction exceeds the size or cycles limit.
+    ///
+    /// The `to_address`, `capacity` and `to_data` fields of `args` are ignored.
+    pub fn transfer_batch(
+        &mut self,
+        args: TransferArgs,
+        targets: Vec<TransferTarget>,
+        skip_check: bool,
+    ) -> Result<Vec<TransferBatch>, String> {
         let TransferArgs {
             privkey_path,
             from_account,
@@ -140,19 +352,32 @@ impl<'a> WalletSubCommand<'a> {
             password,
             derive_receiving_address_length,
             derive_change_address,
-            capacity,
             fee_rate,
             force_small_change_as_fee,
-            to_address,
-            to_data,
             is_type_id,
             skip_check_to_address,
+            build_only,
+            coin_selection,
+            use_cells,
+            exclude_cells,
+            lock_until,
+            signer_cmd,
+            ..
         } = args;
+        if targets.is_empty() {
+            return Err("No transfer target given".to_string());
+        }
+        if is_type_id && targets.len() > 1 {
+            return Err("Type id can only be added to a single transfer target".to_string());
+        }
 
         let network_type = get_network_type(self.rpc_client)?;
         let from_privkey: Option<PrivkeyWrapper> = privkey_path
             .map(|input| PrivkeyPathParser.parse(&input))
             .transpose()?;
+        let signer_cmd: Option<PathBuf> = signer_cmd
+            .map(|input| FilePathParser::new(true).parse(&input))
+            .transpose()?;
         let from_account: Option<H160> = from_account
             .map(|input| {
                 FixedHashParser::<H160>::default()
@@ -174,10 +399,37 @@ impl<'a> WalletSubCommand<'a> {
                     .parse(&input)
             })
             .transpose()?;
-        let to_capacity: u64 = CapacityParser.parse(&capacity)?.into();
         let fee_rate: u64 = FromStrParser::<u64>::default().parse(&fee_rate)?;
         let force_small_change_as_fee: Option<u64> =
             force_small_change_as_fee.map(|s| CapacityParser.parse(&s).unwrap().into());
+        let coin_selection = coin_selection
+            .map(|input| CoinSelectionStrategy::from_str(&input))
+            .transpose()?
+            .unwrap_or(CoinSelectionStrategy::Default);
+        if coin_selection == CoinSelectionStrategy::BranchAndBound
+            && force_small_change_as_fee.is_none()
+        {
+            return Err("Coin selection strategy branch-and-bound requires max-tx-fee".to_string());
+        }
+        let use_cells = use_cells
+            .iter()
+            .map(|input| OutPointParser.parse(input))
+            .collect::<Result<Vec<_>, String>>()?;
+        let exclude_cells = exclude_cells
+            .iter()
+            .map(|input| OutPointParser.parse(input))
+            .collect::<Result<Vec<_>, String>>()?;
+        if let Some(out_point) = use_cells
+            .iter()
+            .find(|out_point| exclude_cells.contains(out_point))
+        {
+            let tx_hash: H256 = out_point.tx_hash().unpack();
+            let index: u32 = out_point.index().unpack();
+            return Err(format!(
+                "Cell {:#x}-{} can not be both used and excluded",
+                tx_hash, index
+            ));
+        }
         let receiving_address_length: u32 = derive_receiving_address_length
             .map(|input| FromStrParser::<u32>::default().parse(&input))
             .transpose()?
@@ -189,10 +441,63 @@ impl<'a> WalletSubCommand<'a> {
                     .parse(&input)
             })
             .transpose()?;
-        let to_address: Address = AddressParser::default()
-            .set_network(network_type)
-            .parse(&to_address)?;
-        let to_data = to_data.unwrap_or_default();
+        let lock_until_since: Option<u64> = lock_until
+            .map(|input| {
+                let tip_header = self.rpc_client.get_tip_header()?;
+                parse_lock_until(&input, &tip_header).map(|(since, _)| since)
+            })
+            .transpose()?;
+
+        // Add outputs
+        let placeholder_type_script = if is_type_id {
+            Some(
+                Script::new_builder()
+                    .code_hash(TYPE_ID_CODE_HASH.pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .args(Bytes::from(vec![0u8; 32]).pack())
+                    .build(),
+            )
+        } else {
+            None
+        };
+        let mut outputs = Vec::with_capacity(targets.len());
+        for (idx, target) in targets.iter().enumerate() {
+            let err_prefix = if targets.len() > 1 {
+                format!("Invalid transfer target #{}: ", idx)
+            } else {
+                String::new()
+            };
+            let to_address: Address = AddressParser::default()
+                .set_network(network_type)
+                .parse(&target.address)
+                .map_err(|err| format!("{}{}", err_prefix, err))?;
+            let to_capacity: u64 = CapacityParser
+                .parse(&target.capacity)
+                .map_err(|err| format!("{}{}", err_prefix, err))?
+                .into();
+            let to_data = target.data.clone().unwrap_or_default();
+            check_to_address(&to_address, skip_check_to_address)
+                .map_err(|err| format!("{}{}", err_prefix, err))?;
+            check_capacity(to_capacity, to_data.len())
+                .map_err(|err| format!("{}{}", err_prefix, err))?;
+            let to_lock_script = if let Some(since) = lock_until_since {
+                if !is_sighash_payload(to_address.payload()) {
+                    return Err(format!(
+                        "{}lock-until only support sighash <to-address>",
+                        err_prefix
+                    ));
+                }
+                Script::from(&gen_since_multisig_addr(to_address.payload(), since))
+            } else {
+                to_address.payload().into()
+            };
+            let to_output = CellOutput::new_builder()
+                .capacity(Capacity::shannons(to_capacity).pack())
+                .lock(to_lock_script)
+                .type_(placeholder_type_script.clone().pack())
+                .build();
+            outputs.push((to_output, to_data));
+        }
 
         let (from_address_payload, password) = if let Some(from_privkey) = from_privkey.as_ref() {
             let from_pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, from_privkey);
@@ -200,7 +505,10 @@ impl<'a> WalletSubCommand<'a> {
         } else {
             let password = if let Some(password) = password {
                 Some(password)
-            } else if self.plugin_mgr.keystore_require_password() {
+            } else if !build_only
+                && signer_cmd.is_none()
+                && self.plugin_mgr.keystore_require_password()
+            {
                 Some(read_password(false, None)?)
             } else {
                 None
@@ -221,35 +529,10 @@ impl<'a> WalletSubCommand<'a> {
             let mut since_bytes = [0u8; 8];
             since_bytes.copy_from_slice(&args[20..]);
             let since = Since::from_raw_value(u64::from_le_bytes(since_bytes));
-            if !since.flags_is_valid() {
+            if !since.flags_is_valid() || since.extract_metric().is_none() {
                 return Err(format!("{}: invalid since flags", err_prefix));
             }
-            if !since.is_absolute() {
-                return Err(format!("{}: only support absolute since value", err_prefix));
-            }
-            if since.extract_metric().map(|(ty, _)| ty) != Some(SinceType::EpochNumberWithFraction)
-            {
-                return Err(format!("{}: only support epoch since value", err_prefix));
-            }
-        }
-
-        let to_address_hash_type = to_address.payload().hash_type();
-        let to_address_code_hash: H256 = to_address
-            .payload()
-            .code_hash(Some(to_address.network()))
-            .unpack();
-        let to_address_args_len = to_address.payload().args().len();
-        if !(skip_check_to_address
-            || (to_address_hash_type == ScriptHashType::Type
-                && to_address_code_hash == SIGHASH_TYPE_HASH
-                && to_address_args_len == 20)
-            || (to_address_hash_type == ScriptHashType::Type
-                && to_address_code_hash == MULTISIG_TYPE_HASH
-                && (to_address_args_len == 20 || to_address_args_len == 28)))
-        {
-            return Err(format!("Invalid to-address: {}\n[Hint]: Add `--skip-check-to-address` flag to transfer to any address", to_address));
         }
-        check_capacity(to_capacity, to_data.len())?;
 
         let genesis_info = self.genesis_info()?;
 
@@ -264,19 +547,32 @@ impl<'a> WalletSubCommand<'a> {
         )];
 
         let from_lock_arg = H160::from_slice(from_address.payload().args().as_ref()).unwrap();
+        // Only start the external signer once
+        let mut external_signer = signer_cmd.map(ExternalSigner::new).transpose()?;
+        if let Some(signer) = external_signer.as_ref() {
+            signer.check_account(&from_lock_arg)?;
+        }
         let mut path_map: HashMap<H160, DerivationPath> = Default::default();
         let (change_address_payload, change_path) =
             if let Some(last_change_address) = last_change_address_opt.as_ref() {
                 // Behave like HD wallet
                 let change_last =
                     H160::from_slice(last_change_address.payload().args().as_ref()).unwrap();
-                let key_set = self.plugin_mgr.keystore_handler().derived_key_set(
-                    from_lock_arg.clone(),
-                    receiving_address_length,
-                    change_last.clone(),
-                    DERIVE_CHANGE_ADDRESS_MAX_LEN,
-                    None,
-                )?;
+                let key_set = if let Some(signer) = external_signer.as_mut() {
+                    signer.derived_key_set(
+                        receiving_address_length,
+                        &change_last,
+                        DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                    )?
+                } else {
+                    self.plugin_mgr.keystore_handler().derived_key_set(
+                        from_lock_arg.clone(),
+                        receiving_address_length,
+                        change_last.clone(),
+                        DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                        None,
+                    )?
+                };
                 let mut change_path_opt = None;
                 for (path, hash160) in key_set
                     .external
@@ -287,6 +583,10 @@ impl<'a> WalletSubCommand<'a> {
                         change_path_opt = Some(path.clone());
                     }
                     path_map.insert(hash160.clone(), path);
+                    // The first receiving key is the id of a watch-only account
+                    if hash160 == from_lock_arg {
+                        continue;
+                    }
                     let payload = AddressPayload::from_pubkey_hash(hash160);
                     lock_scripts.push((
                         Script::from(&payload),
@@ -308,6 +608,8 @@ impl<'a> WalletSubCommand<'a> {
         let get_signer = || -> Result<Box<dyn Signer>, String> {
             if let Some(privkey) = from_privkey.as_ref() {
                 Ok(Box::new(privkey.clone()))
+            } else if let Some(signer) = external_signer.as_ref() {
+                Ok(Box::new(signer.clone()))
             } else {
                 let mut signer = KeyStoreHandlerSigner::new(
                     self.plugin_mgr.keystore_handler(),
@@ -374,176 +676,1133 @@ impl<'a> WalletSubCommand<'a> {
             }
         }
 
+        let change_lock_script = Script::from(&change_address_payload);
+        let min_change_capacity = CellOutput::new_builder()
+            .lock(change_lock_script.clone())
+            .build()
+            .occupied_capacity(Capacity::zero())
+            .expect("change cell capacity")
+            .as_u64();
         let balancer = CapacityBalancer {
             fee_rate: FeeRate::from_u64(fee_rate),
-            change_lock_script: Some(Script::from(&change_address_payload)),
+            change_lock_script: Some(change_lock_script),
             capacity_provider: CapacityProvider::new(lock_scripts),
             force_small_change_as_fee,
         };
         let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
-        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        let mut cell_collector = CoinSelectionCollector::new(
+            DefaultCellCollector::new(self.rpc_client.url()),
+            coin_selection,
+            use_cells.clone(),
+            exclude_cells,
+        );
+        // The estimated transaction fee used by the coin selection
+        let fee_margin = fee_rate * COIN_SELECTION_TX_SIZE / 1000;
         let header_dep_resolver = DefaultHeaderDepResolver::new(self.rpc_client.url());
 
-        // Add outputs
-        let placeholder_type_script = if is_type_id {
-            Some(
-                Script::new_builder()
-                    .code_hash(TYPE_ID_CODE_HASH.pack())
-                    .hash_type(ScriptHashType::Type.into())
-                    .args(Bytes::from(vec![0u8; 32]).pack())
-                    .build(),
-            )
+        let outputs_validator = if is_type_id || skip_check || skip_check_to_address {
+            Some(json_types::OutputsValidator::Passthrough)
         } else {
             None
         };
-        let to_output = CellOutput::new_builder()
-            .capacity(Capacity::shannons(to_capacity).pack())
-            .lock(to_address.payload().into())
-            .type_(placeholder_type_script.pack())
-            .build();
-        let builder = CapacityTransferBuilder::new(vec![(to_output, to_data)]);
-        let mut tx = builder
-            .build_balanced(
-                &mut cell_collector,
-                &genesis_info.cell_dep_resolver,
-                &header_dep_resolver,
-                &tx_dep_provider,
-                &balancer,
-                &unlockers,
-            )
-            .map_err(|err| {
-                if balancer.force_small_change_as_fee.is_none() {
-                    if let TxBuilderError::BalanceCapacity(BalanceTxCapacityError::CapacityNotEnough(ref msg)) =
-                        err
-                    {
-                        let prefix = "can not create change cell, left capacity=";
-                        if msg.contains(prefix) {
-                            let left_capacity = HumanCapacity::from_str(&msg[prefix.len()..]);
-                            if let Ok(left_capacity) = left_capacity {
-                                let suggest_capacity = HumanCapacity(left_capacity.0 + to_capacity);
-
-                                return format!("{}, try to transfer {} or try parameter `--max-tx-fee` to make small left capacity as transaction fee", err, suggest_capacity);
-                            }
-                        }
-                    }
-                }
-                map_tx_builder_error_2_str(balancer.force_small_change_as_fee.is_none(), err)
-            })?;
-        if is_type_id {
-            let mut blake2b = new_blake2b();
-            let first_cell_input = tx.inputs().into_iter().next().expect("inputs empty");
-            blake2b.update(first_cell_input.as_slice());
-            blake2b.update(&0u64.to_le_bytes());
-            let mut ret = [0; 32];
-            blake2b.finalize(&mut ret);
-            let type_script = Script::new_builder()
-                .code_hash(TYPE_ID_CODE_HASH.pack())
-                .hash_type(ScriptHashType::Type.into())
-                .args(Bytes::from(ret.to_vec()).pack())
-                .build();
-            let mut outputs = tx.outputs().into_iter().collect::<Vec<_>>();
-            outputs[0] = tx
-                .output(0)
-                .expect("first output")
-                .as_builder()
-                .type_(Some(type_script).pack())
-                .build();
-            tx = tx.as_advanced_builder().set_outputs(outputs).build();
-        }
-        let (tx, still_locked_groups) =
-            unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
-        assert!(still_locked_groups.is_empty());
-
-        let outputs_validator = if is_type_id || skip_check || skip_check_to_address {
-            Some(json_types::OutputsValidator::Passthrough)
+        // Only check the limits when the targets may be split
+        let limits = if outputs.len() > 1 {
+            Some(TxLimits::from_consensus(self.rpc_client)?)
         } else {
             None
         };
+        let chunker = TxChunker {
+            len: outputs.len(),
+            max_chunk_len: outputs.len(),
+            limits,
+            // The transaction will be signed by `tx sign-file` in build only
+            // mode, the cycles can only be checked after it is signed
+            check_cycles: !build_only,
+            outputs_validator: outputs_validator.clone(),
+        };
 
-        let tx_hash = self
-            .rpc_client
-            .send_transaction(tx.data(), outputs_validator)
-            .map_err(|err| format!("Send transaction error: {}", err))?;
-        assert_eq!(tx.hash(), tx_hash.pack());
-        Ok(tx)
-    }
+        let mut batches = Vec::new();
+        let result = chunker.run(
+            self.rpc_client,
+            &mut cell_collector,
+            |index| format!("transfer target #{}", index),
+            |_rpc_client, chunk_cell_collector, range| {
+                let chunk_capacity: u64 = outputs[range.clone()]
+                    .iter()
+                    .map(|(output, _)| {
+                        let capacity: u64 = output.capacity().unpack();
+                        capacity
+                    })
+                    .sum();
+                let target = match coin_selection {
+                    CoinSelectionStrategy::BranchAndBound => SelectionTarget {
+                        min: chunk_capacity + fee_margin,
+                        max: force_small_change_as_fee.map(|max_fee| chunk_capacity + max_fee),
+                    },
+                    _ => SelectionTarget {
+                        min: chunk_capacity + fee_margin + min_change_capacity,
+                        max: None,
+                    },
+                };
+                chunk_cell_collector.set_target(target);
+                let builder = CapacityTransferBuilder::new(outputs[range.clone()].to_vec());
+                let mut tx = builder
+                    .build_balanced(
+                        chunk_cell_collector,
+                        &genesis_info.cell_dep_resolver,
+                        &header_dep_resolver,
+                        &tx_dep_provider,
+                        &balancer,
+                        &unlockers,
+                    )
+                    .map_err(|err| {
+                        if balancer.force_small_change_as_fee.is_none() && outputs.len() == 1 {
+                            if let TxBuilderError::BalanceCapacity(BalanceTxCapacityError::CapacityNotEnough(ref msg)) =
+                                err
+                            {
+                                let prefix = "can not create change cell, left capacity=";
+                                if msg.contains(prefix) {
+                                    let left_capacity = HumanCapacity::from_str(&msg[prefix.len()..]);
+                                    if let Ok(left_capacity) = left_capacity {
+                                        let to_capacity: u64 = outputs[0].0.capacity().unpack();
+                                        let suggest_capacity = HumanCapacity(left_capacity.0 + to_capacity);
 
-    pub fn get_capacity(&mut self, lock_scripts: Vec<Script>) -> Result<(u64, u64, u64), String> {
-        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
-        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
-        let mut total_all = 0;
-        let mut total_immature = 0;
-        let mut total_dao = 0;
-        for script in lock_scripts {
-            let mut query = CellQueryOptions::new_lock(script);
-            query.maturity = MaturityOption::Both;
-            query.min_total_capacity = u64::max_value();
-            let (cells, total_capacity) = cell_collector
-                .collect_live_cells(&query, false)
-                .map_err(|err| err.to_string())?;
-            total_all += total_capacity;
-            for cell in &cells {
-                let capacity: u64 = cell.output.capacity().unpack();
-                if !is_mature(cell, max_mature_number) {
-                    total_immature += capacity;
+                                        return format!("{}, try to transfer {} or try parameter `--max-tx-fee` to make small left capacity as transaction fee", err, suggest_capacity);
+                                    }
+                                }
+                            }
+                        }
+                        map_tx_builder_error_2_str(balancer.force_small_change_as_fee.is_none(), err)
+                    })?;
+                if range.start == 0 {
+                    check_use_cells(
+                        &use_cells,
+                        tx.inputs().into_iter().map(|input| input.previous_output()),
+                    )?;
                 }
-                if cell
-                    .output
-                    .type_()
-                    .to_opt()
-                    .map(|script| script.code_hash().as_slice() == DAO_TYPE_HASH.as_bytes())
-                    .unwrap_or(false)
-                {
-                    total_dao += capacity;
+                if is_type_id {
+                    let mut blake2b = new_blake2b();
+                    let first_cell_input = tx.inputs().into_iter().next().expect("inputs empty");
+                    blake2b.update(first_cell_input.as_slice());
+                    blake2b.update(&0u64.to_le_bytes());
+                    let mut ret = [0; 32];
+                    blake2b.finalize(&mut ret);
+                    let type_script = Script::new_builder()
+                        .code_hash(TYPE_ID_CODE_HASH.pack())
+                        .hash_type(ScriptHashType::Type.into())
+                        .args(Bytes::from(ret.to_vec()).pack())
+                        .build();
+                    let mut outputs = tx.outputs().into_iter().collect::<Vec<_>>();
+                    outputs[0] = tx
+                        .output(0)
+                        .expect("first output")
+                        .as_builder()
+                        .type_(Some(type_script).pack())
+                        .build();
+                    tx = tx.as_advanced_builder().set_outputs(outputs).build();
+                }
+                if build_only {
+                    // The transaction will be signed by `tx sign-file`
+                    return Ok(tx);
+                }
+                let (tx, still_locked_groups) =
+                    unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
+                if !still_locked_groups.is_empty() {
+                    return Err(format!(
+                        "Can not sign all the inputs ({} script groups left)",
+                        still_locked_groups.len()
+                    ));
+                }
+                Ok(tx)
+            },
+            |rpc_client, tx, range| {
+                if !build_only {
+                    let tx_hash = rpc_client
+                        .send_transaction(tx.data(), outputs_validator.clone())
+                        .map_err(|err| format!("Send transaction error: {}", err))?;
+                    assert_eq!(tx.hash(), tx_hash.pack());
                 }
+                batches.push(TransferBatch {
+                    tx: tx.clone(),
+                    targets: range.collect(),
+                });
+                Ok(())
+            },
+        );
+        // Keep the sent transactions when a later chunk fails
+        if let Err(err) = result {
+            if build_only || batches.is_empty() {
+                return Err(err);
             }
+            return Err(format!(
+                "{}\nThe transactions below are already sent, remove their targets (0-based row index) before retrying:\n{}",
+                err,
+                sent_batches_report(&batches)
+            ));
         }
-        Ok((total_all, total_immature, total_dao))
+        Ok(batches)
     }
 
-    pub fn get_live_cells(
-        &mut self,
-        script: Script,
-        script_type: PrimaryScriptType,
-        from_number: u64,
-        to_number: u64,
-        limit: u32,
-    ) -> Result<Vec<LiveCell>, String> {
-        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
-
-        let mut query = CellQueryOptions::new(script, script_type);
-        query.maturity = MaturityOption::Both;
-        query.min_total_capacity = u64::max_value();
-        query.limit = Some(limit);
-        query.block_range = Some(ValueRangeOption::new(from_number, to_number));
-        let (cells, _total_capacity) = cell_collector
-            .collect_live_cells(&query, false)
-            .map_err(|err| err.to_string())?;
+    /// Replace a pending transaction with a higher fee rate one, return the
+    /// new transaction and the old/new transaction fee.
+    pub fn bump_fee(&mut self, args: BumpFeeArgs) -> Result<(TransactionView, u64, u64), String> {
+        let BumpFeeArgs {
+            privkey_path,
+            from_account,
+            password,
+            derive_receiving_address_length,
+            derive_change_address,
+            tx_hash,
+            fee_rate,
+            signer_cmd,
+        } = args;
 
-        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
-        let live_cells = cells
-            .into_iter()
-            .map(|cell| {
-                let mature = is_mature(&cell, max_mature_number);
-                LiveCell {
-                    info: to_live_cell_info(&cell),
-                    mature,
-                }
+        let network_type = get_network_type(self.rpc_client)?;
+        let from_privkey: Option<PrivkeyWrapper> = privkey_path
+            .map(|input| PrivkeyPathParser.parse(&input))
+            .transpose()?;
+        let signer_cmd: Option<PathBuf> = signer_cmd
+            .map(|input| FilePathParser::new(true).parse(&input))
+            .transpose()?;
+        let from_account: Option<H160> = from_account
+            .map(|input| {
+                FixedHashParser::<H160>::default()
+                    .parse(&input)
+                    .or_else(|err| {
+                        let result: Result<Address, String> = AddressParser::new_sighash()
+                            .set_network(network_type)
+                            .parse(&input);
+                        result
+                            .map(|address| H160::from_slice(&address.payload().args()).unwrap())
+                            .map_err(|_| err)
+                    })
             })
-            .collect::<Vec<_>>();
-        Ok(live_cells)
-    }
-}
-
-impl<'a> CliSubCommand for WalletSubCommand<'a> {
-    fn process(&mut self, matches: &ArgMatches, debug: bool) -> Result<Output, String> {
-        match matches.subcommand() {
-            ("transfer", Some(m)) => {
-                let to_data = get_to_data(m)?;
-                let args = TransferArgs {
-                    privkey_path: m.value_of("privkey-path").map(|s| s.to_string()),
-                    from_account: m.value_of("from-account").map(|s| s.to_string()),
-                    from_locked_address: m.value_of("from-locked-address").map(|s| s.to_string()),
+            .transpose()?;
+        let tx_hash: H256 = FixedHashParser::<H256>::default().parse(&tx_hash)?;
+        let fee_rate: u64 = FromStrParser::<u64>::default().parse(&fee_rate)?;
+        let receiving_address_length: u32 = derive_receiving_address_length
+            .map(|input| FromStrParser::<u32>::default().parse(&input))
+            .transpose()?
+            .unwrap_or(1000);
+        let last_change_address_opt: Option<Address> = derive_change_address
+            .map(|input| {
+                AddressParser::default()
+                    .set_network(network_type)
+                    .parse(&input)
+            })
+            .transpose()?;
+
+        let tx_with_status = self
+            .rpc_client
+            .get_transaction(tx_hash.clone())?
+            .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?;
+        if tx_with_status.tx_status.status != json_types::Status::Pending {
+            return Err(format!(
+                "Only pending transaction can be replaced, the status of {:#x} is: {:?}",
+                tx_hash, tx_with_status.tx_status.status
+            ));
+        }
+        // Check the RBF rules of the tx pool early: RBF is enabled, the
+        // transaction is not proposed and the fee rate pays the RBF fee.
+        let pool_info = self.rpc_client.tx_pool_info()?;
+        if pool_info.min_rbf_rate <= pool_info.min_fee_rate {
+            return Err(format!(
+                "RBF is disabled by the node (min_rbf_rate: {}, min_fee_rate: {})",
+                pool_info.min_rbf_rate, pool_info.min_fee_rate
+            ));
+        }
+        if fee_rate < pool_info.min_rbf_rate {
+            return Err(format!(
+                "The fee rate {} is less than the min_rbf_rate {} of the tx pool",
+                fee_rate, pool_info.min_rbf_rate
+            ));
+        }
+        let pool_tx_info = self.rpc_client.get_pool_tx_detail_info(tx_hash.clone())?;
+        if pool_tx_info.entry_status == "proposed" {
+            return Err(format!(
+                "The transaction {:#x} is already proposed, it can not be replaced",
+                tx_hash
+            ));
+        }
+        let old_tx = packed::Transaction::from(
+            tx_with_status
+                .transaction
+                .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?
+                .inner,
+        )
+        .into_view();
+
+        let (from_address_payload, password) = if let Some(from_privkey) = from_privkey.as_ref() {
+            let from_pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, from_privkey);
+            (AddressPayload::from_pubkey(&from_pubkey), None)
+        } else {
+            let password = if let Some(password) = password {
+                Some(password)
+            } else if signer_cmd.is_none() && self.plugin_mgr.keystore_require_password() {
+                Some(read_password(false, None)?)
+            } else {
+                None
+            };
+            (
+                AddressPayload::from_pubkey_hash(from_account.unwrap()),
+                password,
+            )
+        };
+        let from_lock_arg = H160::from_slice(from_address_payload.args().as_ref()).unwrap();
+
+        let genesis_info = self.genesis_info()?;
+        let sighash_placeholder_witness = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+            .build();
+        let mut lock_scripts = vec![(
+            Script::from(&from_address_payload),
+            sighash_placeholder_witness.clone(),
+            SinceSource::default(),
+        )];
+        let mut external_signer = signer_cmd.map(ExternalSigner::new).transpose()?;
+        if let Some(signer) = external_signer.as_ref() {
+            signer.check_account(&from_lock_arg)?;
+        }
+        let change_path = if let Some(last_change_address) = last_change_address_opt.as_ref() {
+            // Behave like HD wallet
+            let change_last =
+                H160::from_slice(last_change_address.payload().args().as_ref()).unwrap();
+            let key_set = if let Some(signer) = external_signer.as_mut() {
+                signer.derived_key_set(
+                    receiving_address_length,
+                    &change_last,
+                    DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                )?
+            } else {
+                self.plugin_mgr.keystore_handler().derived_key_set(
+                    from_lock_arg.clone(),
+                    receiving_address_length,
+                    change_last.clone(),
+                    DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                    None,
+                )?
+            };
+            let mut change_path_opt = None;
+            for (path, hash160) in key_set
+                .external
+                .into_iter()
+                .chain(key_set.change.into_iter())
+            {
+                if hash160 == change_last {
+                    change_path_opt = Some(path);
+                }
+                if hash160 == from_lock_arg {
+                    continue;
+                }
+                let payload = AddressPayload::from_pubkey_hash(hash160);
+                lock_scripts.push((
+                    Script::from(&payload),
+                    sighash_placeholder_witness.clone(),
+                    SinceSource::default(),
+                ));
+            }
+            change_path_opt.expect("change path not exists")
+        } else {
+            self.plugin_mgr.root_key_path(from_lock_arg.clone())?
+        };
+
+        let signer: Box<dyn Signer> = if let Some(privkey) = from_privkey.as_ref() {
+            Box::new(privkey.clone())
+        } else if let Some(signer) = external_signer {
+            Box::new(signer)
+        } else {
+            let mut signer = KeyStoreHandlerSigner::new(
+                self.plugin_mgr.keystore_handler(),
+                Box::new(DefaultTransactionDependencyProvider::new(
+                    self.rpc_client.url(),
+                    0,
+                )),
+            );
+            if let Some(password) = password.as_ref() {
+                signer.set_password(from_lock_arg.clone(), password.clone());
+            }
+            if let Some(last_change_address) = last_change_address_opt.as_ref() {
+                let change_last =
+                    H160::from_slice(last_change_address.payload().args().as_ref()).unwrap();
+                signer.cache_key_set(
+                    from_lock_arg.clone(),
+                    receiving_address_length,
+                    change_last,
+                    DERIVE_CHANGE_ADDRESS_MAX_LEN,
+                )?;
+            }
+            signer.set_change_path(from_lock_arg.clone(), change_path.to_string());
+            Box::new(signer)
+        };
+        let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
+        unlockers.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+            Box::new(SecpSighashUnlocker::new(SecpSighashScriptSigner::new(
+                signer,
+            ))),
+        );
+
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        let get_tx_fee = |tx: &TransactionView| -> Result<u64, String> {
+            let mut input_total: u64 = 0;
+            for input in tx.inputs() {
+                let output = tx_dep_provider
+                    .get_cell(&input.previous_output())
+                    .map_err(|err| err.to_string())?;
+                let capacity: u64 = output.capacity().unpack();
+                input_total += capacity;
+            }
+            let output_total = tx
+                .outputs_capacity()
+                .map_err(|err| err.to_string())?
+                .as_u64();
+            input_total
+                .checked_sub(output_total)
+                .ok_or_else(|| "Input capacity is less than output capacity".to_string())
+        };
+        let old_tx_fee = get_tx_fee(&old_tx)?;
+
+        // The last output locked by the sender is treated as change output, it
+        // will be re-created by the balancer with the new fee rate.
+        let change_idx_opt = old_tx
+            .outputs()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, output)| {
+                output.type_().is_none()
+                    && lock_scripts
+                        .iter()
+                        .any(|(lock_script, _, _)| lock_script == &output.lock())
+            })
+            .map(|(idx, _)| idx)
+            .last();
+        let change_lock_script = change_idx_opt
+            .map(|idx| old_tx.output(idx).expect("change output").lock())
+            .unwrap_or_else(|| Script::from(&from_address_payload));
+        let (outputs, outputs_data): (Vec<_>, Vec<_>) = old_tx
+            .outputs_with_data_iter()
+            .enumerate()
+            .filter(|(idx, _)| Some(*idx) != change_idx_opt)
+            .map(|(_, (output, data))| (output, data.pack()))
+            .unzip();
+        // Remove the old signatures, keep other fields of the witness
+        let witnesses = old_tx
+            .witnesses()
+            .into_iter()
+            .map(|witness| {
+                WitnessArgs::from_slice(&witness.raw_data())
+                    .map(|witness_args| {
+                        witness_args
+                            .as_builder()
+                            .lock(None::<Bytes>.pack())
+                            .build()
+                            .as_bytes()
+                            .pack()
+                    })
+                    .unwrap_or(witness)
+            })
+            .collect::<Vec<_>>();
+        let base_tx = old_tx
+            .as_advanced_builder()
+            .set_outputs(outputs)
+            .set_outputs_data(outputs_data)
+            .set_witnesses(witnesses)
+            .build();
+        let (base_tx, _) = fill_placeholder_witnesses(base_tx, &tx_dep_provider, &unlockers)
+            .map_err(|err| err.to_string())?;
+
+        let balancer = CapacityBalancer {
+            fee_rate: FeeRate::from_u64(fee_rate),
+            change_lock_script: Some(change_lock_script),
+            capacity_provider: CapacityProvider::new(lock_scripts),
+            force_small_change_as_fee: None,
+        };
+        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        // The inputs of the replaced transaction are still live cells in indexer
+        let tip_number = self.rpc_client.get_tip_block_number()?;
+        for input in old_tx.inputs() {
+            cell_collector
+                .lock_cell(input.previous_output(), tip_number)
+                .map_err(|err| err.to_string())?;
+        }
+        let header_dep_resolver = DefaultHeaderDepResolver::new(self.rpc_client.url());
+        let tx = balance_tx_capacity(
+            &base_tx,
+            &balancer,
+            &mut cell_collector,
+            &tx_dep_provider,
+            &genesis_info.cell_dep_resolver,
+            &header_dep_resolver,
+        )
+        .map_err(|err| map_tx_builder_error_2_str(true, err.into()))?;
+        let new_tx_fee = get_tx_fee(&tx)?;
+        // The replacement pays the old fee plus its own size at min_rbf_rate
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        let min_replace_fee = FeeRate::from_u64(pool_info.min_rbf_rate)
+            .fee(tx_size)
+            .as_u64()
+            + old_tx_fee;
+        if new_tx_fee < min_replace_fee {
+            return Err(format!(
+                "New transaction fee {:#} is less than the minimal replace fee {:#} (old fee {:#} + size {} at min_rbf_rate {}), try a higher fee rate",
+                HumanCapacity::from(new_tx_fee),
+                HumanCapacity::from(min_replace_fee),
+                HumanCapacity::from(old_tx_fee),
+                tx_size,
+                pool_info.min_rbf_rate,
+            ));
+        }
+
+        let (tx, still_locked_groups) =
+            unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
+        if !still_locked_groups.is_empty() {
+            return Err(format!(
+                "Can not sign all the inputs ({} script groups left), only sighash inputs of the sender are supported",
+                still_locked_groups.len()
+            ));
+        }
+        // The outputs are already accepted by the replaced transaction
+        let new_tx_hash = self
+            .rpc_client
+            .send_transaction(tx.data(), Some(json_types::OutputsValidator::Passthrough))
+            .map_err(|err| format!("Send transaction error: {}", err))?;
+        assert_eq!(tx.hash(), new_tx_hash.pack());
+        Ok((tx, old_tx_fee, new_tx_fee))
+    }
+
+    /// Merge the small plain capacity cells of the sender (including derived
+    /// addresses of an HD account) into one cell per transaction.
+    pub fn consolidate(&mut self, args: ConsolidateArgs) -> Result<ConsolidateResult, String> {
+        let ConsolidateArgs {
+            privkey_path,
+            from_account,
+            password,
+            derive_receiving_address_length,
+            derive_change_address_length,
+            to_address,
+            max_inputs,
+            min_cell_capacity,
+            fee_rate,
+            dry_run,
+            signer_cmd,
+        } = args;
+
+        let network_type = get_network_type(self.rpc_client)?;
+        let from_privkey: Option<PrivkeyWrapper> = privkey_path
+            .map(|input| PrivkeyPathParser.parse(&input))
+            .transpose()?;
+        let signer_cmd: Option<PathBuf> = signer_cmd
+            .map(|input| FilePathParser::new(true).parse(&input))
+            .transpose()?;
+        let from_account: Option<H160> = from_account
+            .map(|input| {
+                FixedHashParser::<H160>::default()
+                    .parse(&input)
+                    .or_else(|err| {
+                        let result: Result<Address, String> = AddressParser::new_sighash()
+                            .set_network(network_type)
+                            .parse(&input);
+                        result
+                            .map(|address| H160::from_slice(&address.payload().args()).unwrap())
+                            .map_err(|_| err)
+                    })
+            })
+            .transpose()?;
+        let to_address: Option<Address> = to_address
+            .map(|input| {
+                AddressParser::default()
+                    .set_network(network_type)
+                    .parse(&input)
+            })
+            .transpose()?;
+        let receiving_address_length: u32 = derive_receiving_address_length
+            .map(|input| FromStrParser::<u32>::default().parse(&input))
+            .transpose()?
+            .unwrap_or(1000);
+        let change_address_length: u32 = derive_change_address_length
+            .map(|input| FromStrParser::<u32>::default().parse(&input))
+            .transpose()?
+            .unwrap_or(1000);
+        let max_inputs: usize = FromStrParser::<usize>::default().parse(&max_inputs)?;
+        if max_inputs < 2 {
+            return Err("max-inputs must be greater than 1".to_string());
+        }
+        let min_cell_capacity: u64 = CapacityParser.parse(&min_cell_capacity)?.into();
+        let fee_rate: u64 = FromStrParser::<u64>::default().parse(&fee_rate)?;
+
+        let (from_address_payload, password) = if let Some(from_privkey) = from_privkey.as_ref() {
+            let from_pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, from_privkey);
+            (AddressPayload::from_pubkey(&from_pubkey), None)
+        } else {
+            let password = if let Some(password) = password {
+                Some(password)
+            } else if !dry_run
+                && signer_cmd.is_none()
+                && self.plugin_mgr.keystore_require_password()
+            {
+                Some(read_password(false, None)?)
+            } else {
+                None
+            };
+            (
+                AddressPayload::from_pubkey_hash(from_account.clone().unwrap()),
+                password,
+            )
+        };
+        let from_lock_arg = H160::from_slice(from_address_payload.args().as_ref()).unwrap();
+        let mut lock_scripts = vec![Script::from(&from_address_payload)];
+        let mut external_signer = signer_cmd.map(ExternalSigner::new).transpose()?;
+        if let Some(signer) = external_signer.as_ref() {
+            signer.check_account(&from_lock_arg)?;
+        }
+        if from_account.is_some() {
+            let key_set = if let Some(signer) = external_signer.as_mut() {
+                signer.derived_key_set_by_index(receiving_address_length, change_address_length)
+            } else {
+                self.plugin_mgr
+                    .keystore_handler()
+                    .derived_key_set_by_index(
+                        from_lock_arg.clone(),
+                        0,
+                        receiving_address_length,
+                        0,
+                        change_address_length,
+                        None,
+                    )?
+            };
+            for (_, hash160) in key_set.external.iter().chain(key_set.change.iter()) {
+                if hash160 == &from_lock_arg {
+                    continue;
+                }
+                let payload = AddressPayload::from_pubkey_hash(hash160.clone());
+                lock_scripts.push(Script::from(&payload));
+            }
+        }
+        let to_lock_script = to_address
+            .map(|address| Script::from(address.payload()))
+            .unwrap_or_else(|| Script::from(&from_address_payload));
+
+        // Collect the small plain capacity cells
+        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        let mut cells = Vec::new();
+        for lock_script in lock_scripts {
+            let mut query = CellQueryOptions::new_lock(lock_script);
+            query.secondary_script_len_range = Some(ValueRangeOption::new_exact(0));
+            query.data_len_range = Some(ValueRangeOption::new_exact(0));
+            query.maturity = MaturityOption::Mature;
+            query.min_total_capacity = u64::max_value();
+            let (more_cells, _) = cell_collector
+                .collect_live_cells(&query, false)
+                .map_err(|err| err.to_string())?;
+            cells.extend(more_cells.into_iter().filter(|cell| {
+                let capacity: u64 = cell.output.capacity().unpack();
+                capacity < min_cell_capacity
+            }));
+        }
+        if cells.len() < 2 {
+            return Err(format!(
+                "Only {} cell(s) with capacity less than {:#} found, nothing to consolidate",
+                cells.len(),
+                HumanCapacity::from(min_cell_capacity)
+            ));
+        }
+
+        let genesis_info = self.genesis_info()?;
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        let signer: Box<dyn Signer> = if let Some(privkey) = from_privkey.as_ref() {
+            Box::new(privkey.clone())
+        } else if let Some(signer) = external_signer {
+            Box::new(signer)
+        } else {
+            let mut signer = KeyStoreHandlerSigner::new(
+                self.plugin_mgr.keystore_handler(),
+                Box::new(DefaultTransactionDependencyProvider::new(
+                    self.rpc_client.url(),
+                    0,
+                )),
+            );
+            if let Some(password) = password.as_ref() {
+                signer.set_password(from_lock_arg.clone(), password.clone());
+            }
+            if !dry_run {
+                signer.cache_key_set_by_index(
+                    from_lock_arg.clone(),
+                    receiving_address_length,
+                    change_address_length,
+                )?;
+            }
+            let root_path = self.plugin_mgr.root_key_path(from_lock_arg.clone())?;
+            signer.set_change_path(from_lock_arg.clone(), root_path.to_string());
+            Box::new(signer)
+        };
+        let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
+        unlockers.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+            Box::new(SecpSighashUnlocker::new(SecpSighashScriptSigner::new(
+                signer,
+            ))),
+        );
+
+        let mut txs = Vec::new();
+        // A single cell left in the last chunk is not worth a transaction
+        for chunk in cells.chunks(max_inputs).filter(|chunk| chunk.len() > 1) {
+            let mut build_and_send = || -> Result<ConsolidateTransaction, String> {
+                let input_capacity: u64 = chunk
+                    .iter()
+                    .map(|cell| {
+                        let capacity: u64 = cell.output.capacity().unpack();
+                        capacity
+                    })
+                    .sum();
+                let inputs = chunk
+                    .iter()
+                    .map(|cell| CellInput::new(cell.out_point.clone(), 0))
+                    .collect::<Vec<_>>();
+                let output = CellOutput::new_builder()
+                    .capacity(Capacity::shannons(input_capacity).pack())
+                    .lock(to_lock_script.clone())
+                    .build();
+                let base_tx = TransactionView::new_advanced_builder()
+                    .cell_dep(genesis_info.sighash_dep())
+                    .inputs(inputs)
+                    .output(output.clone())
+                    .output_data(Bytes::new().pack())
+                    .build();
+                let (base_tx, _) =
+                    fill_placeholder_witnesses(base_tx, &tx_dep_provider, &unlockers)
+                        .map_err(|err| err.to_string())?;
+                // The output capacity does not change the transaction size
+                let tx_size = base_tx.data().as_reader().serialized_size_in_block() as u64;
+                let fee = FeeRate::from_u64(fee_rate).fee(tx_size).as_u64();
+                let output_capacity = input_capacity
+                    .checked_sub(fee)
+                    .filter(|capacity| {
+                        output
+                            .occupied_capacity(Capacity::zero())
+                            .map(|occupied| *capacity >= occupied.as_u64())
+                            .unwrap_or(false)
+                    })
+                    .ok_or_else(|| {
+                        format!(
+                            "Total capacity {:#} of the cells is not enough for the output cell and fee {:#}",
+                            HumanCapacity::from(input_capacity),
+                            HumanCapacity::from(fee)
+                        )
+                    })?;
+                let output = output
+                    .as_builder()
+                    .capacity(Capacity::shannons(output_capacity).pack())
+                    .build();
+                let tx = base_tx
+                    .as_advanced_builder()
+                    .set_outputs(vec![output])
+                    .build();
+                let tx = if dry_run {
+                    tx
+                } else {
+                    let (tx, still_locked_groups) = unlock_tx(tx, &tx_dep_provider, &unlockers)
+                        .map_err(|err| err.to_string())?;
+                    if !still_locked_groups.is_empty() {
+                        return Err(format!(
+                            "Can not sign all the inputs ({} script groups left)",
+                            still_locked_groups.len()
+                        ));
+                    }
+                    let tx_hash = self
+                        .rpc_client
+                        .send_transaction(tx.data(), None)
+                        .map_err(|err| format!("Send transaction error: {}", err))?;
+                    assert_eq!(tx.hash(), tx_hash.pack());
+                    tx
+                };
+                Ok(ConsolidateTransaction {
+                    tx,
+                    input_capacity,
+                    fee,
+                })
+            };
+            match build_and_send() {
+                Ok(tx) => txs.push(tx),
+                // Keep the sent transactions in the result, or their hashes are lost
+                Err(err) if !dry_run && !txs.is_empty() => {
+                    return Ok(ConsolidateResult {
+                        transactions: txs,
+                        error: Some(err),
+                    });
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(ConsolidateResult {
+            transactions: txs,
+            error: None,
+        })
+    }
+
+    /// List the transactions related to the lock scripts (sorted by block
+    /// number and transaction index), return the items of the page and
+    /// whether there are more transactions after the page.
+    ///
+    /// The transactions of each lock script are fetched with the indexer cursor
+    /// in the given order and merged, only the transactions up to the page are
+    /// fetched.
+    pub fn history(
+        &mut self,
+        lock_scripts: Vec<Script>,
+        order: Order,
+        page: usize,
+        limit: usize,
+    ) -> Result<(bool, Vec<HistoryItem>), String> {
+        let network_type = get_network_type(self.rpc_client)?;
+        let skip = page
+            .checked_mul(limit)
+            .ok_or_else(|| format!("The page {} is too large for limit {}", page, limit))?;
+        let end = skip
+            .checked_add(limit)
+            .ok_or_else(|| format!("The page {} is too large for limit {}", page, limit))?;
+        let desc = matches!(order, Order::Desc);
+        // Fetch one more transaction to tell whether there are more
+        let fetch_limit = end.saturating_add(1).min(HISTORY_SEARCH_LIMIT as usize) as u32;
+        let mut streams = lock_scripts
+            .iter()
+            .map(|lock_script| HistoryTxStream::new(lock_script.clone()))
+            .collect::<Vec<_>>();
+        let mut related_txs = Vec::new();
+        while related_txs.len() <= end {
+            let mut next_key = None;
+            for stream in streams.iter_mut() {
+                if let Some(key) = stream.peek_key(self.rpc_client, &order, fetch_limit)? {
+                    next_key = match next_key {
+                        Some(next) if (desc && next >= key) || (!desc && next <= key) => Some(next),
+                        _ => Some(key),
+                    };
+                }
+            }
+            let next_key = match next_key {
+                Some(key) => key,
+                None => break,
+            };
+            // The same transaction may be related to several lock scripts
+            let mut merged: Option<HistoryTx> = None;
+            for stream in streams.iter_mut() {
+                if stream.peek_key(self.rpc_client, &order, fetch_limit)? != Some(next_key) {
+                    continue;
+                }
+                let tx = stream.pop().expect("peeked");
+                match merged.as_mut() {
+                    Some(merged) => {
+                        merged.inputs.extend(tx.inputs);
+                        merged.outputs.extend(tx.outputs);
+                    }
+                    None => merged = Some(tx),
+                }
+            }
+            related_txs.push(merged.expect("merged"));
+        }
+        let more = related_txs.len() > end;
+
+        let lock_hashes: HashSet<Byte32> = lock_scripts
+            .iter()
+            .map(|lock_script| lock_script.calc_script_hash())
+            .collect();
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        let mut items = Vec::new();
+        for HistoryTx {
+            tx_hash,
+            block_number,
+            inputs,
+            outputs,
+            ..
+        } in related_txs.into_iter().skip(skip).take(limit)
+        {
+            let tx = tx_dep_provider
+                .get_transaction(&tx_hash.pack())
+                .map_err(|err| err.to_string())?;
+            let mut input_capacity: u64 = 0;
+            let mut input_locks = Vec::new();
+            for (idx, input) in tx.inputs().into_iter().enumerate() {
+                if input.previous_output().is_null() {
+                    // cellbase
+                    continue;
+                }
+                let output = tx_dep_provider
+                    .get_cell(&input.previous_output())
+                    .map_err(|err| err.to_string())?;
+                if inputs.contains(&(idx as u32)) {
+                    let capacity: u64 = output.capacity().unpack();
+                    input_capacity += capacity;
+                }
+                input_locks.push(output.lock());
+            }
+            let mut output_capacity: u64 = 0;
+            for idx in &outputs {
+                let output = tx
+                    .output(*idx as usize)
+                    .ok_or_else(|| format!("Invalid output index {} of {:#x}", idx, tx_hash))?;
+                let capacity: u64 = output.capacity().unpack();
+                output_capacity += capacity;
+            }
+            // The counterparties of outgoing transaction are the receivers,
+            // otherwise they are the senders.
+            let other_locks = if input_capacity > 0 {
+                tx.outputs()
+                    .into_iter()
+                    .map(|output| output.lock())
+                    .collect::<Vec<_>>()
+            } else {
+                input_locks
+            };
+            let mut counterparties = Vec::new();
+            for lock_script in other_locks {
+                if lock_hashes.contains(&lock_script.calc_script_hash()) {
+                    continue;
+                }
+                let address =
+                    Address::new(network_type, AddressPayload::from(lock_script), true).to_string();
+                if !counterparties.contains(&address) {
+                    counterparties.push(address);
+                }
+            }
+            let timestamp = self
+                .rpc_client
+                .get_header_by_number(block_number)?
+                .map(|header| header.inner.timestamp.0)
+                .unwrap_or_default();
+            items.push(HistoryItem {
+                tx_hash,
+                block_number,
+                timestamp,
+                capacity_change: output_capacity as i128 - input_capacity as i128,
+                counterparties,
+            });
+        }
+        Ok((more, items))
+    }
+
+    pub fn get_capacity(&mut self, lock_scripts: Vec<Script>) -> Result<(u64, u64, u64), String> {
+        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
+        let mut total_all = 0;
+        let mut total_immature = 0;
+        let mut total_dao = 0;
+        for script in lock_scripts {
+            let mut query = CellQueryOptions::new_lock(script);
+            query.maturity = MaturityOption::Both;
+            query.min_total_capacity = u64::max_value();
+            let (cells, total_capacity) = cell_collector
+                .collect_live_cells(&query, false)
+                .map_err(|err| err.to_string())?;
+            total_all += total_capacity;
+            for cell in &cells {
+                let capacity: u64 = cell.output.capacity().unpack();
+                if !is_mature(cell, max_mature_number) {
+                    total_immature += capacity;
+                }
+                if cell
+                    .output
+                    .type_()
+                    .to_opt()
+                    .map(|script| script.code_hash().as_slice() == DAO_TYPE_HASH.as_bytes())
+                    .unwrap_or(false)
+                {
+                    total_dao += capacity;
+                }
+            }
+        }
+        Ok((total_all, total_immature, total_dao))
+    }
+
+    /// Get the capacity of the time locked 1-of-1 multisig cells (created by
+    /// `transfer --lock-until`) of the sighash addresses, return the locked
+    /// and unlocked capacity.
+    pub fn get_timelock_capacity(
+        &mut self,
+        sighash_payloads: Vec<AddressPayload>,
+    ) -> Result<(u64, u64), String> {
+        let tip_header: core::HeaderView = self.rpc_client.get_tip_header()?.into();
+        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        // The headers of the blocks the cells are committed in
+        let mut headers: HashMap<u64, core::HeaderView> = HashMap::default();
+        let mut total_locked = 0;
+        let mut total_unlocked = 0;
+        for payload in sighash_payloads {
+            // Search all the multisig addresses with since by args prefix
+            let script = Script::new_builder()
+                .code_hash(MULTISIG_TYPE_HASH.pack())
+                .hash_type(ScriptHashType::Type.into())
+                .args(Bytes::from(single_multisig_hash(&payload).to_vec()).pack())
+                .build();
+            let mut query = CellQueryOptions::new_lock(script);
+            query.script_search_mode = Some(SearchMode::Prefix);
+            query.maturity = MaturityOption::Both;
+            query.min_total_capacity = u64::max_value();
+            let (cells, _) = cell_collector
+                .collect_live_cells(&query, false)
+                .map_err(|err| err.to_string())?;
+            for cell in cells {
+                let args = cell.output.lock().args().raw_data();
+                if args.len() != 28 {
+                    continue;
+                }
+                let mut since_bytes = [0u8; 8];
+                since_bytes.copy_from_slice(&args[20..]);
+                let since = u64::from_le_bytes(since_bytes);
+                // The relative since is based on the block the cell is committed in
+                let input_header = if Since::from_raw_value(since).is_absolute() {
+                    None
+                } else {
+                    if let Entry::Vacant(entry) = headers.entry(cell.block_number) {
+                        let header = self
+                            .rpc_client
+                            .get_header_by_number(cell.block_number)?
+                            .ok_or_else(|| format!("Block #{} not found", cell.block_number))?;
+                        entry.insert(header.into());
+                    }
+                    headers.get(&cell.block_number)
+                };
+                // Unknown since (invalid flags or median time) is treated as locked
+                let unlocked = since_satisfied(self.rpc_client, since, input_header, &tip_header)?
+                    .unwrap_or(false);
+                let capacity: u64 = cell.output.capacity().unpack();
+                if unlocked {
+                    total_unlocked += capacity;
+                } else {
+                    total_locked += capacity;
+                }
+            }
+        }
+        Ok((total_locked, total_unlocked))
+    }
+
+    pub fn get_live_cells(
+        &mut self,
+        script: Script,
+        script_type: PrimaryScriptType,
+        from_number: u64,
+        to_number: u64,
+        limit: u32,
+    ) -> Result<Vec<LiveCell>, String> {
+        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+
+        let mut query = CellQueryOptions::new(script, script_type);
+        query.maturity = MaturityOption::Both;
+        query.min_total_capacity = u64::max_value();
+        query.limit = Some(limit);
+        query.block_range = Some(ValueRangeOption::new(from_number, to_number));
+        let (cells, _total_capacity) = cell_collector
+            .collect_live_cells(&query, false)
+            .map_err(|err| err.to_string())?;
+
+        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
+        let live_cells = cells
+            .into_iter()
+            .map(|cell| {
+                let mature = is_mature(&cell, max_mature_number);
+                LiveCell {
+                    info: to_live_cell_info(&cell),
+                    mature,
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(live_cells)
+    }
+}
+
+impl<'a> CliSubCommand for WalletSubCommand<'a> {
+    fn process(&mut self, matches: &ArgMatches, debug: bool) -> Result<Output, String> {
+        match matches.subcommand() {
+            ("transfer", Some(m)) => {
+                if let Some(batch_file) = m.value_of("batch-file") {
+                    let targets = load_transfer_targets(batch_file)?;
+                    let args = TransferArgs {
+                        privkey_path: m.value_of("privkey-path").map(|s| s.to_string()),
+                        from_account: m.value_of("from-account").map(|s| s.to_string()),
+                        from_locked_address: m
+                            .value_of("from-locked-address")
+                            .map(|s| s.to_string()),
+                        password: None,
+                        capacity: String::new(),
+                        fee_rate: get_arg_value(m, "fee-rate")?,
+                        force_small_change_as_fee: m.value_of("max-tx-fee").map(|s| s.to_string()),
+                        derive_receiving_address_length: Some(get_arg_value(
+                            m,
+                            "derive-receiving-address-length",
+                        )?),
+                        derive_change_address: m
+                            .value_of("derive-change-address")
+                            .map(|s| s.to_string()),
+                        to_address: String::new(),
+                        to_data: None,
+                        is_type_id: false,
+                        skip_check_to_address: m.is_present("skip-check-to-address"),
+                        // The targets are split by the size limit in dry run mode
+                        build_only: m.is_present("dry-run"),
+                        coin_selection: Some(get_arg_value(m, "coin-selection")?),
+                        use_cells: get_arg_values(m, "use-cell"),
+                        exclude_cells: get_arg_values(m, "exclude-cell"),
+                        lock_until: None,
+                        signer_cmd: m.value_of("signer-cmd").map(|s| s.to_string()),
+                    };
+                    if debug {
+                        eprintln!("[coin selection]: {}", get_arg_value(m, "coin-selection")?);
+                    }
+                    let batches = self.transfer_batch(args, targets.clone(), false)?;
+                    if m.is_present("dry-run") {
+                        let outputs_validator = outputs_validator_of(m);
+                        let network = get_network_type(self.rpc_client)?;
+                        let tx_dep_provider =
+                            DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+                        let mut reports = Vec::with_capacity(batches.len());
+                        for batch in batches {
+                            let change_index = Some(batch.targets.len())
+                                .filter(|index| *index < batch.tx.outputs().len());
+                            reports.push(dry_run_report(
+                                self.rpc_client,
+                                network,
+                                &batch.tx,
+                                change_index,
+                                outputs_validator.clone(),
+                                provider_cell_getter(&tx_dep_provider),
+                            )?);
+                        }
+                        return Ok(Output::new_output(reports));
+                    }
+                    let mut target_txs = Vec::with_capacity(targets.len());
+                    let mut transactions = Vec::with_capacity(batches.len());
+                    for batch in batches {
+                        let tx_hash: H256 = batch.tx.hash().unpack();
+                        for idx in &batch.targets {
+                            let target = &targets[*idx];
+                            target_txs.push(serde_json::json!({
+                                "index": idx,
+                                "address": target.address,
+                                "capacity": target.capacity,
+                                "tx_hash": tx_hash,
+                            }));
+                        }
+                        if debug {
+                            transactions.push(serde_json::json!({
+                                "transaction": json_types::TransactionView::from(batch.tx),
+                                "targets": batch.targets,
+                            }));
+                        } else {
+                            transactions.push(serde_json::json!({
+                                "tx_hash": tx_hash,
+                                "targets": batch.targets,
+                            }));
+                        }
+                    }
+                    let resp = serde_json::json!({
+                        "transactions": transactions,
+                        "targets": target_txs,
+                    });
+                    return Ok(Output::new_output(resp));
+                }
+                let to_data = get_to_data(m)?;
+                let mut args = TransferArgs {
+                    privkey_path: m.value_of("privkey-path").map(|s| s.to_string()),
+                    from_account: m.value_of("from-account").map(|s| s.to_string()),
+                    from_locked_address: m.value_of("from-locked-address").map(|s| s.to_string()),
                     password: None,
                     capacity: get_arg_value(m, "capacity")?,
                     fee_rate: get_arg_value(m, "fee-rate")?,
@@ -559,16 +1818,241 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     to_data: Some(to_data),
                     is_type_id: m.is_present("type-id"),
                     skip_check_to_address: m.is_present("skip-check-to-address"),
+                    build_only: m.is_present("build-only") || m.is_present("dry-run"),
+                    coin_selection: Some(get_arg_value(m, "coin-selection")?),
+                    use_cells: get_arg_values(m, "use-cell"),
+                    exclude_cells: get_arg_values(m, "exclude-cell"),
+                    lock_until: None,
+                    signer_cmd: m.value_of("signer-cmd").map(|s| s.to_string()),
+                };
+                // Parse the time only once, so the printed unlock time is the used one
+                let lock_until_info = if let Some(input) = m.value_of("lock-until") {
+                    let tip_header = self.rpc_client.get_tip_header()?;
+                    let (since, unlock_timestamp) = parse_lock_until(input, &tip_header)?;
+                    args.lock_until = Some(format!("{:#x}", since));
+                    Some((since, unlock_timestamp))
+                } else {
+                    None
                 };
+                if debug {
+                    eprintln!("[coin selection]: {}", get_arg_value(m, "coin-selection")?);
+                }
                 let tx = self.transfer(args, false)?;
+                if m.is_present("dry-run") {
+                    let network = get_network_type(self.rpc_client)?;
+                    let tx_dep_provider =
+                        DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+                    let change_index = Some(1).filter(|index| *index < tx.outputs().len());
+                    let report = dry_run_report(
+                        self.rpc_client,
+                        network,
+                        &tx,
+                        change_index,
+                        outputs_validator_of(m),
+                        provider_cell_getter(&tx_dep_provider),
+                    )?;
+                    return Ok(Output::new_output(report));
+                }
+                if m.is_present("build-only") {
+                    let output_file: PathBuf =
+                        FilePathParser::new(false).from_matches(m, "output-file")?;
+                    let repr_tx = dump_mock_tx(self.rpc_client, tx.data().into(), true)?;
+                    let content =
+                        serde_json::to_string_pretty(&repr_tx).map_err(|err| err.to_string())?;
+                    fs::write(output_file, content).map_err(|err| err.to_string())?;
+                    let tx_hash: H256 = tx.hash().unpack();
+                    return Ok(Output::new_output(tx_hash));
+                }
                 if debug {
                     let rpc_tx_view = json_types::TransactionView::from(tx);
                     Ok(Output::new_output(rpc_tx_view))
+                } else if let Some((since, unlock_timestamp)) = lock_until_info {
+                    let tx_hash: H256 = tx.hash().unpack();
+                    let network_type = get_network_type(self.rpc_client)?;
+                    let to_address: Address = AddressParser::default()
+                        .set_network(network_type)
+                        .from_matches(m, "to-address")?;
+                    let lock_address = Address::new(
+                        network_type,
+                        gen_since_multisig_addr(to_address.payload(), since),
+                        true,
+                    );
+                    let unlock_time = Utc
+                        .timestamp_millis_opt(unlock_timestamp as i64)
+                        .single()
+                        .map(|time| time.to_rfc3339())
+                        .unwrap_or_default();
+                    let resp = serde_json::json!({
+                        "tx_hash": tx_hash,
+                        "lock_address": lock_address.to_string(),
+                        "since": format!("{:#x}", since),
+                        "estimated_unlock_time": unlock_time,
+                    });
+                    Ok(Output::new_output(resp))
                 } else {
                     let tx_hash: H256 = tx.hash().unpack();
                     Ok(Output::new_output(tx_hash))
                 }
             }
+            ("bump-fee", Some(m)) => {
+                let args = BumpFeeArgs {
+                    privkey_path: m.value_of("privkey-path").map(|s| s.to_string()),
+                    from_account: m.value_of("from-account").map(|s| s.to_string()),
+                    password: None,
+                    derive_receiving_address_length: Some(get_arg_value(
+                        m,
+                        "derive-receiving-address-length",
+                    )?),
+                    derive_change_address: m
+                        .value_of("derive-change-address")
+                        .map(|s| s.to_string()),
+                    tx_hash: get_arg_value(m, "tx-hash")?,
+                    fee_rate: get_arg_value(m, "fee-rate")?,
+                    signer_cmd: m.value_of("signer-cmd").map(|s| s.to_string()),
+                };
+                let old_tx_hash = args.tx_hash.clone();
+                let (tx, old_tx_fee, new_tx_fee) = self.bump_fee(args)?;
+                if debug {
+                    let rpc_tx_view = json_types::TransactionView::from(tx);
+                    Ok(Output::new_output(rpc_tx_view))
+                } else {
+                    let tx_hash: H256 = tx.hash().unpack();
+                    let resp = serde_json::json!({
+                        "old_tx_hash": old_tx_hash,
+                        "tx_hash": tx_hash,
+                        "old_tx_fee": format!("{:#}", HumanCapacity::from(old_tx_fee)),
+                        "new_tx_fee": format!("{:#}", HumanCapacity::from(new_tx_fee)),
+                    });
+                    Ok(Output::new_output(resp))
+                }
+            }
+            ("consolidate", Some(m)) => {
+                let dry_run = m.is_present("dry-run");
+                let args = ConsolidateArgs {
+                    privkey_path: m.value_of("privkey-path").map(|s| s.to_string()),
+                    from_account: m.value_of("from-account").map(|s| s.to_string()),
+                    password: None,
+                    derive_receiving_address_length: Some(get_arg_value(
+                        m,
+                        "derive-receiving-address-length",
+                    )?),
+                    derive_change_address_length: Some(get_arg_value(
+                        m,
+                        "derive-change-address-length",
+                    )?),
+                    to_address: m.value_of("to-address").map(|s| s.to_string()),
+                    max_inputs: get_arg_value(m, "max-inputs")?,
+                    min_cell_capacity: get_arg_value(m, "min-cell-capacity")?,
+                    fee_rate: get_arg_value(m, "fee-rate")?,
+                    dry_run,
+                    signer_cmd: m.value_of("signer-cmd").map(|s| s.to_string()),
+                };
+                let ConsolidateResult {
+                    transactions: txs,
+                    error,
+                } = self.consolidate(args)?;
+                let total_inputs: usize = txs.iter().map(|item| item.tx.inputs().len()).sum();
+                let total_fee: u64 = txs.iter().map(|item| item.fee).sum();
+                let transactions = txs
+                    .into_iter()
+                    .map(|item| {
+                        let tx_hash: H256 = item.tx.hash().unpack();
+                        let mut value = serde_json::json!({
+                            "tx_hash": tx_hash,
+                            "inputs": item.tx.inputs().len(),
+                            "input_capacity": format!("{:#}", HumanCapacity::from(item.input_capacity)),
+                            "fee": format!("{:#}", HumanCapacity::from(item.fee)),
+                        });
+                        if debug {
+                            value["transaction"] =
+                                serde_json::json!(json_types::TransactionView::from(item.tx));
+                        }
+                        value
+                    })
+                    .collect::<Vec<_>>();
+                let mut resp = serde_json::json!({
+                    "dry_run": dry_run,
+                    "transactions": transactions,
+                    "total_inputs": total_inputs,
+                    "total_fee": format!("{:#}", HumanCapacity::from(total_fee)),
+                });
+                if let Some(err) = error {
+                    resp["error"] = serde_json::json!(err);
+                }
+                Ok(Output::new_output(resp))
+            }
+            ("history", Some(m)) => {
+                let network_type = get_network_type(self.rpc_client)?;
+                let order = parse_order(&get_arg_value(m, "order")?)?;
+                let page: usize = FromStrParser::<usize>::default().from_matches(m, "page")?;
+                let limit: usize = FromStrParser::<usize>::default().from_matches(m, "limit")?;
+                let export_path: Option<PathBuf> =
+                    FilePathParser::new(false).from_matches_opt(m, "export")?;
+
+                let lock_scripts = if let Some(input) = m.value_of("from-account") {
+                    let lock_arg: H160 =
+                        FixedHashParser::<H160>::default()
+                            .parse(input)
+                            .or_else(|err| {
+                                let result: Result<Address, String> = AddressParser::new_sighash()
+                                    .set_network(network_type)
+                                    .parse(input);
+                                result
+                                    .map(|address| {
+                                        H160::from_slice(&address.payload().args()).unwrap()
+                                    })
+                                    .map_err(|_| err)
+                            })?;
+                    let receiving_address_length: u32 = FromStrParser::<u32>::default()
+                        .from_matches(m, "derive-receiving-address-length")?;
+                    let change_address_length: u32 = FromStrParser::<u32>::default()
+                        .from_matches(m, "derive-change-address-length")?;
+                    let key_set = self
+                        .plugin_mgr
+                        .keystore_handler()
+                        .derived_key_set_by_index(
+                            lock_arg.clone(),
+                            0,
+                            receiving_address_length,
+                            0,
+                            change_address_length,
+                            None,
+                        )?;
+                    std::iter::once(lock_arg.clone())
+                        .chain(
+                            key_set
+                                .external
+                                .into_iter()
+                                .chain(key_set.change)
+                                .map(|(_, hash160)| hash160)
+                                .filter(|hash160| hash160 != &lock_arg),
+                        )
+                        .map(|hash160| Script::from(&AddressPayload::from_pubkey_hash(hash160)))
+                        .collect::<Vec<_>>()
+                } else {
+                    let address: Address = AddressParser::default()
+                        .set_network(network_type)
+                        .from_matches(m, "address")?;
+                    vec![Script::from(address.payload())]
+                };
+
+                let (more, items) = self.history(lock_scripts, order, page, limit)?;
+                if let Some(path) = export_path {
+                    let content = if path.extension().map(|ext| ext == "csv").unwrap_or(false) {
+                        history_to_csv(&items)
+                    } else {
+                        let values = items.iter().map(HistoryItem::to_json).collect::<Vec<_>>();
+                        serde_json::to_string_pretty(&values).map_err(|err| err.to_string())?
+                    };
+                    fs::write(path, content).map_err(|err| err.to_string())?;
+                }
+                let resp = serde_json::json!({
+                    "page": page,
+                    "more": more,
+                    "transactions": items.iter().map(HistoryItem::to_json).collect::<Vec<_>>(),
+                });
+                Ok(Output::new_output(resp))
+            }
             ("get-capacity", Some(m)) => {
                 let network_type = get_network_type(self.rpc_client)?;
 
@@ -576,7 +2060,26 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     .from_matches(m, "derive-receiving-address-length")?;
                 let change_address_length: u32 = FromStrParser::<u32>::default()
                     .from_matches(m, "derive-change-address-length")?;
-                let address_payload = if let Some(address_str) = m.value_of("address") {
+                let from_account: Option<H160> = m
+                    .value_of("from-account")
+                    .map(|input| {
+                        FixedHashParser::<H160>::default()
+                            .parse(input)
+                            .or_else(|err| {
+                                let result: Result<Address, String> = AddressParser::new_sighash()
+                                    .set_network(network_type)
+                                    .parse(input);
+                                result
+                                    .map(|address| {
+                                        H160::from_slice(&address.payload().args()).unwrap()
+                                    })
+                                    .map_err(|_| err)
+                            })
+                    })
+                    .transpose()?;
+                let address_payload = if let Some(lock_arg) = from_account.clone() {
+                    AddressPayload::from_pubkey_hash(lock_arg)
+                } else if let Some(address_str) = m.value_of("address") {
                     AddressParser::default()
                         .set_network(network_type)
                         .parse(address_str)?
@@ -586,14 +2089,18 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     get_address(Some(network_type), m)?
                 };
                 let mut lock_scripts = vec![Script::from(&address_payload)];
-                if m.is_present("derived") {
+                let mut sighash_payloads = Vec::new();
+                if is_sighash_payload(&address_payload) {
+                    sighash_payloads.push(address_payload.clone());
+                }
+                if m.is_present("derived") || from_account.is_some() {
                     let lock_arg = H160::from_slice(address_payload.args().as_ref()).unwrap();
 
                     let key_set = self
                         .plugin_mgr
                         .keystore_handler()
                         .derived_key_set_by_index(
-                            lock_arg,
+                            lock_arg.clone(),
                             0,
                             receiving_address_length,
                             0,
@@ -601,12 +2108,18 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                             None,
                         )?;
                     for (_, hash160) in key_set.external.iter().chain(key_set.change.iter()) {
+                        if hash160 == &lock_arg {
+                            continue;
+                        }
                         let payload = AddressPayload::from_pubkey_hash(hash160.clone());
                         lock_scripts.push(Script::from(&payload));
+                        sighash_payloads.push(payload);
                     }
                 }
 
                 let (total, immature, dao) = self.get_capacity(lock_scripts)?;
+                let (timelock_locked, timelock_unlocked) =
+                    self.get_timelock_capacity(sighash_payloads)?;
 
                 let mut resp =
                     serde_json::json!({ "total": format!("{:#}", HumanCapacity::from(total)) });
@@ -619,6 +2132,12 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     resp["dao"] = serde_json::json!(format!("{:#}", HumanCapacity::from(dao)));
                     resp["free"] = serde_json::json!(format!("{:#}", HumanCapacity::from(free)));
                 }
+                if timelock_locked > 0 || timelock_unlocked > 0 {
+                    resp["timelock"] = serde_json::json!({
+                        "locked": format!("{:#}", HumanCapacity::from(timelock_locked)),
+                        "unlocked": format!("{:#}", HumanCapacity::from(timelock_unlocked)),
+                    });
+                }
                 Ok(Output::new_output(resp))
             }
             ("get-live-cells", Some(m)) => {
@@ -676,6 +2195,331 @@ pub struct TransferArgs {
     pub to_data: Option<Bytes>,
     pub is_type_id: bool,
     pub skip_check_to_address: bool,
+    /// Only build the balanced transaction, do not sign and send it
+    pub build_only: bool,
+    /// The coin selection strategy name, default strategy is used when absent
+    pub coin_selection: Option<String>,
+    /// The cells (out points) must be used as inputs
+    pub use_cells: Vec<String>,
+    /// The cells (out points) must not be used as inputs
+    pub exclude_cells: Vec<String>,
+    /// Lock the target cell until the time (see `parse_lock_until`)
+    pub lock_until: Option<String>,
+    /// The external signer executable, used instead of the keystore
+    pub signer_cmd: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BumpFeeArgs {
+    pub privkey_path: Option<String>,
+    pub from_account: Option<String>,
+    pub password: Option<String>,
+    pub derive_receiving_address_length: Option<String>,
+    pub derive_change_address: Option<String>,
+    /// The hash of the pending transaction to replace
+    pub tx_hash: String,
+    /// The new fee rate (unit: shannons/KB)
+    pub fee_rate: String,
+    /// The external signer executable, used instead of the keystore
+    pub signer_cmd: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConsolidateArgs {
+    pub privkey_path: Option<String>,
+    pub from_account: Option<String>,
+    pub password: Option<String>,
+    pub derive_receiving_address_length: Option<String>,
+    pub derive_change_address_length: Option<String>,
+    /// The address to receive the merged cells, default is the sender
+    pub to_address: Option<String>,
+    /// Max input cells in one transaction
+    pub max_inputs: String,
+    /// The cells with capacity less than this value will be merged
+    pub min_cell_capacity: String,
+    pub fee_rate: String,
+    /// Only build the transactions, do not sign and send them
+    pub dry_run: bool,
+    /// The external signer executable, used instead of the keystore
+    pub signer_cmd: Option<String>,
+}
+
+/// A consolidate transaction and its input capacity and fee
+#[derive(Clone, Debug)]
+pub struct ConsolidateTransaction {
+    pub tx: TransactionView,
+    pub input_capacity: u64,
+    pub fee: u64,
+}
+
+/// The consolidate transactions, when a later transaction failed the already
+/// sent ones are still returned along with the error
+#[derive(Clone, Debug)]
+pub struct ConsolidateResult {
+    pub transactions: Vec<ConsolidateTransaction>,
+    pub error: Option<String>,
+}
+
+/// A transaction related to the wallet
+#[derive(Clone, Debug)]
+pub struct HistoryItem {
+    pub tx_hash: H256,
+    pub block_number: u64,
+    /// Block timestamp in milliseconds
+    pub timestamp: u64,
+    /// Net capacity change of the wallet (unit: shannon)
+    pub capacity_change: i128,
+    /// Receivers of outgoing transaction or senders of incoming transaction
+    pub counterparties: Vec<String>,
+}
+
+/// A transaction found by the indexer and its related cells
+struct HistoryTx {
+    tx_hash: H256,
+    block_number: u64,
+    tx_index: u32,
+    inputs: BTreeSet<u32>,
+    outputs: BTreeSet<u32>,
+}
+
+/// The transactions of a lock script, fetched page by page with the indexer cursor
+struct HistoryTxStream {
+    search_key: SearchKey,
+    buffer: VecDeque<HistoryTx>,
+    after: Option<json_types::JsonBytes>,
+    finished: bool,
+}
+
+impl HistoryTxStream {
+    fn new(lock_script: Script) -> HistoryTxStream {
+        let search_key = SearchKey {
+            script: lock_script.into(),
+            script_type: ScriptType::Lock,
+            script_search_mode: None,
+            filter: None,
+            with_data: None,
+            group_by_transaction: Some(true),
+        };
+        HistoryTxStream {
+            search_key,
+            buffer: VecDeque::new(),
+            after: None,
+            finished: false,
+        }
+    }
+
+    /// The (block number, transaction index) of the next transaction
+    fn peek_key(
+        &mut self,
+        rpc_client: &mut HttpRpcClient,
+        order: &Order,
+        limit: u32,
+    ) -> Result<Option<(u64, u32)>, String> {
+        if self.buffer.is_empty() && !self.finished {
+            let txs_page = rpc_client.get_transactions(
+                self.search_key.clone(),
+                order.clone(),
+                limit.into(),
+                self.after.take(),
+            )?;
+            if txs_page.objects.len() < limit as usize {
+                self.finished = true;
+            }
+            self.after = Some(txs_page.last_cursor);
+            for tx in txs_page.objects {
+                let (tx_hash, block_number, tx_index, cells) = match tx {
+                    Tx::Grouped(tx) => (tx.tx_hash, tx.block_number, tx.tx_index, tx.cells),
+                    Tx::Ungrouped(tx) => (
+                        tx.tx_hash,
+                        tx.block_number,
+                        tx.tx_index,
+                        vec![(tx.io_type, tx.io_index)],
+                    ),
+                };
+                let mut history_tx = HistoryTx {
+                    tx_hash,
+                    block_number,
+                    tx_index,
+                    inputs: BTreeSet::new(),
+                    outputs: BTreeSet::new(),
+                };
+                for (cell_type, io_index) in cells {
+                    match cell_type {
+                        CellType::Input => history_tx.inputs.insert(io_index),
+                        CellType::Output => history_tx.outputs.insert(io_index),
+                    };
+                }
+                self.buffer.push_back(history_tx);
+            }
+        }
+        Ok(self.buffer.front().map(|tx| (tx.block_number, tx.tx_index)))
+    }
+
+    fn pop(&mut self) -> Option<HistoryTx> {
+        self.buffer.pop_front()
+    }
+}
+
+impl HistoryItem {
+    fn capacity_change_string(&self) -> String {
+        let sign = if self.capacity_change < 0 { "-" } else { "+" };
+        let capacity = HumanCapacity::from(self.capacity_change.unsigned_abs() as u64);
+        format!("{}{}", sign, capacity)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "tx_hash": self.tx_hash,
+            "block_number": self.block_number,
+            "timestamp": self.timestamp,
+            "capacity_change": self.capacity_change_string(),
+            "counterparties": self.counterparties,
+        })
+    }
+}
+
+fn history_to_csv(items: &[HistoryItem]) -> String {
+    let mut content =
+        String::from("tx_hash,block_number,timestamp,capacity_change,counterparties\n");
+    for item in items {
+        content.push_str(&format!(
+            "{:#x},{},{},{},{}\n",
+            item.tx_hash,
+            item.block_number,
+            item.timestamp,
+            item.capacity_change_string(),
+            item.counterparties.join(";"),
+        ));
+    }
+    content
+}
+
+/// One target of a batch transfer
+#[derive(Clone, Debug)]
+pub struct TransferTarget {
+    pub address: String,
+    pub capacity: String,
+    pub data: Option<Bytes>,
+}
+
+/// A sent transaction of a batch transfer and the indexes of targets it contains
+#[derive(Clone, Debug)]
+pub struct TransferBatch {
+    pub tx: TransactionView,
+    pub targets: Vec<usize>,
+}
+
+fn sent_batches_report(batches: &[TransferBatch]) -> String {
+    let sent = batches
+        .iter()
+        .map(|batch| {
+            let tx_hash: H256 = batch.tx.hash().unpack();
+            serde_json::json!({
+                "tx_hash": tx_hash,
+                "targets": batch.targets,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&sent).expect("serialize sent batches")
+}
+
+/// Load batch transfer targets from a json file or a csv file
+pub fn load_transfer_targets(path: &str) -> Result<Vec<TransferTarget>, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let targets = if Path::new(path)
+        .extension()
+        .map(|ext| ext == "json")
+        .unwrap_or(false)
+    {
+        let rows: Vec<ReprTransferTarget> =
+            serde_json::from_str(&content).map_err(|err| err.to_string())?;
+        rows.into_iter()
+            .map(|row| TransferTarget {
+                address: row.address,
+                capacity: row.capacity,
+                data: row.data.map(|data| data.into_bytes()),
+            })
+            .collect::<Vec<_>>()
+    } else {
+        parse_csv_targets(&content)?
+    };
+    if targets.is_empty() {
+        return Err(format!("No transfer target found in {}", path));
+    }
+    Ok(targets)
+}
+
+fn parse_csv_targets(content: &str) -> Result<Vec<TransferTarget>, String> {
+    let mut targets = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let columns = line.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+        // Skip the header line
+        if targets.is_empty() && columns[0] == "address" {
+            continue;
+        }
+        if columns.len() < 2 || columns.len() > 3 {
+            return Err(format!(
+                "Invalid csv line {}: expected columns address,capacity[,data]",
+                line_idx + 1
+            ));
+        }
+        let data = match columns.get(2) {
+            Some(data) if !data.is_empty() => {
+                Some(Bytes::from(HexParser.parse(data).map_err(|err| {
+                    format!("Invalid csv line {}: {}", line_idx + 1, err)
+                })?))
+            }
+            _ => None,
+        };
+        targets.push(TransferTarget {
+            address: columns[0].to_string(),
+            capacity: columns[1].to_string(),
+            data,
+        });
+    }
+    Ok(targets)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReprTransferTarget {
+    address: String,
+    capacity: String,
+    #[serde(default)]
+    data: Option<json_types::JsonBytes>,
+}
+
+// The outputs validator used when sending the transfer transaction
+fn outputs_validator_of(m: &ArgMatches) -> Option<json_types::OutputsValidator> {
+    if m.is_present("type-id") || m.is_present("skip-check-to-address") {
+        Some(json_types::OutputsValidator::Passthrough)
+    } else {
+        None
+    }
+}
+
+fn check_to_address(to_address: &Address, skip_check_to_address: bool) -> Result<(), String> {
+    let to_address_hash_type = to_address.payload().hash_type();
+    let to_address_code_hash: H256 = to_address
+        .payload()
+        .code_hash(Some(to_address.network()))
+        .unpack();
+    let to_address_args_len = to_address.payload().args().len();
+    if !(skip_check_to_address
+        || (to_address_hash_type == ScriptHashType::Type
+            && to_address_code_hash == SIGHASH_TYPE_HASH
+            && to_address_args_len == 20)
+        || (to_address_hash_type == ScriptHashType::Type
+            && to_address_code_hash == MULTISIG_TYPE_HASH
+            && (to_address_args_len == 20 || to_address_args_len == 28)))
+    {
+        return Err(format!("Invalid to-address: {}\n[Hint]: Add `--skip-check-to-address` flag to transfer to any address", to_address));
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -683,3 +2527,75 @@ pub struct LiveCell {
     pub info: LiveCellInfo,
     pub mature: bool,
 }
+
+fn is_sighash_payload(payload: &AddressPayload) -> bool {
+    let script = Script::from(payload);
+    script.code_hash() == SIGHASH_TYPE_HASH.pack()
+        && script.hash_type() == ScriptHashType::Type.into()
+        && script.args().raw_data().len() == 20
+}
+
+/// Parse `--lock-until` value into an absolute since value and the estimated
+/// unlock timestamp (in milliseconds).
+fn parse_lock_until(input: &str, tip_header: &HeaderView) -> Result<(u64, u64), String> {
+    let tip_number = tip_header.inner.number;
+    let tip_epoch = EpochNumberWithFraction::from_full_value(tip_header.inner.epoch.0);
+    let tip_timestamp = tip_header.inner.timestamp.0;
+    let epoch_blocks = |epoch: &EpochNumberWithFraction| {
+        epoch.number() * EPOCH_LENGTH + epoch.index() * EPOCH_LENGTH / epoch.length()
+    };
+    let epoch_since = |epoch: EpochNumberWithFraction| {
+        let since = Since::new(
+            SinceType::EpochNumberWithFraction,
+            epoch.full_value(),
+            false,
+        );
+        let elapsed_blocks = epoch_blocks(&epoch).saturating_sub(epoch_blocks(&tip_epoch));
+        (since.value(), tip_timestamp + elapsed_blocks * BLOCK_PERIOD)
+    };
+
+    if let Some(value) = input.strip_prefix("epoch:") {
+        let parts = value
+            .split(',')
+            .map(|part| FromStrParser::<u64>::default().parse(part.trim()))
+            .collect::<Result<Vec<_>, String>>()?;
+        let epoch = match parts[..] {
+            [number] => EpochNumberWithFraction::new(number, 0, 1),
+            [number, index, length] if length > 0 && index < length => {
+                EpochNumberWithFraction::new(number, index, length)
+            }
+            _ => return Err(format!("Invalid epoch: {}", value)),
+        };
+        Ok(epoch_since(epoch))
+    } else if let Some(value) = input.strip_prefix("block:") {
+        let number = FromStrParser::<u64>::default().parse(value.trim())?;
+        let since = Since::new(SinceType::BlockNumber, number, false);
+        let elapsed_blocks = number.saturating_sub(tip_number);
+        Ok((since.value(), tip_timestamp + elapsed_blocks * BLOCK_PERIOD))
+    } else if let Some(value) = input.strip_prefix("0x") {
+        let since = Since::from_raw_value(
+            u64::from_str_radix(value, 16).map_err(|err| format!("Invalid since: {}", err))?,
+        );
+        if !since.is_absolute() {
+            return Err("Only absolute since is supported".to_string());
+        }
+        match since.extract_metric() {
+            Some((SinceType::EpochNumberWithFraction, value)) => {
+                let epoch = EpochNumberWithFraction::from_full_value(value);
+                Ok((since.value(), epoch_since(epoch).1))
+            }
+            Some((SinceType::BlockNumber, value)) => {
+                let elapsed_blocks = value.saturating_sub(tip_number);
+                Ok((since.value(), tip_timestamp + elapsed_blocks * BLOCK_PERIOD))
+            }
+            Some((SinceType::Timestamp, value)) => Ok((since.value(), value * 1000)),
+            None => Err(format!("Invalid since: {}", input)),
+        }
+    } else {
+        let timestamp = DateTime::parse_from_rfc3339(input)
+            .map(|time| time.timestamp_millis() as u64)
+            .map_err(|err| format!("Invalid lock-until time: {}", err))?;
+        let epoch = estimate_epoch(Some(tip_epoch), timestamp.saturating_sub(tip_timestamp));
+        Ok(epoch_since(epoch))
+    }
+}
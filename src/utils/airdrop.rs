@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ckb_hash::blake2b_256;
+use ckb_types::H256;
+use serde::{Deserialize, Serialize};
+
+/// One row of the airdrop recipients file (format: csv, columns:
+/// `address,amount[,kind]`), the kind is one of:
+///
+///   * `sighash`: create a udt cell with the sighash address lock
+///   * `acp`: add the amount to the existing udt cell of the anyone-can-pay address
+///   * `cheque`: create a cheque cell, the address is the cheque receiver (sighash)
+///
+/// When the kind is empty it is `acp` for an anyone-can-pay address, otherwise `sighash`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AirdropRow {
+    pub address: String,
+    pub amount: String,
+    pub kind: Option<String>,
+}
+
+pub fn parse_airdrop_rows(content: &str) -> Result<Vec<AirdropRow>, String> {
+    let mut rows = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let columns = line.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+        // Skip the header line
+        if rows.is_empty() && columns[0] == "address" {
+            continue;
+        }
+        if columns.len() < 2 || columns.len() > 3 {
+            return Err(format!(
+                "Invalid csv line {}: expected columns address,amount[,kind]",
+                line_idx + 1
+            ));
+        }
+        let kind = columns
+            .get(2)
+            .filter(|kind| !kind.is_empty())
+            .map(|kind| kind.to_string());
+        if let Some(kind) = kind.as_ref() {
+            if !["sighash", "acp", "cheque"].contains(&kind.as_str()) {
+                return Err(format!(
+                    "Invalid csv line {}: invalid kind {}, expected: sighash, acp or cheque",
+                    line_idx + 1,
+                    kind
+                ));
+            }
+        }
+        rows.push(AirdropRow {
+            address: columns[0].to_string(),
+            amount: columns[1].to_string(),
+            kind,
+        });
+    }
+    Ok(rows)
+}
+
+/// The progress of an airdrop, the transaction is recorded before it is sent,
+/// so a resumed airdrop can check the transaction status and never pays a
+/// recipient twice.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropJournal {
+    /// The blake2b hash of the recipients file content
+    pub recipients_hash: H256,
+    /// The udt type script hash
+    pub type_script_hash: H256,
+    pub transactions: Vec<AirdropTransaction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropTransaction {
+    pub tx_hash: H256,
+    /// The indexes of the recipients (in the recipients file) paid by the transaction
+    pub recipients: Vec<usize>,
+}
+
+impl AirdropJournal {
+    /// The default journal path: `{recipients file}.journal.json`
+    pub fn default_path(recipients_path: &Path) -> PathBuf {
+        let mut path = recipients_path.as_os_str().to_owned();
+        path.push(".journal.json");
+        PathBuf::from(path)
+    }
+
+    /// Load the journal or create an empty one if the file not exists, the
+    /// journal must belong to the same recipients file and udt.
+    pub fn load_or_new(
+        path: &Path,
+        recipients_content: &[u8],
+        type_script_hash: H256,
+    ) -> Result<AirdropJournal, String> {
+        let recipients_hash = H256::from(blake2b_256(recipients_content));
+        if !path.exists() {
+            return Ok(AirdropJournal {
+                recipients_hash,
+                type_script_hash,
+                transactions: Vec::new(),
+            });
+        }
+        let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let journal: AirdropJournal = serde_json::from_str(&content)
+            .map_err(|err| format!("Invalid airdrop journal file {:?}: {}", path, err))?;
+        if journal.recipients_hash != recipients_hash {
+            return Err(format!(
+                "The recipients file is changed since the airdrop journal {:?} is created",
+                path
+            ));
+        }
+        if journal.type_script_hash != type_script_hash {
+            return Err(format!(
+                "The airdrop journal {:?} is for another udt (type script hash: {:#x})",
+                path, journal.type_script_hash
+            ));
+        }
+        Ok(journal)
+    }
+
+    /// Save the journal by writing a temporary file then renaming it, so the
+    /// journal is never left half written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, content).map_err(|err| err.to_string())?;
+        fs::rename(&tmp_path, path).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_airdrop_rows() {
+        let content = "address,amount,kind\n\
+            # comment\n\
+            ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy, 100\n\
+            ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy,1.5,cheque\n\
+            \n\
+            ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy,2,\n";
+        let rows = parse_airdrop_rows(content).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].amount, "100");
+        assert_eq!(rows[0].kind, None);
+        assert_eq!(rows[1].kind, Some("cheque".to_string()));
+        assert_eq!(rows[2].kind, None);
+
+        assert!(parse_airdrop_rows("ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy").is_err());
+        assert!(
+            parse_airdrop_rows("ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy,1,multisig")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_journal() {
+        let recipients_path =
+            std::env::temp_dir().join(format!("ckb-cli-airdrop-test-{}.csv", std::process::id()));
+        let path = AirdropJournal::default_path(&recipients_path);
+        assert!(path.to_string_lossy().ends_with(".csv.journal.json"));
+
+        let type_script_hash = H256::from([1u8; 32]);
+        let mut journal =
+            AirdropJournal::load_or_new(&path, b"content", type_script_hash.clone()).unwrap();
+        assert!(journal.transactions.is_empty());
+        journal.transactions.push(AirdropTransaction {
+            tx_hash: H256::from([2u8; 32]),
+            recipients: vec![0, 1],
+        });
+        journal.save(&path).unwrap();
+
+        let loaded =
+            AirdropJournal::load_or_new(&path, b"content", type_script_hash.clone()).unwrap();
+        assert_eq!(loaded, journal);
+        assert!(AirdropJournal::load_or_new(&path, b"changed", type_script_hash).is_err());
+        assert!(AirdropJournal::load_or_new(&path, b"content", H256::from([3u8; 32])).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
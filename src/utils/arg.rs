@@ -88,6 +88,20 @@ pub fn from_account<'a>() -> Arg<'a> {
         .about("The account's lock-arg or sighash address (transfer from this account)")
 }
 
+pub fn signer_cmd<'a>() -> Arg<'a> {
+    Arg::with_name("signer-cmd")
+        .long("signer-cmd")
+        .takes_value(true)
+        .validator(|input| FilePathParser::new(true).validate(input))
+        .about("The external signer executable (sign by the JSON-over-stdio protocol, see: docs/External-Signer.md)")
+}
+
+pub fn dry_run<'a>() -> Arg<'a> {
+    Arg::with_name("dry-run")
+        .long("dry-run")
+        .about("Only build the transaction and report its size, fee, cycles and tx-pool check result, do not sign and send it")
+}
+
 pub fn from_locked_address<'a>() -> Arg<'a> {
     Arg::with_name("from-locked-address")
         .long("from-locked-address")
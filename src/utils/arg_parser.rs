@@ -11,13 +11,20 @@ use clap::ArgMatches;
 use faster_hex::hex_decode;
 use url::Url;
 
+use ckb_jsonrpc_types as json_types;
 use ckb_sdk::{
     constants::{MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
     util::zeroize_privkey,
-    Address, AddressPayload, HumanCapacity, NetworkType, OldAddress, ScriptId,
+    Address, AddressPayload, HumanCapacity, NetworkType, OldAddress, ScriptId, Since, SinceType,
 };
 use ckb_signer::MasterPrivKey;
-use ckb_types::{core::ScriptHashType, packed::OutPoint, prelude::*, H160, H256};
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, ScriptHashType},
+    packed::{OutPoint, Script},
+    prelude::*,
+    H160, H256,
+};
 
 use crate::utils::cell_dep::CellDeps;
 
@@ -478,29 +485,105 @@ impl ArgParser<OutPoint> for OutPointParser {
     }
 }
 
+/// Max decimals of an udt amount (u128::MAX is about 3.4e38)
+pub const UDT_MAX_DECIMALS: u32 = 38;
+
+/// Parse an udt amount with decimals (example: "12.5" with decimals 8), the
+/// result is in base units.
+pub struct UdtAmountParser {
+    decimals: u32,
+}
+
+impl UdtAmountParser {
+    pub fn new(decimals: u32) -> UdtAmountParser {
+        UdtAmountParser { decimals }
+    }
+}
+
+impl ArgParser<u128> for UdtAmountParser {
+    fn parse(&self, input: &str) -> Result<u128, String> {
+        let (integer_str, fraction_str) = input.split_once('.').unwrap_or((input, ""));
+        if fraction_str.len() > self.decimals as usize {
+            return Err(format!(
+                "invalid amount: {}, too many decimal places (decimals: {})",
+                input, self.decimals
+            ));
+        }
+        let parse_digits = |digits: &str| -> Result<u128, String> {
+            if digits.is_empty() {
+                return Ok(0);
+            }
+            if !digits.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("invalid amount: {}", input));
+            }
+            FromStrParser::<u128>::default()
+                .parse(digits)
+                .map_err(|err| format!("invalid amount: {}, error: {}", input, err))
+        };
+        if integer_str.is_empty() && fraction_str.is_empty() {
+            return Err(format!("invalid amount: {}", input));
+        }
+        let integer = parse_digits(integer_str)?;
+        let fraction = parse_digits(fraction_str)?;
+        let overflow = || format!("invalid amount: {}, overflow", input);
+        let integer = 10u128
+            .checked_pow(self.decimals)
+            .and_then(|unit| integer.checked_mul(unit))
+            .ok_or_else(overflow)?;
+        let fraction = fraction * 10u128.pow(self.decimals - fraction_str.len() as u32);
+        integer.checked_add(fraction).ok_or_else(overflow)
+    }
+}
+
 pub struct UdtTargetParser {
     address_parser: AddressParser,
+    decimals: u32,
 }
 
 impl UdtTargetParser {
     pub fn new(address_parser: AddressParser) -> UdtTargetParser {
-        UdtTargetParser { address_parser }
+        UdtTargetParser {
+            address_parser,
+            decimals: 0,
+        }
     }
+
+    /// The decimals of the amount, the amount is in base units by default
+    pub fn set_decimals(&mut self, decimals: u32) -> &mut Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Only check the `{address}:{amount}` format, for validating the argument
+    /// before the decimals of the token is known.
+    pub fn validate_format(&self, input: &str) -> Result<(), String> {
+        let (addr_str, amount_str) = split_udt_target(input)?;
+        self.address_parser.parse(addr_str)?;
+        let fraction_len = amount_str
+            .split_once('.')
+            .map(|(_, fraction_str)| fraction_str.len())
+            .unwrap_or(0);
+        UdtAmountParser::new(fraction_len as u32)
+            .parse(amount_str)
+            .map(|_| ())
+    }
+}
+
+fn split_udt_target(input: &str) -> Result<(&str, &str), String> {
+    input.split_once(':').ok_or_else(|| {
+        format!(
+            "Invalid udt target: {}, format: {{address}}:{{amount}}",
+            input
+        )
+    })
 }
+
 impl ArgParser<(Address, u128)> for UdtTargetParser {
     fn parse(&self, input: &str) -> Result<(Address, u128), String> {
-        if let Some((addr_str, amount_str)) = input.split_once(':') {
-            let address: Address = self.address_parser.parse(addr_str)?;
-            let amount = FromStrParser::<u128>::default()
-                .parse(amount_str)
-                .map_err(|err| format!("invalid amount: {}, error: {}", amount_str, err))?;
-            Ok((address, amount))
-        } else {
-            Err(format!(
-                "Invalid udt target: {}, format: {{address}}:{{amount}}",
-                input
-            ))
-        }
+        let (addr_str, amount_str) = split_udt_target(input)?;
+        let address: Address = self.address_parser.parse(addr_str)?;
+        let amount = UdtAmountParser::new(self.decimals).parse(amount_str)?;
+        Ok((address, amount))
     }
 }
 
@@ -525,6 +608,100 @@ impl ArgParser<ScriptId> for ScriptIdParser {
     }
 }
 
+/// Parse a script from json (`{"code_hash": "0x..", "hash_type": "type", "args": "0x.."}`)
+/// or `{code_hash}-{hash_type}-{args}`
+pub struct ScriptParser;
+
+impl ArgParser<Script> for ScriptParser {
+    fn parse(&self, input: &str) -> Result<Script, String> {
+        if input.trim_start().starts_with('{') {
+            let script: json_types::Script = serde_json::from_str(input)
+                .map_err(|err| format!("Invalid script json: {}", err))?;
+            return Ok(script.into());
+        }
+        let parts = input.splitn(3, '-').collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err(format!(
+                "Invalid script: {}, format: {{code_hash}}-{{hash_type}}-{{args}} or json, `hash_type` can be: [type, data, data1, data2]",
+                input
+            ));
+        }
+        let code_hash: H256 = FixedHashParser::<H256>::default().parse(parts[0])?;
+        let hash_type = match parts[1] {
+            "type" => ScriptHashType::Type,
+            "data" => ScriptHashType::Data,
+            "data1" => ScriptHashType::Data1,
+            "data2" => ScriptHashType::Data2,
+            _ => return Err(format!("invalid hash_type: {}", parts[1])),
+        };
+        let args: Vec<u8> = HexParser.parse(parts[2])?;
+        Ok(Script::new_builder()
+            .code_hash(code_hash.pack())
+            .hash_type(hash_type.into())
+            .args(Bytes::from(args).pack())
+            .build())
+    }
+}
+
+/// Parse since value (see RFC0017), the formats:
+///
+///   * `0x{hex}`: the raw 64 bits since value
+///   * `[absolute:|relative:]epoch:{number}[,{index},{length}]`
+///   * `[absolute:|relative:]block:{number}`
+///   * `[absolute:|relative:]timestamp:{seconds}` (compared with the median time)
+///
+/// The since is absolute when the `absolute:`/`relative:` prefix is omitted.
+pub struct SinceParser;
+
+impl ArgParser<u64> for SinceParser {
+    fn parse(&self, input: &str) -> Result<u64, String> {
+        if let Some(value) = input.strip_prefix("0x") {
+            let since =
+                u64::from_str_radix(value, 16).map_err(|err| format!("Invalid since: {}", err))?;
+            if !Since::from_raw_value(since).flags_is_valid() {
+                return Err(format!("Invalid since flags: {}", input));
+            }
+            return Ok(since);
+        }
+        let (is_relative, input) = if let Some(rest) = input.strip_prefix("relative:") {
+            (true, rest)
+        } else {
+            (false, input.strip_prefix("absolute:").unwrap_or(input))
+        };
+        let (since_type, value) = if let Some(value) = input.strip_prefix("epoch:") {
+            let parts = value
+                .split(',')
+                .map(|part| FromStrParser::<u64>::default().parse(part.trim()))
+                .collect::<Result<Vec<_>, String>>()?;
+            let (number, index, length) = match parts[..] {
+                [number] => (number, 0, 1),
+                [number, index, length] => (number, index, length),
+                _ => return Err(format!("Invalid epoch: {}", value)),
+            };
+            if number >= 1 << 24 || length == 0 || length >= 1 << 16 || index >= length {
+                return Err(format!("Invalid epoch: {}", value));
+            }
+            let epoch = EpochNumberWithFraction::new(number, index, length);
+            (SinceType::EpochNumberWithFraction, epoch.full_value())
+        } else if let Some(value) = input.strip_prefix("block:") {
+            let number = FromStrParser::<u64>::default().parse(value.trim())?;
+            (SinceType::BlockNumber, number)
+        } else if let Some(value) = input.strip_prefix("timestamp:") {
+            let seconds = FromStrParser::<u64>::default().parse(value.trim())?;
+            (SinceType::Timestamp, seconds)
+        } else {
+            return Err(format!(
+                "Invalid since: {}, expected 0x{{hex}} or [absolute:|relative:]{{epoch|block|timestamp}}:{{value}}",
+                input
+            ));
+        };
+        if value >= 1 << 56 {
+            return Err(format!("Since value too large: {}", value));
+        }
+        Ok(Since::new(since_type, value, is_relative).value())
+    }
+}
+
 pub struct DurationParser;
 
 impl ArgParser<Duration> for DurationParser {
@@ -674,4 +851,103 @@ mod tests {
             ))
         )
     }
+
+    #[test]
+    fn test_script() {
+        let script = Script::new_builder()
+            .code_hash(h256!("0x1234").pack())
+            .hash_type(ScriptHashType::Data1.into())
+            .args(Bytes::from(vec![0x33, 0x44]).pack())
+            .build();
+        assert_eq!(
+            ScriptParser.parse(
+                "0x0000000000000000000000000000000000000000000000000000000000001234-data1-0x3344"
+            ),
+            Ok(script.clone())
+        );
+        assert_eq!(
+            ScriptParser.parse(
+                r#"{"code_hash": "0x0000000000000000000000000000000000000000000000000000000000001234", "hash_type": "data1", "args": "0x3344"}"#
+            ),
+            Ok(script)
+        );
+        assert!(ScriptParser
+            .parse("0x0000000000000000000000000000000000000000000000000000000000001234-data1")
+            .is_err());
+        assert!(ScriptParser
+            .parse("0x0000000000000000000000000000000000000000000000000000000000001234-data3-0x")
+            .is_err());
+    }
+
+    #[test]
+    fn test_udt_amount() {
+        assert_eq!(UdtAmountParser::new(0).parse("100"), Ok(100));
+        assert!(UdtAmountParser::new(0).parse("1.5").is_err());
+        assert_eq!(UdtAmountParser::new(8).parse("12.5"), Ok(12_5000_0000));
+        assert_eq!(UdtAmountParser::new(8).parse("0.00000001"), Ok(1));
+        assert_eq!(UdtAmountParser::new(2).parse(".5"), Ok(50));
+        assert!(UdtAmountParser::new(2).parse("0.001").is_err());
+        assert!(UdtAmountParser::new(2).parse("1.-1").is_err());
+        assert!(UdtAmountParser::new(2).parse("").is_err());
+        assert!(UdtAmountParser::new(2).parse(".").is_err());
+        assert_eq!(
+            UdtAmountParser::new(0).parse(&u128::MAX.to_string()),
+            Ok(u128::MAX)
+        );
+        assert!(UdtAmountParser::new(UDT_MAX_DECIMALS).parse("10").is_err());
+    }
+
+    #[test]
+    fn test_udt_target() {
+        let address = "ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy";
+        let parser = UdtTargetParser::new(AddressParser::default());
+        for amount in ["10", "12.5", "340282366920938463463374607431768211455"] {
+            assert!(parser
+                .validate_format(&format!("{}:{}", address, amount))
+                .is_ok());
+        }
+        assert!(parser.validate_format(address).is_err());
+        assert!(parser.validate_format(&format!("{}:1.x", address)).is_err());
+        assert!(parser.validate_format("ckt1xxx:10").is_err());
+
+        let mut parser = UdtTargetParser::new(AddressParser::default());
+        parser.set_decimals(8);
+        assert_eq!(
+            parser
+                .parse(&format!("{}:12.5", address))
+                .map(|(_, amount)| amount),
+            Ok(12_5000_0000)
+        );
+        assert!(parser.parse(&format!("{}:0.000000001", address)).is_err());
+    }
+
+    #[test]
+    fn test_since() {
+        assert_eq!(SinceParser.parse("0x0"), Ok(0));
+        assert_eq!(SinceParser.parse("epoch:10"), Ok(0x2000_0100_0000_000a));
+        assert_eq!(
+            SinceParser.parse("absolute:epoch:10,300,1800"),
+            Ok(Since::new(
+                SinceType::EpochNumberWithFraction,
+                EpochNumberWithFraction::new(10, 300, 1800).full_value(),
+                false
+            )
+            .value())
+        );
+        assert_eq!(
+            SinceParser.parse("relative:block:100"),
+            Ok(0x8000_0000_0000_0064)
+        );
+        assert_eq!(
+            SinceParser.parse("relative:timestamp:3600"),
+            Ok(0xc000_0000_0000_0e10)
+        );
+        assert_eq!(SinceParser.parse("block:100"), Ok(100));
+        assert!(SinceParser.parse("epoch:10,1800,1800").is_err());
+        assert!(SinceParser.parse("epoch:10,3").is_err());
+        assert!(SinceParser.parse("relative:height:10").is_err());
+        assert!(SinceParser.parse("block:72057594037927936").is_err());
+        // Reserved flag bits are not zero
+        assert!(SinceParser.parse("0x0100000000000000").is_err());
+    }
 }
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
@@ -28,18 +29,38 @@ pub enum CellDepName {
     Cheque,
     /// Simple UDT
     Sudt,
+    /// Extensible UDT
+    Xudt,
 }
+impl CellDepName {
+    pub const NAMES: [&'static str; 4] = ["acp", "cheque", "sudt", "xudt"];
+}
+
 impl fmt::Display for CellDepName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let output = match self {
             CellDepName::Acp => "acp",
             CellDepName::Cheque => "cheque",
             CellDepName::Sudt => "sudt",
+            CellDepName::Xudt => "xudt",
         };
         write!(f, "{}", output)
     }
 }
 
+impl FromStr for CellDepName {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "acp" => Ok(CellDepName::Acp),
+            "cheque" => Ok(CellDepName::Cheque),
+            "sudt" => Ok(CellDepName::Sudt),
+            "xudt" => Ok(CellDepName::Xudt),
+            _ => Err(format!("Invalid cell dep name: {}", input)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CellDepItem {
     pub script_id: ScriptId,
@@ -49,6 +70,9 @@ pub struct CellDepItem {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CellDeps {
     pub items: HashMap<CellDepName, CellDepItem>,
+    /// The cell deps of the xUDT extension scripts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<CellDepItem>,
 }
 
 impl CellDeps {
@@ -67,6 +91,13 @@ impl CellDeps {
                 name.to_string(),
             );
         }
+        for item in self.extensions.clone() {
+            resolver.insert(
+                item.script_id.into(),
+                item.cell_dep.into(),
+                "extension".to_string(),
+            );
+        }
         Ok(())
     }
 }
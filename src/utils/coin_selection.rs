@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use ckb_sdk::traits::{
+    CellCollector, CellCollectorError, CellQueryOptions, DefaultCellCollector, LiveCell,
+};
+use ckb_types::{
+    packed::{Byte32, OutPoint, Transaction},
+    prelude::*,
+};
+
+// Max search steps of branch-and-bound strategy
+const BNB_MAX_TRIES: usize = 100_000;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CoinSelectionStrategy {
+    /// The order of cells returned by the indexer
+    Default,
+    /// Use the cells with larger capacity first
+    LargestFirst,
+    /// Use the cells with smaller capacity first
+    SmallestFirst,
+    /// Search a set of cells which can pay the target without change cell
+    BranchAndBound,
+    /// Search a set of cells which makes the change cell as small as possible
+    MinimizeChange,
+}
+
+impl CoinSelectionStrategy {
+    pub const NAMES: [&'static str; 5] = [
+        "default",
+        "largest-first",
+        "smallest-first",
+        "branch-and-bound",
+        "minimize-change",
+    ];
+}
+
+impl fmt::Display for CoinSelectionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let output = match self {
+            CoinSelectionStrategy::Default => "default",
+            CoinSelectionStrategy::LargestFirst => "largest-first",
+            CoinSelectionStrategy::SmallestFirst => "smallest-first",
+            CoinSelectionStrategy::BranchAndBound => "branch-and-bound",
+            CoinSelectionStrategy::MinimizeChange => "minimize-change",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+impl FromStr for CoinSelectionStrategy {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "default" => Ok(CoinSelectionStrategy::Default),
+            "largest-first" => Ok(CoinSelectionStrategy::LargestFirst),
+            "smallest-first" => Ok(CoinSelectionStrategy::SmallestFirst),
+            "branch-and-bound" => Ok(CoinSelectionStrategy::BranchAndBound),
+            "minimize-change" => Ok(CoinSelectionStrategy::MinimizeChange),
+            _ => Err(format!("Invalid coin selection strategy: {}", input)),
+        }
+    }
+}
+
+/// The capacity range the selected cells should cover, the range is used by
+/// `branch-and-bound` and `minimize-change` strategies.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SelectionTarget {
+    /// Lower bound of the total capacity
+    pub min: u64,
+    /// Upper bound of the total capacity
+    pub max: Option<u64>,
+}
+
+/// A cell collector wraps `DefaultCellCollector` and returns the live cells
+/// in the order of the coin selection strategy.
+///
+/// The cells in `use_cells` are always returned first (all at once), the
+/// cells in `exclude_cells` are never returned.
+#[derive(Clone)]
+pub struct CoinSelectionCollector {
+    inner: DefaultCellCollector,
+    strategy: CoinSelectionStrategy,
+    target: SelectionTarget,
+    use_cells: HashSet<OutPoint>,
+    exclude_cells: HashSet<OutPoint>,
+    // Ordered candidate cells of each query (key: lock script hash)
+    candidates: HashMap<Byte32, VecDeque<LiveCell>>,
+    // Total capacity already returned to the caller
+    selected_capacity: u64,
+}
+
+impl CoinSelectionCollector {
+    pub fn new(
+        inner: DefaultCellCollector,
+        strategy: CoinSelectionStrategy,
+        use_cells: Vec<OutPoint>,
+        exclude_cells: Vec<OutPoint>,
+    ) -> CoinSelectionCollector {
+        CoinSelectionCollector {
+            inner,
+            strategy,
+            target: SelectionTarget::default(),
+            use_cells: use_cells.into_iter().collect(),
+            exclude_cells: exclude_cells.into_iter().collect(),
+            candidates: HashMap::default(),
+            selected_capacity: 0,
+        }
+    }
+
+    pub fn set_target(&mut self, target: SelectionTarget) {
+        self.target = target;
+    }
+
+    fn load_candidates(&mut self, query: &CellQueryOptions) -> Result<(), CellCollectorError> {
+        let key = query.primary_script.calc_script_hash();
+        if self.candidates.contains_key(&key) {
+            return Ok(());
+        }
+        let mut all_query = query.clone();
+        all_query.min_total_capacity = u64::max_value();
+        let (cells, _) = self.inner.collect_live_cells(&all_query, false)?;
+        let (used, mut rest): (Vec<_>, Vec<_>) = cells
+            .into_iter()
+            .filter(|cell| !self.exclude_cells.contains(&cell.out_point))
+            .partition(|cell| self.use_cells.contains(&cell.out_point));
+        let used_capacity: u64 = used.iter().map(cell_capacity).sum();
+
+        match self.strategy {
+            CoinSelectionStrategy::Default => {}
+            CoinSelectionStrategy::LargestFirst => {
+                rest.sort_by_key(|cell| std::cmp::Reverse(cell_capacity(cell)));
+            }
+            CoinSelectionStrategy::SmallestFirst => {
+                rest.sort_by_key(cell_capacity);
+            }
+            CoinSelectionStrategy::BranchAndBound | CoinSelectionStrategy::MinimizeChange => {
+                rest.sort_by_key(|cell| std::cmp::Reverse(cell_capacity(cell)));
+                let paid = self.selected_capacity + used_capacity;
+                let target = SelectionTarget {
+                    min: self.target.min.saturating_sub(paid),
+                    max: self.target.max.map(|max| max.saturating_sub(paid)),
+                };
+                let capacities = rest.iter().map(cell_capacity).collect::<Vec<_>>();
+                if target.min == 0 {
+                    if self.strategy == CoinSelectionStrategy::BranchAndBound {
+                        rest.clear();
+                    }
+                } else if let Some(indexes) = select_subset(&capacities, target) {
+                    let selected: HashSet<usize> = indexes.into_iter().collect();
+                    let (picked, others): (Vec<_>, Vec<_>) = rest
+                        .into_iter()
+                        .enumerate()
+                        .partition(|(idx, _)| selected.contains(idx));
+                    rest = picked.into_iter().map(|(_, cell)| cell).collect();
+                    // The picked cells are enough for branch-and-bound, more
+                    // inputs will produce a change cell
+                    if self.strategy == CoinSelectionStrategy::MinimizeChange {
+                        rest.extend(others.into_iter().map(|(_, cell)| cell));
+                    }
+                }
+                // Fallback to largest-first when no matched set found
+            }
+        }
+        let cells = used.into_iter().chain(rest).collect();
+        self.candidates.insert(key, cells);
+        Ok(())
+    }
+}
+
+impl CellCollector for CoinSelectionCollector {
+    fn collect_live_cells(
+        &mut self,
+        query: &CellQueryOptions,
+        apply_changes: bool,
+    ) -> Result<(Vec<LiveCell>, u64), CellCollectorError> {
+        self.load_candidates(query)?;
+        let key = query.primary_script.calc_script_hash();
+        let candidates = self.candidates.get_mut(&key).expect("candidates loaded");
+        let mut count = 0;
+        let mut total_capacity = 0;
+        for cell in candidates.iter() {
+            if total_capacity >= query.min_total_capacity
+                && count > 0
+                && !self.use_cells.contains(&cell.out_point)
+            {
+                break;
+            }
+            count += 1;
+            total_capacity += cell_capacity(cell);
+        }
+        let cells: Vec<_> = if apply_changes {
+            self.selected_capacity += total_capacity;
+            candidates.drain(..count).collect()
+        } else {
+            candidates.iter().take(count).cloned().collect()
+        };
+        Ok((cells, total_capacity))
+    }
+
+    fn lock_cell(
+        &mut self,
+        out_point: OutPoint,
+        tip_block_number: u64,
+    ) -> Result<(), CellCollectorError> {
+        for candidates in self.candidates.values_mut() {
+            candidates.retain(|cell| cell.out_point != out_point);
+        }
+        self.inner.lock_cell(out_point, tip_block_number)
+    }
+
+    fn apply_tx(
+        &mut self,
+        tx: Transaction,
+        tip_block_number: u64,
+    ) -> Result<(), CellCollectorError> {
+        // New change cells may be created, reload the candidates
+        self.candidates.clear();
+        self.selected_capacity = 0;
+        self.inner.apply_tx(tx, tip_block_number)
+    }
+
+    fn reset(&mut self) {
+        self.candidates.clear();
+        self.selected_capacity = 0;
+        self.inner.reset();
+    }
+}
+
+/// Check all the cells given by `--use-cell` are used by the transaction
+pub fn check_use_cells(
+    use_cells: &[OutPoint],
+    inputs: impl Iterator<Item = OutPoint>,
+) -> Result<(), String> {
+    let inputs: HashSet<OutPoint> = inputs.collect();
+    for out_point in use_cells {
+        if !inputs.contains(out_point) {
+            let tx_hash: ckb_types::H256 = out_point.tx_hash().unpack();
+            let index: u32 = out_point.index().unpack();
+            return Err(format!(
+                "Cell {:#x}-{} is not a live capacity cell of the sender",
+                tx_hash, index
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn cell_capacity(cell: &LiveCell) -> u64 {
+    cell.output.capacity().unpack()
+}
+
+/// Search the set with the smallest total capacity (and the fewest cells) in
+/// the target range by depth first branch-and-bound, `capacities` must be
+/// sorted in descending order.
+fn select_subset(capacities: &[u64], target: SelectionTarget) -> Option<Vec<usize>> {
+    let mut remaining = vec![0u64; capacities.len() + 1];
+    for idx in (0..capacities.len()).rev() {
+        remaining[idx] = remaining[idx + 1] + capacities[idx];
+    }
+    let max = target.max.unwrap_or(u64::max_value());
+    let mut best: Option<(u64, Vec<usize>)> = None;
+    let mut current = Vec::new();
+    let mut tries = 0;
+    // (index, current total, include the cell)
+    let mut stack = vec![(0usize, 0u64, true), (0usize, 0u64, false)];
+    while let Some((idx, total, include)) = stack.pop() {
+        tries += 1;
+        if tries > BNB_MAX_TRIES {
+            break;
+        }
+        current.truncate(idx);
+        if idx >= capacities.len() {
+            continue;
+        }
+        let total = if include {
+            current.push(Some(idx));
+            total + capacities[idx]
+        } else {
+            current.push(None);
+            total
+        };
+        if total >= target.min {
+            let indexes: Vec<usize> = current.iter().filter_map(|idx| *idx).collect();
+            // Prefer the smaller total, then the fewer cells
+            let better = best
+                .as_ref()
+                .map(|(sum, best_indexes)| {
+                    total < *sum || (total == *sum && indexes.len() < best_indexes.len())
+                })
+                .unwrap_or(true);
+            if total <= max && better {
+                best = Some((total, indexes));
+            }
+            // Adding more cells only increases the total
+            continue;
+        }
+        if total + remaining[idx + 1] < target.min {
+            continue;
+        }
+        if let Some((best_total, _)) = best.as_ref() {
+            if total >= *best_total {
+                continue;
+            }
+        }
+        stack.push((idx + 1, total, false));
+        stack.push((idx + 1, total, true));
+    }
+    best.map(|(_, indexes)| indexes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_subset() {
+        let capacities = vec![100, 60, 50, 30, 10];
+        let target = SelectionTarget {
+            min: 80,
+            max: Some(80),
+        };
+        assert_eq!(select_subset(&capacities, target), Some(vec![2, 3]));
+        let target = SelectionTarget { min: 95, max: None };
+        assert_eq!(select_subset(&capacities, target), Some(vec![0]));
+        let target = SelectionTarget {
+            min: 101,
+            max: Some(105),
+        };
+        assert_eq!(select_subset(&capacities, target), None);
+        let target = SelectionTarget {
+            min: 300,
+            max: None,
+        };
+        assert_eq!(select_subset(&capacities, target), None);
+    }
+}
@@ -13,6 +13,22 @@ use serde_json::json;
 use crate::utils::printer::{OutputFormat, Printable};
 
 pub const DEFAULT_CKB_URL: &str = "http://127.0.0.1:8114";
+/// The pattern of the `${key}` variables
+pub const ENV_PATTERN: &str = r"\$\{\s*(?P<key>[^\s}]+)\s*\}";
+
+/// Replace the `${key}` variables (matched by `regex`, see `ENV_PATTERN`) in
+/// the text, the unknown variables are replaced by empty string.
+pub fn replace_vars<F>(regex: &Regex, text: &str, mut get_value: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    regex
+        .replace_all(text, |caps: &Captures| match caps.name("key") {
+            Some(key) => get_value(key.as_str()).unwrap_or_default(),
+            None => String::new(),
+        })
+        .into_owned()
+}
 
 pub struct GlobalConfig {
     url: Option<String>,
@@ -89,20 +105,15 @@ impl GlobalConfig {
     }
 
     pub fn replace_cmd(&self, regex: &Regex, line: &str) -> String {
-        regex
-            .replace_all(line, |caps: &Captures| match caps.name("key") {
-                Some(key) => self
-                    .get(Some(key.as_str()))
-                    .map(|value| match value {
-                        serde_json::Value::String(s) => s.to_owned(),
-                        serde_json::Value::Number(n) => n.to_string(),
-                        _ => String::new(),
-                    })
-                    .next()
-                    .unwrap_or_default(),
-                None => String::new(),
-            })
-            .into_owned()
+        replace_vars(regex, line, |key| {
+            self.get(Some(key))
+                .map(|value| match value {
+                    serde_json::Value::String(s) => s.to_owned(),
+                    serde_json::Value::Number(n) => n.to_string(),
+                    _ => String::new(),
+                })
+                .next()
+        })
     }
 
     pub fn set_url(&mut self, value: String) {
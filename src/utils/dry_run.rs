@@ -0,0 +1,124 @@
+use ckb_jsonrpc_types::OutputsValidator;
+use ckb_sdk::{
+    traits::TransactionDependencyProvider, Address, AddressPayload, HumanCapacity, NetworkType,
+};
+use ckb_types::{
+    core::{Capacity, FeeRate, TransactionView},
+    packed::{CellOutput, OutPoint},
+    prelude::*,
+    H256,
+};
+
+use super::{rpc::HttpRpcClient, tx_chunks::TxLimits};
+use crate::subcommands::mock_tx::verify_script_groups;
+
+/// Get the input cell from the transaction dependency provider
+pub fn provider_cell_getter(
+    tx_dep_provider: &dyn TransactionDependencyProvider,
+) -> impl FnMut(&OutPoint) -> Result<CellOutput, String> + '_ {
+    move |out_point: &OutPoint| {
+        tx_dep_provider
+            .get_cell(out_point)
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Report the transaction built by `--dry-run` (the transaction is not signed
+/// and not sent):
+///
+///   * size, fee and fee rate
+///   * input/output capacity summary and the change output
+///   * the result of `estimate_cycles` and `test_tx_pool_accept` rpc
+///   * the cycles or the verification error of each script group
+///
+/// NOTE: the lock scripts are not signed (the witnesses are placeholders),
+/// the verification error of the lock groups is expected.
+pub fn dry_run_report<F>(
+    rpc_client: &mut HttpRpcClient,
+    network: NetworkType,
+    tx: &TransactionView,
+    change_index: Option<usize>,
+    outputs_validator: Option<OutputsValidator>,
+    mut get_input_cell: F,
+) -> Result<serde_json::Value, String>
+where
+    F: FnMut(&OutPoint) -> Result<CellOutput, String>,
+{
+    let mut input_capacity: u64 = 0;
+    for input in tx.inputs() {
+        let output = get_input_cell(&input.previous_output())?;
+        let capacity: u64 = output.capacity().unpack();
+        input_capacity += capacity;
+    }
+    let output_capacity: u64 = tx
+        .outputs()
+        .into_iter()
+        .map(|output| {
+            let capacity: u64 = output.capacity().unpack();
+            capacity
+        })
+        .sum();
+    let fee = input_capacity.checked_sub(output_capacity).ok_or_else(|| {
+        format!(
+            "Output capacity {:#} is more than input capacity {:#}",
+            HumanCapacity::from(output_capacity),
+            HumanCapacity::from(input_capacity),
+        )
+    })?;
+    let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+    let change = change_index
+        .and_then(|index| tx.output(index).map(|output| (index, output)))
+        .map(|(index, output)| {
+            let capacity: u64 = output.capacity().unpack();
+            let payload = AddressPayload::from(output.lock());
+            serde_json::json!({
+                "index": index,
+                "address": Address::new(network, payload, true).to_string(),
+                "capacity": format!("{:#}", HumanCapacity::from(capacity)),
+            })
+        });
+    let estimate_cycles = match rpc_client.estimate_cycles(tx.data()) {
+        Ok(result) => serde_json::json!({ "cycles": result.cycles }),
+        Err(err) => serde_json::json!({ "error": err }),
+    };
+    let tx_pool_accept = match rpc_client.test_tx_pool_accept(tx.data(), outputs_validator) {
+        Ok(entry) => serde_json::json!({ "accepted": true, "cycles": entry.cycles }),
+        Err(err) => serde_json::json!({ "accepted": false, "reason": err }),
+    };
+    let max_cycles = TxLimits::from_consensus(rpc_client)?.max_cycles;
+    let script_groups = match verify_script_groups(rpc_client, tx, max_cycles) {
+        Ok(groups) => {
+            let groups = groups
+                .into_iter()
+                .map(|group| {
+                    let mut group_info = group.to_json();
+                    if group.is_lock() && group.result.is_err() {
+                        group_info["expected_error"] = serde_json::json!(true);
+                    }
+                    group_info
+                })
+                .collect::<Vec<_>>();
+            serde_json::json!(groups)
+        }
+        Err(err) => serde_json::json!({ "error": err }),
+    };
+    let tx_hash: H256 = tx.hash().unpack();
+    Ok(serde_json::json!({
+        "tx_hash": tx_hash,
+        "size": tx_size,
+        "fee": format!("{:#}", HumanCapacity::from(fee)),
+        "fee_rate": FeeRate::calculate(Capacity::shannons(fee), tx_size).as_u64(),
+        "inputs": {
+            "count": tx.inputs().len(),
+            "capacity": format!("{:#}", HumanCapacity::from(input_capacity)),
+        },
+        "outputs": {
+            "count": tx.outputs().len(),
+            "capacity": format!("{:#}", HumanCapacity::from(output_capacity)),
+        },
+        "change": change,
+        "estimate_cycles": estimate_cycles,
+        "test_tx_pool_accept": tx_pool_accept,
+        "script_groups": script_groups,
+    }))
+}
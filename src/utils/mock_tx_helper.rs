@@ -6,11 +6,12 @@ use std::sync::Arc;
 use ckb_error::OtherError;
 use ckb_hash::new_blake2b;
 use ckb_jsonrpc_types as rpc_types;
-use ckb_mock_tx_types::{MockResourceLoader, MockTransaction, Resource};
-use ckb_script::{TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_mock_tx_types::{MockInfo, MockResourceLoader, MockTransaction, Resource};
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier, TxVerifyEnv};
 use ckb_sdk::constants::{MIN_SECP_CELL_CAPACITY, SIGHASH_TYPE_HASH};
+use ckb_sdk::traits::{TransactionDependencyError, TransactionDependencyProvider};
 use ckb_types::core::hardfork::{HardForks, CKB2021, CKB2023};
-use ckb_types::core::HeaderBuilder;
+use ckb_types::core::{HeaderBuilder, HeaderView, TransactionView};
 use ckb_types::{
     bytes::Bytes,
     core::{cell::resolve_transaction, Capacity, Cycle, ScriptHashType},
@@ -310,8 +311,44 @@ impl<'a> MockTransactionHelper<'a> {
     pub fn verify<L: MockResourceLoader>(
         &mut self,
         max_cycle: Cycle,
-        mut loader: L,
+        loader: L,
     ) -> Result<Cycle, String> {
+        let mut verifier = self.build_verifier(loader)?;
+        verifier.set_debug_printer(|script_hash, message| {
+            println!("script: {:x}, debug: {}", script_hash, message);
+        });
+        verifier
+            .verify(max_cycle)
+            .map_err(|err| format!("Verify script error: {:?}", err))
+    }
+
+    /// Verify each script group separately by local ScriptVerifier, the
+    /// verification error of one group does not stop the others.
+    pub fn verify_groups<L: MockResourceLoader>(
+        &mut self,
+        max_cycle: Cycle,
+        loader: L,
+    ) -> Result<Vec<ScriptGroupCycles>, String> {
+        let verifier = self.build_verifier(loader)?;
+        Ok(verifier
+            .groups_with_type()
+            .map(|(group_type, script_hash, group)| ScriptGroupCycles {
+                group_type,
+                script_hash: script_hash.clone(),
+                script: group.script.clone(),
+                input_indices: group.input_indices.clone(),
+                output_indices: group.output_indices.clone(),
+                result: verifier
+                    .verify_single(group_type, script_hash, max_cycle)
+                    .map_err(|err| err.to_string()),
+            })
+            .collect())
+    }
+
+    fn build_verifier<L: MockResourceLoader>(
+        &mut self,
+        mut loader: L,
+    ) -> Result<TransactionScriptsVerifier<Resource>, String> {
         let resource = Resource::from_both(self.mock_tx, &mut loader)?;
         let tx = self.mock_tx.core_transaction();
         let rtx = {
@@ -328,18 +365,128 @@ impl<'a> MockTransactionHelper<'a> {
         let tip = HeaderBuilder::default().number(0.pack()).build();
         let tx_verify_env = TxVerifyEnv::new_submit(&tip);
 
-        let mut verifier = TransactionScriptsVerifier::new(
+        Ok(TransactionScriptsVerifier::new(
             Arc::new(rtx),
             resource,
             Arc::new(consensus),
             Arc::new(tx_verify_env),
-        );
-        verifier.set_debug_printer(|script_hash, message| {
-            println!("script: {:x}, debug: {}", script_hash, message);
+        ))
+    }
+}
+
+/// The cycles consumed by a script group (or the verification error)
+pub struct ScriptGroupCycles {
+    pub group_type: ScriptGroupType,
+    pub script_hash: Byte32,
+    pub script: Script,
+    pub input_indices: Vec<usize>,
+    pub output_indices: Vec<usize>,
+    pub result: Result<Cycle, String>,
+}
+
+impl ScriptGroupCycles {
+    pub fn is_lock(&self) -> bool {
+        self.group_type == ScriptGroupType::Lock
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let group_type = match self.group_type {
+            ScriptGroupType::Lock => "lock",
+            ScriptGroupType::Type => "type",
+        };
+        let script_hash: H256 = self.script_hash.unpack();
+        let mut group_info = serde_json::json!({
+            "group_type": group_type,
+            "script_hash": script_hash,
+            "script": rpc_types::Script::from(self.script.clone()),
+            "inputs": self.input_indices,
+            "outputs": self.output_indices,
         });
-        verifier
-            .verify(max_cycle)
-            .map_err(|err| format!("Verify script error: {:?}", err))
+        match self.result {
+            Ok(cycles) => group_info["cycles"] = serde_json::json!(cycles),
+            Err(ref err) => group_info["error"] = serde_json::json!(err),
+        }
+        group_info
+    }
+}
+
+/// Provide the transaction dependencies (input cells, cell deps, headers) from
+/// the mock info, so that the mock transaction can be unlocked without network.
+#[derive(Clone, Default)]
+pub struct MockTransactionDependencyProvider {
+    cells: HashMap<OutPoint, (CellOutput, Bytes)>,
+    headers: HashMap<Byte32, HeaderView>,
+}
+
+impl MockTransactionDependencyProvider {
+    pub fn new(mock_info: &MockInfo) -> MockTransactionDependencyProvider {
+        let mut cells = HashMap::default();
+        for input in &mock_info.inputs {
+            cells.insert(
+                input.input.previous_output(),
+                (input.output.clone(), input.data.clone()),
+            );
+        }
+        for cell_dep in &mock_info.cell_deps {
+            cells.insert(
+                cell_dep.cell_dep.out_point(),
+                (cell_dep.output.clone(), cell_dep.data.clone()),
+            );
+        }
+        let headers = mock_info
+            .header_deps
+            .iter()
+            .map(|header| (header.hash(), header.clone()))
+            .collect();
+        MockTransactionDependencyProvider { cells, headers }
+    }
+}
+
+impl TransactionDependencyProvider for MockTransactionDependencyProvider {
+    fn get_transaction(
+        &self,
+        tx_hash: &Byte32,
+    ) -> Result<TransactionView, TransactionDependencyError> {
+        Err(TransactionDependencyError::NotFound(format!(
+            "transaction not included in mock info: {}",
+            tx_hash
+        )))
+    }
+    fn get_cell(&self, out_point: &OutPoint) -> Result<CellOutput, TransactionDependencyError> {
+        self.cells
+            .get(out_point)
+            .map(|(output, _)| output.clone())
+            .ok_or_else(|| {
+                TransactionDependencyError::NotFound(format!(
+                    "cell not included in mock info: {}",
+                    out_point
+                ))
+            })
+    }
+    fn get_cell_data(&self, out_point: &OutPoint) -> Result<Bytes, TransactionDependencyError> {
+        self.cells
+            .get(out_point)
+            .map(|(_, data)| data.clone())
+            .ok_or_else(|| {
+                TransactionDependencyError::NotFound(format!(
+                    "cell not included in mock info: {}",
+                    out_point
+                ))
+            })
+    }
+    fn get_header(&self, block_hash: &Byte32) -> Result<HeaderView, TransactionDependencyError> {
+        self.headers.get(block_hash).cloned().ok_or_else(|| {
+            TransactionDependencyError::NotFound(format!(
+                "header not included in mock info: {}",
+                block_hash
+            ))
+        })
+    }
+    fn get_block_extension(
+        &self,
+        _block_hash: &Byte32,
+    ) -> Result<Option<ckb_types::packed::Bytes>, TransactionDependencyError> {
+        Ok(None)
     }
 }
 
@@ -1,8 +1,11 @@
+pub mod airdrop;
 pub mod arg;
 pub mod arg_parser;
 pub mod cell_dep;
+pub mod coin_selection;
 pub mod completer;
 pub mod config;
+pub mod dry_run;
 pub mod genesis_info;
 pub mod json_color;
 pub mod mock_tx_helper;
@@ -10,7 +13,11 @@ pub mod other;
 pub mod printer;
 pub mod rpc;
 pub mod signer;
+pub mod token_registry;
+pub mod tx_chunks;
+pub mod tx_explain;
 pub mod tx_helper;
+pub mod tx_template;
 
 #[allow(clippy::cast_lossless)]
 pub mod yaml_ser;
@@ -14,12 +14,12 @@ use ckb_sdk::{
     traits::LiveCell,
     tx_builder::{BalanceTxCapacityError, TxBuilderError},
     util::serialize_signature,
-    Address, AddressPayload, NetworkType, SECP256K1,
+    Address, AddressPayload, NetworkType, Since, SinceType, SECP256K1,
 };
 use ckb_signer::{KeyStore, ScryptType};
 use ckb_types::{
     bytes::Bytes,
-    core::{BlockView, Capacity, TransactionView},
+    core::{BlockView, Capacity, EpochNumberWithFraction, TransactionView},
     h256,
     packed::{CellInput, CellOutput, OutPoint},
     prelude::*,
@@ -34,6 +34,7 @@ use super::arg_parser::{
     AddressParser, ArgParser, FixedHashParser, HexParser, PrivkeyWrapper, PubkeyHexParser,
 };
 use super::rpc::{AlertMessage, HttpRpcClient};
+use super::signer::ExternalSigner;
 use super::tx_helper::SignerFn;
 use crate::plugin::{KeyStoreHandler, SignTarget};
 use crate::utils::genesis_info::GenesisInfo;
@@ -326,6 +327,30 @@ pub fn get_privkey_signer(privkey: PrivkeyWrapper) -> SignerFn {
     )
 }
 
+pub fn get_external_signer(signer: ExternalSigner) -> SignerFn {
+    Box::new(
+        move |lock_args: &HashSet<H160>, message: &H256, _tx: &rpc_types::Transaction| {
+            if let Some(lock_arg) = lock_args
+                .iter()
+                .find(|lock_arg| signer.has_account(lock_arg))
+            {
+                if message == &h256!("0x0") {
+                    Ok(Some([0u8; 65]))
+                } else {
+                    let data = signer
+                        .sign_message(lock_arg, message, true)?
+                        .expect("signer has the account");
+                    let mut signature = [0u8; 65];
+                    signature.copy_from_slice(&data[..]);
+                    Ok(Some(signature))
+                }
+            } else {
+                Ok(None)
+            }
+        },
+    )
+}
+
 pub fn get_arg_value(matches: &ArgMatches, name: &str) -> Result<String, String> {
     matches
         .value_of(name)
@@ -333,6 +358,13 @@ pub fn get_arg_value(matches: &ArgMatches, name: &str) -> Result<String, String>
         .ok_or_else(|| format!("<{}> is required", name))
 }
 
+pub fn get_arg_values(matches: &ArgMatches, name: &str) -> Vec<String> {
+    matches
+        .values_of(name)
+        .map(|values| values.map(|s| s.to_string()).collect())
+        .unwrap_or_default()
+}
+
 pub fn to_live_cell_info(cell: &LiveCell) -> LiveCellInfo {
     let output_index: u32 = cell.out_point.index().unpack();
     LiveCellInfo {
@@ -362,6 +394,77 @@ pub fn address_json(payload: AddressPayload, is_new: bool) -> serde_json::Value
     })
 }
 
+/// Explain a raw since value (see RFC0017)
+pub fn since_json(since: u64) -> serde_json::Value {
+    let raw = format!("{:#x}", since);
+    if since == 0 {
+        return serde_json::json!({
+            "raw": raw,
+            "valid": true,
+            "description": "No since restriction",
+        });
+    }
+    let since_value = Since::from_raw_value(since);
+    let (since_type, value) = match since_value.extract_metric() {
+        Some(metric) if since_value.flags_is_valid() => metric,
+        _ => {
+            return serde_json::json!({
+                "raw": raw,
+                "valid": false,
+                "description": "Invalid since flags",
+            });
+        }
+    };
+    let is_relative = since_value.is_relative();
+    let (metric, value, description) = match since_type {
+        SinceType::EpochNumberWithFraction => {
+            let epoch = EpochNumberWithFraction::from_full_value(value);
+            let epoch_str = format!("{}+{}/{}", epoch.number(), epoch.index(), epoch.length());
+            let description = if is_relative {
+                format!("{} epochs after the input cell committed", epoch_str)
+            } else {
+                format!("Not before epoch {}", epoch_str)
+            };
+            let value = serde_json::json!({
+                "number": epoch.number(),
+                "index": epoch.index(),
+                "length": epoch.length(),
+            });
+            ("epoch", value, description)
+        }
+        SinceType::BlockNumber => {
+            let description = if is_relative {
+                format!("{} blocks after the input cell committed", value)
+            } else {
+                format!("Not before block {}", value)
+            };
+            ("block_number", serde_json::json!(value), description)
+        }
+        SinceType::Timestamp => {
+            let description = if is_relative {
+                format!(
+                    "{} seconds (median time) after the input cell committed",
+                    value
+                )
+            } else {
+                let time = chrono::DateTime::from_timestamp(value as i64, 0)
+                    .map(|time| time.to_rfc3339())
+                    .unwrap_or_else(|| value.to_string());
+                format!("Not before {} (median time)", time)
+            };
+            ("timestamp", serde_json::json!(value), description)
+        }
+    };
+    serde_json::json!({
+        "raw": raw,
+        "valid": true,
+        "relative": is_relative,
+        "metric": metric,
+        "value": value,
+        "description": description,
+    })
+}
+
 pub fn calculate_type_id(first_cell_input: &CellInput, output_index: u64) -> [u8; 32] {
     let mut blake2b = new_blake2b();
     blake2b.update(first_cell_input.as_slice());
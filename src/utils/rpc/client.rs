@@ -2,7 +2,7 @@ use std::convert::TryInto;
 
 use ckb_jsonrpc_types::{
     Alert, BlockNumber, CellWithStatus, EpochNumber, EpochNumberWithFraction, JsonBytes,
-    OutputsValidator, Uint32,
+    OutputsValidator, PoolTxDetailInfo, Uint32,
 };
 pub use ckb_sdk::{
     rpc::ckb_indexer::{Order, Pagination, SearchKey},
@@ -376,6 +376,11 @@ impl HttpRpcClient {
             .map(Into::into)
             .map_err(|err| err.to_string())
     }
+    pub fn get_pool_tx_detail_info(&mut self, tx_hash: H256) -> Result<PoolTxDetailInfo, String> {
+        self.client
+            .get_pool_tx_detail_info(tx_hash)
+            .map_err(|err| err.to_string())
+    }
     pub fn clear_tx_verify_queue(&mut self) -> Result<(), String> {
         self.client
             .clear_tx_verify_queue()
@@ -6,7 +6,7 @@ mod types;
 pub use client::{HttpRpcClient, RawHttpRpcClient};
 pub use primitive::Timestamp;
 pub use types::{
-    parse_order, AlertMessage, BannedAddr, BlockEconomicState, BlockView, ChainInfo, EpochView,
-    HeaderView, JsonBytes, PackedBlockResponse, RemoteNode, TransactionProof,
-    TransactionWithStatus,
+    parse_order, AlertMessage, BannedAddr, BlockEconomicState, BlockView, Cell, ChainInfo,
+    EpochView, HeaderView, JsonBytes, PackedBlockResponse, RemoteNode, TransactionProof,
+    TransactionWithStatus, Tx,
 };
@@ -1609,6 +1609,7 @@ pub struct TxPoolInfo {
     pub total_tx_size: Uint64,
     pub total_tx_cycles: Uint64,
     pub min_fee_rate: Uint64,
+    pub min_rbf_rate: Uint64,
     pub last_txs_updated_at: Timestamp,
 }
 impl From<rpc_types::TxPoolInfo> for TxPoolInfo {
@@ -1622,6 +1623,7 @@ impl From<rpc_types::TxPoolInfo> for TxPoolInfo {
             total_tx_size: json.total_tx_size.into(),
             total_tx_cycles: json.total_tx_cycles.into(),
             min_fee_rate: json.min_fee_rate.value(),
+            min_rbf_rate: json.min_rbf_rate.value(),
             last_txs_updated_at: json.last_txs_updated_at.into(),
         }
     }
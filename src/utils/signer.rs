@@ -1,7 +1,11 @@
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
 
 use anyhow::anyhow;
-use bitcoin::bip32::DerivationPath;
+use bitcoin::bip32::{DerivationPath, Xpub};
 
 use ckb_hash::blake2b_256;
 use ckb_jsonrpc_types as json_types;
@@ -11,10 +15,11 @@ use ckb_sdk::traits::{
 use ckb_sdk::types::ScriptId;
 use ckb_sdk::util::serialize_signature;
 use ckb_sdk::SECP256K1;
-use ckb_signer::KeyChain;
+use ckb_signer::{CkbRoot, DerivedKeySet, KeyChain, CKB_ROOT_PATH};
 use ckb_types::{bytes::Bytes, core::TransactionView, packed::Script, prelude::*, H160, H256};
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
 
-use super::arg_parser::PrivkeyWrapper;
+use super::arg_parser::{ArgParser, HexParser, PrivkeyWrapper};
 use crate::plugin::{KeyStoreHandler, SignTarget};
 
 impl Signer for PrivkeyWrapper {
@@ -202,6 +207,34 @@ impl KeyStoreHandlerSigner {
         Ok(())
     }
 
+    pub fn cache_key_set_by_index(
+        &mut self,
+        account: H160,
+        external_length: u32,
+        change_length: u32,
+    ) -> Result<(), String> {
+        let password = self.passwords.get(&account).cloned();
+        let key_set = self.handler.derived_key_set_by_index(
+            account.clone(),
+            0,
+            external_length,
+            0,
+            change_length,
+            password,
+        )?;
+        for (path, pubkey_hash) in key_set.external {
+            self.ids.insert(
+                pubkey_hash,
+                (path, Some(KeyChain::External), account.clone()),
+            );
+        }
+        for (path, pubkey_hash) in key_set.change {
+            self.ids
+                .insert(pubkey_hash, (path, Some(KeyChain::Change), account.clone()));
+        }
+        Ok(())
+    }
+
     fn get_id_info(&self, id: &[u8]) -> Option<(DerivationPath, Option<KeyChain>, H160)> {
         if id.len() != 20 {
             return None;
@@ -283,6 +316,259 @@ impl Signer for KeyStoreHandlerSigner {
     }
 }
 
+/// Default derived key set length of the external signer
+pub const EXTERNAL_SIGNER_KEY_SET_LENGTH: u32 = 100;
+
+/// A signer delegates the signing to an external process (HSM, PKCS#11
+/// bridge, etc.), the protocol is described in `docs/External-Signer.md`.
+///
+/// The external process is spawned for every request, one json request line
+/// is written to its stdin and one json response line is read from its stdout.
+#[derive(Clone)]
+pub struct ExternalSigner {
+    cmd: PathBuf,
+    ckb_root: CkbRoot,
+    // id => (derivation path, pubkey hash)
+    ids: HashMap<H160, (DerivationPath, H160)>,
+}
+
+impl ExternalSigner {
+    pub fn new(cmd: PathBuf) -> Result<ExternalSigner, String> {
+        let root_path = DerivationPath::from_str(CKB_ROOT_PATH).expect("parse ckb root path");
+        let extended_pubkey = external_extended_pubkey(&cmd, &root_path)?;
+        let ckb_root =
+            CkbRoot::from_extended_pubkey(&extended_pubkey).map_err(|err| err.to_string())?;
+        let mut signer = ExternalSigner {
+            cmd,
+            ckb_root,
+            ids: HashMap::default(),
+        };
+        let root_hash160 = signer.ckb_root.hash160();
+        signer
+            .ids
+            .insert(root_hash160.clone(), (root_path, root_hash160));
+        signer.derived_key_set_by_index(
+            EXTERNAL_SIGNER_KEY_SET_LENGTH,
+            EXTERNAL_SIGNER_KEY_SET_LENGTH,
+        );
+        Ok(signer)
+    }
+
+    /// The account id (pubkey hash of the ckb root key)
+    pub fn account(&self) -> H160 {
+        self.ckb_root.hash160()
+    }
+
+    pub fn has_account(&self, account: &H160) -> bool {
+        self.ids
+            .get(account)
+            .map(|(_, hash160)| hash160 == account)
+            .unwrap_or(false)
+    }
+
+    /// Make sure the lock arg is derived from the key of the signer, so the
+    /// signer will not sign for a wrong account/derivation path.
+    pub fn check_account(&self, lock_arg: &H160) -> Result<(), String> {
+        if self.has_account(lock_arg) {
+            Ok(())
+        } else {
+            Err(format!(
+                "The external signer (account: {:#x}) can not sign for lock arg {:#x}, it is not derived from the signer's key (m/44'/309'/0', first {} receiving/change keys)",
+                self.account(),
+                lock_arg,
+                EXTERNAL_SIGNER_KEY_SET_LENGTH,
+            ))
+        }
+    }
+
+    /// Derive the key set (and remember the keys for signing)
+    pub fn derived_key_set(
+        &mut self,
+        external_max_len: u32,
+        change_last: &H160,
+        change_max_len: u32,
+    ) -> Result<DerivedKeySet, String> {
+        let key_set = self
+            .ckb_root
+            .derived_key_set(external_max_len, change_last, change_max_len)
+            .map_err(|err| err.to_string())?;
+        self.cache_key_set(&key_set);
+        Ok(key_set)
+    }
+
+    /// Derive the key set by index (and remember the keys for signing)
+    pub fn derived_key_set_by_index(
+        &mut self,
+        external_length: u32,
+        change_length: u32,
+    ) -> DerivedKeySet {
+        let key_set = self
+            .ckb_root
+            .derived_key_set_by_index(0, external_length, 0, change_length);
+        self.cache_key_set(&key_set);
+        key_set
+    }
+
+    fn cache_key_set(&mut self, key_set: &DerivedKeySet) {
+        for (path, hash160) in key_set.external.iter().chain(key_set.change.iter()) {
+            self.ids
+                .insert(hash160.clone(), (path.clone(), hash160.clone()));
+        }
+    }
+
+    pub fn cache_account_lock_hash160(&mut self, account: H160, script_id: &ScriptId) -> bool {
+        if let Some(info) = self.ids.get(&account).cloned() {
+            let script_hash = Script::new_builder()
+                .code_hash(script_id.code_hash.pack())
+                .hash_type(script_id.hash_type.into())
+                .args(Bytes::from(account.as_bytes().to_vec()).pack())
+                .build()
+                .calc_script_hash();
+            let lock_hash160 = H160::from_slice(&script_hash.as_slice()[0..20]).unwrap();
+            self.ids.insert(lock_hash160, info);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sign the message by the key of `id`, return None if the id not belongs to the signer
+    pub fn sign_message(
+        &self,
+        id: &H160,
+        message: &H256,
+        recoverable: bool,
+    ) -> Result<Option<Bytes>, String> {
+        let (path, hash160) = match self.ids.get(id) {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        let params = serde_json::json!({
+            "path": path.to_string(),
+            "digest": format!("{:#x}", message),
+            "recoverable": recoverable,
+        });
+        let result = call_external_signer(&self.cmd, "sign", params)?;
+        let signature_hex = result
+            .get("signature")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| "Invalid external signer response: missing signature".to_string())?;
+        let signature = HexParser.parse(signature_hex)?;
+        let expected_len = if recoverable { 65 } else { 64 };
+        if signature.len() != expected_len {
+            return Err(format!(
+                "Invalid signature length from external signer: {}, expected: {}",
+                signature.len(),
+                expected_len
+            ));
+        }
+        if recoverable {
+            // Make sure the signature is signed by the expected key
+            let recid = RecoveryId::from_i32(i32::from(signature[64]))
+                .map_err(|err| format!("Invalid signature from external signer: {}", err))?;
+            let sig = RecoverableSignature::from_compact(&signature[0..64], recid)
+                .map_err(|err| format!("Invalid signature from external signer: {}", err))?;
+            let msg = secp256k1::Message::from_digest_slice(message.as_bytes())
+                .expect("Convert to message failed");
+            let pubkey = SECP256K1
+                .recover_ecdsa(&msg, &sig)
+                .map_err(|err| format!("Invalid signature from external signer: {}", err))?;
+            if &blake2b_256(&pubkey.serialize()[..])[0..20] != hash160.as_bytes() {
+                return Err(format!(
+                    "The signature from external signer is not signed by the key of path: {}",
+                    path
+                ));
+            }
+        }
+        Ok(Some(Bytes::from(signature)))
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn match_id(&self, id: &[u8]) -> bool {
+        id.len() == 20 && self.ids.contains_key(&H160::from_slice(id).unwrap())
+    }
+
+    fn sign(
+        &self,
+        id: &[u8],
+        message: &[u8],
+        recoverable: bool,
+        _tx: &TransactionView,
+    ) -> Result<Bytes, SignerError> {
+        if id.len() != 20 {
+            return Err(SignerError::IdNotFound);
+        }
+        if message.len() != 32 {
+            return Err(SignerError::InvalidMessage(format!(
+                "expected length: 32, got: {}",
+                message.len()
+            )));
+        }
+        let id = H160::from_slice(id).unwrap();
+        let message = H256::from_slice(message).unwrap();
+        self.sign_message(&id, &message, recoverable)
+            .map_err(|err| SignerError::Other(anyhow!(err)))?
+            .ok_or(SignerError::IdNotFound)
+    }
+}
+
+fn external_extended_pubkey(cmd: &Path, path: &DerivationPath) -> Result<Xpub, String> {
+    let params = serde_json::json!({ "path": path.to_string() });
+    let result = call_external_signer(cmd, "get_xpub", params)?;
+    let xpub = result
+        .get("xpub")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| "Invalid external signer response: missing xpub".to_string())?;
+    Xpub::from_str(xpub).map_err(|err| format!("Invalid xpub from external signer: {}", err))
+}
+
+fn call_external_signer(
+    cmd: &Path,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let request = serde_json::json!({
+        "id": 0,
+        "method": method,
+        "params": params,
+    });
+    let mut child = Command::new(cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|err| format!("Start external signer {:?} failed: {}", cmd, err))?;
+    {
+        let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+        stdin
+            .write_all(format!("{}\n", request).as_bytes())
+            .map_err(|err| format!("Write request to external signer failed: {}", err))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Read response from external signer failed: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("External signer exit with: {}", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next().unwrap_or_default();
+    let response: serde_json::Value = serde_json::from_str(line)
+        .map_err(|err| format!("Invalid external signer response: {}", err))?;
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|value| value.as_str())
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| error.to_string());
+        return Err(format!("External signer error: {}", message));
+    }
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| "Invalid external signer response: missing result".to_string())
+}
+
 pub struct CommonSigner {
     signers: Vec<Box<dyn Signer>>,
 }
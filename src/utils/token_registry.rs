@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ckb_jsonrpc_types as json_types;
+use serde::{Deserialize, Serialize};
+
+use super::{arg_parser::UDT_MAX_DECIMALS, cell_dep::CellDeps};
+
+pub const TOKEN_REGISTRY_FILENAME: &str = "tokens.json";
+
+/// A registered udt token, `sudt` subcommands use it (by `--token <symbol>`)
+/// instead of `--owner`, `--cell-deps` and the udt type arguments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub symbol: String,
+    pub decimals: u32,
+    /// The owner address of the token
+    pub owner: String,
+    /// "sudt" or "xudt"
+    pub udt_type: String,
+    #[serde(default)]
+    pub xudt_owner_mode: Vec<String>,
+    #[serde(default)]
+    pub xudt_extensions: Vec<json_types::Script>,
+    pub cell_deps: CellDeps,
+}
+
+/// The local token registry file (`~/.ckb-cli/tokens.json`)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TokenRegistry {
+    pub tokens: Vec<TokenInfo>,
+}
+
+impl TokenRegistry {
+    pub fn path(ckb_cli_dir: &Path) -> PathBuf {
+        ckb_cli_dir.join(TOKEN_REGISTRY_FILENAME)
+    }
+
+    /// Load the registry, return an empty registry if the file not exists
+    pub fn load(path: &Path) -> Result<TokenRegistry, String> {
+        if !path.exists() {
+            return Ok(TokenRegistry::default());
+        }
+        let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let registry: TokenRegistry = serde_json::from_str(&content)
+            .map_err(|err| format!("Invalid token registry file {:?}: {}", path, err))?;
+        for token in &registry.tokens {
+            if token.decimals > UDT_MAX_DECIMALS {
+                return Err(format!(
+                    "Invalid token registry file {:?}: the decimals of {} is more than {}",
+                    path, token.symbol, UDT_MAX_DECIMALS
+                ));
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(path, content).map_err(|err| err.to_string())
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&TokenInfo> {
+        self.tokens.iter().find(|token| token.symbol == symbol)
+    }
+
+    /// Add a token, the symbol must be unique
+    pub fn add(&mut self, token: TokenInfo) -> Result<(), String> {
+        if self.get(&token.symbol).is_some() {
+            return Err(format!("Token already exists: {}", token.symbol));
+        }
+        self.tokens.push(token);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, symbol: &str) -> Option<TokenInfo> {
+        let index = self
+            .tokens
+            .iter()
+            .position(|token| token.symbol == symbol)?;
+        Some(self.tokens.remove(index))
+    }
+}
+
+/// Format the amount in base units with the decimals (example: 1250000000
+/// with decimals 8 is "12.5")
+pub fn format_udt_amount(amount: u128, decimals: u32) -> Result<String, String> {
+    let unit = 10u128
+        .checked_pow(decimals)
+        .ok_or_else(|| format!("The decimals {} is too large", decimals))?;
+    let integer = amount / unit;
+    let fraction = amount % unit;
+    if fraction == 0 {
+        Ok(integer.to_string())
+    } else {
+        let fraction_str = format!("{:0width$}", fraction, width = decimals as usize);
+        Ok(format!(
+            "{}.{}",
+            integer,
+            fraction_str.trim_end_matches('0')
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn token(symbol: &str) -> TokenInfo {
+        TokenInfo {
+            symbol: symbol.to_string(),
+            decimals: 8,
+            owner: "ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy".to_string(),
+            udt_type: "sudt".to_string(),
+            xudt_owner_mode: Vec::new(),
+            xudt_extensions: Vec::new(),
+            cell_deps: CellDeps {
+                items: HashMap::default(),
+                extensions: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = TokenRegistry::default();
+        registry.add(token("AAA")).unwrap();
+        registry.add(token("BBB")).unwrap();
+        assert!(registry.add(token("AAA")).is_err());
+        assert_eq!(registry.get("BBB").unwrap().decimals, 8);
+        assert!(registry.remove("AAA").is_some());
+        assert!(registry.remove("AAA").is_none());
+        assert!(registry.get("AAA").is_none());
+        assert_eq!(registry.tokens.len(), 1);
+    }
+
+    #[test]
+    fn test_format_udt_amount() {
+        assert_eq!(format_udt_amount(12_5000_0000, 8).unwrap(), "12.5");
+        assert_eq!(format_udt_amount(1, 8).unwrap(), "0.00000001");
+        assert_eq!(format_udt_amount(100, 0).unwrap(), "100");
+        assert_eq!(format_udt_amount(200, 2).unwrap(), "2");
+        assert_eq!(
+            format_udt_amount(u128::MAX, 38).unwrap(),
+            "3.40282366920938463463374607431768211455"
+        );
+        assert!(format_udt_amount(1, 39).is_err());
+    }
+
+    #[test]
+    fn test_load_invalid_decimals() {
+        let path = std::env::temp_dir().join(format!(
+            "ckb-cli-token-registry-test-{}.json",
+            std::process::id()
+        ));
+        let mut registry = TokenRegistry::default();
+        registry.add(token("AAA")).unwrap();
+        registry.save(&path).unwrap();
+        assert_eq!(TokenRegistry::load(&path).unwrap().tokens.len(), 1);
+
+        registry.tokens[0].decimals = UDT_MAX_DECIMALS + 1;
+        registry.save(&path).unwrap();
+        assert!(TokenRegistry::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
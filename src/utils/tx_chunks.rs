@@ -0,0 +1,124 @@
+use std::cmp;
+use std::ops::Range;
+
+use ckb_jsonrpc_types::OutputsValidator;
+use ckb_sdk::traits::CellCollector;
+use ckb_types::core::TransactionView;
+
+use super::rpc::HttpRpcClient;
+
+// Max transaction size when split the items into transactions
+pub const MAX_TX_SIZE: u64 = 512 * 1000;
+// Max transaction cycles (default max_tx_verify_cycles of the tx pool)
+pub const MAX_TX_VERIFY_CYCLES: u64 = 70_000_000;
+
+/// The size and cycles limits of one transaction
+#[derive(Clone, Copy, Debug)]
+pub struct TxLimits {
+    pub max_size: u64,
+    pub max_cycles: u64,
+}
+
+impl TxLimits {
+    /// The default limits capped by the consensus of the node
+    pub fn from_consensus(rpc_client: &mut HttpRpcClient) -> Result<TxLimits, String> {
+        let consensus = rpc_client.get_consensus()?;
+        Ok(TxLimits {
+            max_size: cmp::min(MAX_TX_SIZE, consensus.max_block_bytes),
+            max_cycles: cmp::min(MAX_TX_VERIFY_CYCLES, consensus.max_block_cycles),
+        })
+    }
+}
+
+/// Split the items (transfer targets, airdrop recipients, etc.) into
+/// transactions, a chunk is halved until its transaction fits the limits.
+pub struct TxChunker {
+    /// The number of items
+    pub len: usize,
+    /// Max items in one transaction
+    pub max_chunk_len: usize,
+    /// The transactions are not checked when it is `None`
+    pub limits: Option<TxLimits>,
+    /// Check the cycles by `test_tx_pool_accept`, the transaction must be signed
+    pub check_cycles: bool,
+    pub outputs_validator: Option<OutputsValidator>,
+}
+
+impl TxChunker {
+    /// Build the transaction of every chunk by `build_chunk` (with a copy of
+    /// the cell collector), then hand the transaction fits the limits to
+    /// `send_chunk`. The change cell of a sent transaction is available to the
+    /// next one.
+    ///
+    /// The `item_label` describes the item in the error message.
+    pub fn run<C, L, B, S>(
+        &self,
+        rpc_client: &mut HttpRpcClient,
+        cell_collector: &mut C,
+        item_label: L,
+        mut build_chunk: B,
+        mut send_chunk: S,
+    ) -> Result<(), String>
+    where
+        C: CellCollector + Clone,
+        L: Fn(usize) -> String,
+        B: FnMut(&mut HttpRpcClient, &mut C, Range<usize>) -> Result<TransactionView, String>,
+        S: FnMut(&mut HttpRpcClient, &TransactionView, Range<usize>) -> Result<(), String>,
+    {
+        let mut start = 0;
+        let mut chunk_len = cmp::min(self.max_chunk_len, self.len);
+        while start < self.len {
+            let end = cmp::min(start + chunk_len, self.len);
+            // Only commit the collector state when the transaction is sent
+            let mut chunk_cell_collector = cell_collector.clone();
+            let tx = build_chunk(rpc_client, &mut chunk_cell_collector, start..end)?;
+            if self.exceeds(rpc_client, &tx)? {
+                if end - start == 1 {
+                    return Err(format!(
+                        "Transaction for {} exceeds the size or cycles limit",
+                        item_label(start)
+                    ));
+                }
+                chunk_len = (end - start + 1) / 2;
+                continue;
+            }
+
+            send_chunk(rpc_client, &tx, start..end)?;
+            if end < self.len {
+                // Make the change cell of this transaction available to the next one
+                let tip_number = rpc_client.get_tip_block_number()?;
+                chunk_cell_collector
+                    .apply_tx(tx.data(), tip_number)
+                    .map_err(|err| err.to_string())?;
+            }
+            *cell_collector = chunk_cell_collector;
+            start = end;
+        }
+        Ok(())
+    }
+
+    fn exceeds(
+        &self,
+        rpc_client: &mut HttpRpcClient,
+        tx: &TransactionView,
+    ) -> Result<bool, String> {
+        let limits = match self.limits {
+            Some(limits) => limits,
+            None => return Ok(false),
+        };
+        // The placeholder witnesses have the same size as the signatures, so
+        // the size of an unsigned transaction is also checked
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        if tx_size > limits.max_size {
+            return Ok(true);
+        }
+        if !self.check_cycles {
+            return Ok(false);
+        }
+        match rpc_client.test_tx_pool_accept(tx.data(), self.outputs_validator.clone()) {
+            Ok(entry) => Ok(entry.cycles > limits.max_cycles),
+            Err(err) if err.contains("ExceededMaximumCycles") => Ok(true),
+            Err(err) => Err(format!("Test transaction error: {}", err)),
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ckb_jsonrpc_types as json_types;
+use ckb_sdk::{
+    constants::{DAO_TYPE_HASH, MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH, TYPE_ID_CODE_HASH},
+    types::ScriptId,
+    util::calculate_dao_maximum_withdraw4,
+    Address, AddressPayload, HumanCapacity, NetworkType,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{self, Capacity, FeeRate},
+    packed::{self, CellDep, CellOutput, OutPoint, Script},
+    prelude::*,
+    H256,
+};
+use faster_hex::hex_string;
+
+use super::{cell_dep::CellDeps, genesis_info::GenesisInfo, rpc::HttpRpcClient};
+
+/// An input cell of the transaction to explain
+#[derive(Clone, Debug)]
+pub struct ResolvedCell {
+    pub output: CellOutput,
+    pub data: Bytes,
+    /// The hash of the block which the cell committed in
+    pub block_hash: Option<H256>,
+}
+
+/// Explain a transaction:
+///
+///   * the names of the well known lock/type scripts and cell deps (sighash,
+///     multisig, dao, type_id, and sudt/xudt/acp/cheque from `cell_deps`)
+///   * the sudt (or xudt) amount and the DAO phase of the cells
+///   * the capacity and sudt amount changes of every lock script, and the fee
+///
+/// The input cells not in `resolved_inputs` are loaded from the transactions
+/// which created them, so the inputs of a committed transaction (dead cells)
+/// can also be explained.
+pub fn explain_tx(
+    rpc_client: &mut HttpRpcClient,
+    network: NetworkType,
+    genesis_info: &GenesisInfo,
+    cell_deps: Option<&CellDeps>,
+    tx: &core::TransactionView,
+    mut resolved_inputs: HashMap<OutPoint, ResolvedCell>,
+) -> Result<serde_json::Value, String> {
+    let names = ScriptNames::new(cell_deps);
+    let mut changes: BTreeMap<String, LockChange> = BTreeMap::new();
+
+    let mut input_total: u64 = 0;
+    let mut inputs = Vec::new();
+    // The inputs of cellbase are not real cells
+    let real_inputs = if tx.is_cellbase() {
+        Vec::new()
+    } else {
+        tx.inputs().into_iter().collect()
+    };
+    for input in real_inputs {
+        let out_point = input.previous_output();
+        let cell = match resolved_inputs.remove(&out_point) {
+            Some(cell) => cell,
+            None => load_cell(rpc_client, &out_point)?,
+        };
+        let mut cell_json = names.cell_json(network, &cell.output, &cell.data);
+        // The DAO compensation is paid to the withdrawing cell
+        let mut capacity: u64 = cell.output.capacity().unpack();
+        if let Some(maximum_withdraw) = dao_maximum_withdraw(rpc_client, &names, &cell)? {
+            cell_json["dao"]["maximum_withdraw"] =
+                serde_json::json!(format!("{:#}", HumanCapacity(maximum_withdraw)));
+            capacity = maximum_withdraw;
+        }
+        cell_json["out_point"] = serde_json::json!(json_types::OutPoint::from(out_point));
+        inputs.push(cell_json);
+
+        input_total = input_total
+            .checked_add(capacity)
+            .ok_or_else(|| "The input capacity overflows".to_string())?;
+        let change = changes
+            .entry(address_string(network, &cell.output.lock()))
+            .or_insert_with(|| LockChange::new(names.name(&cell.output.lock())));
+        apply_change(&mut change.capacity, capacity.into(), false)?;
+        if let Some((args, amount)) = names.sudt_amount(&cell.output, &cell.data) {
+            apply_change(change.sudt.entry(args).or_default(), amount, false)?;
+        }
+    }
+
+    let mut output_total: u64 = 0;
+    let mut outputs = Vec::new();
+    for (output, data) in tx.outputs_with_data_iter() {
+        outputs.push(names.cell_json(network, &output, &data));
+
+        let capacity: u64 = output.capacity().unpack();
+        output_total = output_total
+            .checked_add(capacity)
+            .ok_or_else(|| "The output capacity overflows".to_string())?;
+        let change = changes
+            .entry(address_string(network, &output.lock()))
+            .or_insert_with(|| LockChange::new(names.name(&output.lock())));
+        apply_change(&mut change.capacity, capacity.into(), true)?;
+        if let Some((args, amount)) = names.sudt_amount(&output, &data) {
+            apply_change(change.sudt.entry(args).or_default(), amount, true)?;
+        }
+    }
+
+    let known_cell_deps = known_cell_deps(genesis_info, cell_deps);
+    let cell_deps_json = tx
+        .cell_deps()
+        .into_iter()
+        .map(|cell_dep| {
+            let name = known_cell_deps
+                .iter()
+                .find(|(known, _)| known == &cell_dep)
+                .map(|(_, name)| name.as_str())
+                .unwrap_or("unknown");
+            let cell_dep = json_types::CellDep::from(cell_dep);
+            serde_json::json!({
+                "name": name,
+                "out_point": cell_dep.out_point,
+                "dep_type": cell_dep.dep_type,
+            })
+        })
+        .collect::<Vec<_>>();
+    let header_deps = tx
+        .header_deps()
+        .into_iter()
+        .map(|block_hash| {
+            let block_hash: H256 = block_hash.unpack();
+            block_hash
+        })
+        .collect::<Vec<_>>();
+
+    let balance_changes = changes
+        .into_iter()
+        .map(|(address, change)| {
+            let sudt = change
+                .sudt
+                .into_iter()
+                .map(|(args, amount)| {
+                    serde_json::json!({
+                        "owner_lock_hash": args,
+                        "amount": format_signed(amount),
+                    })
+                })
+                .collect::<Vec<_>>();
+            serde_json::json!({
+                "address": address,
+                "lock": change.lock_name,
+                "capacity": format_capacity_delta(change.capacity),
+                "sudt": sudt,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+    let tx_hash: H256 = tx.hash().unpack();
+    let mut resp = serde_json::json!({
+        "tx_hash": tx_hash,
+        "size": tx_size,
+        "inputs": inputs,
+        "outputs": outputs,
+        "cell_deps": cell_deps_json,
+        "header_deps": header_deps,
+        "balance_changes": balance_changes,
+        "input_total": format!("{:#}", HumanCapacity(input_total)),
+        "output_total": format!("{:#}", HumanCapacity(output_total)),
+    });
+    if !tx.is_cellbase() {
+        let fee = input_total as i128 - output_total as i128;
+        resp["fee"] = serde_json::json!(format_capacity_delta(fee).trim_start_matches('+'));
+        if fee >= 0 {
+            let fee_rate = FeeRate::calculate(Capacity::shannons(fee as u64), tx_size);
+            resp["fee_rate"] = serde_json::json!(fee_rate.as_u64());
+        }
+    }
+    Ok(resp)
+}
+
+// The capacity and sudt amount changes of a lock script
+struct LockChange {
+    lock_name: String,
+    capacity: i128,
+    // Key: the args of the sudt type script (the owner lock hash)
+    sudt: BTreeMap<String, i128>,
+}
+
+impl LockChange {
+    fn new(lock_name: String) -> LockChange {
+        LockChange {
+            lock_name,
+            capacity: 0,
+            sudt: BTreeMap::default(),
+        }
+    }
+}
+
+// Add (or subtract) the capacity or sudt amount to the change
+fn apply_change(change: &mut i128, amount: u128, increase: bool) -> Result<(), String> {
+    let amount =
+        i128::try_from(amount).map_err(|_| format!("The amount {} is too large", amount))?;
+    let result = if increase {
+        change.checked_add(amount)
+    } else {
+        change.checked_sub(amount)
+    };
+    *change = result.ok_or_else(|| "The amount change overflows".to_string())?;
+    Ok(())
+}
+
+struct ScriptNames {
+    items: Vec<(ScriptId, String)>,
+}
+
+impl ScriptNames {
+    fn new(cell_deps: Option<&CellDeps>) -> ScriptNames {
+        let mut items = vec![
+            (ScriptId::new_type(SIGHASH_TYPE_HASH), "sighash".to_string()),
+            (
+                ScriptId::new_type(MULTISIG_TYPE_HASH),
+                "multisig".to_string(),
+            ),
+            (ScriptId::new_type(DAO_TYPE_HASH), "dao".to_string()),
+            (ScriptId::new_type(TYPE_ID_CODE_HASH), "type_id".to_string()),
+        ];
+        if let Some(cell_deps) = cell_deps {
+            for (name, item) in &cell_deps.items {
+                items.push((item.script_id.clone().into(), name.to_string()));
+            }
+        }
+        ScriptNames { items }
+    }
+
+    fn name(&self, script: &Script) -> String {
+        let script_id = ScriptId::from(script);
+        self.items
+            .iter()
+            .find(|(id, _)| id == &script_id)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    // Return the sudt/xudt type script args and the amount
+    fn sudt_amount(&self, output: &CellOutput, data: &Bytes) -> Option<(String, u128)> {
+        let type_script = output.type_().to_opt()?;
+        let name = self.name(&type_script);
+        if (name != "sudt" && name != "xudt") || data.len() < 16 {
+            return None;
+        }
+        let mut amount_bytes = [0u8; 16];
+        amount_bytes.copy_from_slice(&data[0..16]);
+        let args = format!("0x{}", hex_string(&type_script.args().raw_data()));
+        Some((args, u128::from_le_bytes(amount_bytes)))
+    }
+
+    fn cell_json(
+        &self,
+        network: NetworkType,
+        output: &CellOutput,
+        data: &Bytes,
+    ) -> serde_json::Value {
+        let capacity: u64 = output.capacity().unpack();
+        let lock = output.lock();
+        let lock_name = self.name(&lock);
+        let type_json = output.type_().to_opt().map(|type_script| {
+            let json_script = json_types::Script::from(type_script.clone());
+            serde_json::json!({
+                "name": self.name(&type_script),
+                "code_hash": json_script.code_hash,
+                "hash_type": json_script.hash_type,
+                "args": json_script.args,
+            })
+        });
+        let mut value = serde_json::json!({
+            "capacity": format!("{:#}", HumanCapacity(capacity)),
+            "lock": {
+                "name": lock_name,
+                "address": address_string(network, &lock),
+            },
+            "type": type_json,
+            "data_length": data.len(),
+        });
+        let lock_args = lock.args().raw_data();
+        if lock_name == "cheque" && lock_args.len() == 40 {
+            value["cheque"] = serde_json::json!({
+                "receiver_lock_hash": format!("0x{}", hex_string(&lock_args[0..20])),
+                "sender_lock_hash": format!("0x{}", hex_string(&lock_args[20..40])),
+            });
+        }
+        if let Some((_, amount)) = self.sudt_amount(output, data) {
+            value["sudt_amount"] = serde_json::json!(amount.to_string());
+        }
+        if let Some(deposit_number) = self.dao_deposit_number(output, data) {
+            value["dao"] = if deposit_number == 0 {
+                serde_json::json!({ "phase": "deposit" })
+            } else {
+                serde_json::json!({
+                    "phase": "withdrawing",
+                    "deposit_block_number": deposit_number,
+                })
+            };
+        }
+        value
+    }
+
+    // The DAO cell data is 0 for deposit cell, and the deposit block number
+    // for withdrawing cell
+    fn dao_deposit_number(&self, output: &CellOutput, data: &Bytes) -> Option<u64> {
+        let type_script = output.type_().to_opt()?;
+        if self.name(&type_script) != "dao" || data.len() != 8 {
+            return None;
+        }
+        let mut number_bytes = [0u8; 8];
+        number_bytes.copy_from_slice(&data[..]);
+        Some(u64::from_le_bytes(number_bytes))
+    }
+}
+
+// The cell deps of the genesis scripts and the scripts in `cell_deps`
+fn known_cell_deps(
+    genesis_info: &GenesisInfo,
+    cell_deps: Option<&CellDeps>,
+) -> Vec<(CellDep, String)> {
+    let mut items = vec![
+        (genesis_info.sighash_dep(), "sighash".to_string()),
+        (genesis_info.multisig_dep(), "multisig".to_string()),
+        (genesis_info.dao_dep(), "dao".to_string()),
+    ];
+    if let Some(cell_deps) = cell_deps {
+        for (name, item) in &cell_deps.items {
+            items.push((item.cell_dep.clone().into(), name.to_string()));
+        }
+    }
+    items
+}
+
+fn load_cell(rpc_client: &mut HttpRpcClient, out_point: &OutPoint) -> Result<ResolvedCell, String> {
+    let tx_hash: H256 = out_point.tx_hash().unpack();
+    let index: u32 = out_point.index().unpack();
+    let tx_with_status = rpc_client
+        .get_transaction(tx_hash.clone())?
+        .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?;
+    let tx: packed::Transaction = tx_with_status
+        .transaction
+        .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?
+        .inner
+        .into();
+    let (output, data) = tx
+        .into_view()
+        .output_with_data(index as usize)
+        .ok_or_else(|| format!("Input cell not found: {:#x}-{}", tx_hash, index))?;
+    Ok(ResolvedCell {
+        output,
+        data,
+        block_hash: tx_with_status.tx_status.block_hash,
+    })
+}
+
+// The maximum withdraw capacity of a withdrawing DAO cell
+fn dao_maximum_withdraw(
+    rpc_client: &mut HttpRpcClient,
+    names: &ScriptNames,
+    cell: &ResolvedCell,
+) -> Result<Option<u64>, String> {
+    let deposit_number = match names.dao_deposit_number(&cell.output, &cell.data) {
+        Some(number) if number > 0 => number,
+        _ => return Ok(None),
+    };
+    let block_hash = match cell.block_hash.clone() {
+        Some(block_hash) => block_hash,
+        None => return Ok(None),
+    };
+    let deposit_header: core::HeaderView = rpc_client
+        .get_header_by_number(deposit_number)?
+        .ok_or_else(|| format!("Deposit block not found: {}", deposit_number))?
+        .into();
+    let prepare_header: core::HeaderView = rpc_client
+        .get_header(block_hash.clone())?
+        .ok_or_else(|| format!("Block not found: {:#x}", block_hash))?
+        .into();
+    let occupied_capacity = cell
+        .output
+        .occupied_capacity(Capacity::bytes(cell.data.len()).map_err(|err| err.to_string())?)
+        .map_err(|err| err.to_string())?;
+    Ok(Some(calculate_dao_maximum_withdraw4(
+        &deposit_header,
+        &prepare_header,
+        &cell.output,
+        occupied_capacity.as_u64(),
+    )))
+}
+
+fn address_string(network: NetworkType, lock: &Script) -> String {
+    Address::new(network, AddressPayload::from(lock.clone()), true).to_string()
+}
+
+fn format_signed(value: i128) -> String {
+    if value > 0 {
+        format!("+{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_capacity_delta(delta: i128) -> String {
+    let capacity = HumanCapacity(delta.unsigned_abs() as u64);
+    if delta < 0 {
+        format!("-{:#}", capacity)
+    } else {
+        format!("+{:#}", capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::ScriptHashType;
+
+    #[test]
+    fn test_cell_json() {
+        let names = ScriptNames::new(None);
+        let lock = Script::new_builder()
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(vec![0u8; 20]).pack())
+            .build();
+        let dao_type = Script::new_builder()
+            .code_hash(DAO_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(102_00000000).pack())
+            .lock(lock)
+            .type_(Some(dao_type).pack())
+            .build();
+
+        let value = names.cell_json(NetworkType::Testnet, &output, &Bytes::from(vec![0u8; 8]));
+        assert_eq!(value["lock"]["name"], "sighash");
+        assert_eq!(value["type"]["name"], "dao");
+        assert_eq!(value["dao"]["phase"], "deposit");
+
+        let data = Bytes::from(100u64.to_le_bytes().to_vec());
+        let value = names.cell_json(NetworkType::Testnet, &output, &data);
+        assert_eq!(value["dao"]["phase"], "withdrawing");
+        assert_eq!(value["dao"]["deposit_block_number"], 100);
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_capacity_delta(-100_00000000), "-100.0 (CKB)");
+        assert_eq!(format_capacity_delta(1_000000), "+0.01 (CKB)");
+        assert_eq!(format_signed(-5), "-5");
+        assert_eq!(format_signed(5), "+5");
+        assert_eq!(format_signed(0), "0");
+    }
+
+    #[test]
+    fn test_apply_change() {
+        let mut change = 0i128;
+        apply_change(&mut change, 100, true).unwrap();
+        apply_change(&mut change, 300, false).unwrap();
+        assert_eq!(change, -200);
+        assert!(apply_change(&mut change, u128::MAX, true).is_err());
+        let mut change = i128::MAX;
+        assert!(apply_change(&mut change, 1, true).is_err());
+        let mut change = i128::MIN;
+        assert!(apply_change(&mut change, 1, false).is_err());
+        assert_eq!(change, i128::MIN);
+    }
+}
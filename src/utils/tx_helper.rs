@@ -1,8 +1,10 @@
-use ckb_hash::new_blake2b;
+use ckb_hash::{blake2b_256, new_blake2b};
 use ckb_jsonrpc_types as rpc_types;
 use ckb_types::{
     bytes::{Bytes, BytesMut},
-    core::{ScriptHashType, TransactionBuilder, TransactionView},
+    core::{
+        Capacity, EpochNumberWithFraction, ScriptHashType, TransactionBuilder, TransactionView,
+    },
     h256,
     packed::{
         self, Byte32, CellDep, CellInput, CellOutput, OutPoint, Script, Transaction, WitnessArgs,
@@ -14,7 +16,8 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 
 use ckb_sdk::constants::{MULTISIG_TYPE_HASH, SECP_SIGNATURE_SIZE, SIGHASH_TYPE_HASH};
-use ckb_sdk::{unlock::MultisigConfig, Since};
+use ckb_sdk::{unlock::MultisigConfig, Since, SinceType, SECP256K1};
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
 
 use crate::utils::genesis_info::GenesisInfo;
 
@@ -23,6 +26,7 @@ use crate::utils::genesis_info::GenesisInfo;
 /// A transaction helper handle input/output with secp256k1(sighash/multisg) lock
 ///  1. Sign transaction
 ///  2. Inspect transaction information
+///  3. Unlock other lock scripts by the unlocker (plugins)
 #[derive(Clone)]
 pub struct TxHelper {
     transaction: TransactionView,
@@ -81,11 +85,25 @@ impl TxHelper {
     pub fn clear_signatures(&mut self) {
         self.signatures.clear();
     }
+    pub fn clear_cell_deps(&mut self) {
+        self.transaction = self
+            .transaction
+            .as_advanced_builder()
+            .set_cell_deps(Vec::new())
+            .build();
+    }
+    pub fn clear_header_deps(&mut self) {
+        self.transaction = self
+            .transaction
+            .as_advanced_builder()
+            .set_header_deps(Vec::new())
+            .build();
+    }
 
     pub fn add_input<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
         &mut self,
         out_point: OutPoint,
-        since_absolute_epoch_opt: Option<u64>,
+        since_opt: Option<u64>,
         mut get_live_cell: F,
         genesis_info: &GenesisInfo,
         skip_check: bool,
@@ -93,17 +111,26 @@ impl TxHelper {
         let lock = get_live_cell(out_point.clone(), false)?.lock();
         check_lock_script(&lock, skip_check)?;
 
-        let since = if let Some(number) = since_absolute_epoch_opt {
-            Since::new_absolute_epoch(number).value()
+        let lock_arg = lock.args().raw_data();
+        let lock_since = if lock.code_hash() == MULTISIG_TYPE_HASH.pack() && lock_arg.len() == 28 {
+            let mut since_bytes = [0u8; 8];
+            since_bytes.copy_from_slice(&lock_arg[20..]);
+            Some(u64::from_le_bytes(since_bytes))
         } else {
-            let lock_arg = lock.args().raw_data();
-            if lock.code_hash() == MULTISIG_TYPE_HASH.pack() && lock_arg.len() == 28 {
-                let mut since_bytes = [0u8; 8];
-                since_bytes.copy_from_slice(&lock_arg[20..]);
-                u64::from_le_bytes(since_bytes)
-            } else {
-                0
+            None
+        };
+        let since = match (since_opt, lock_since) {
+            (Some(since), Some(lock_since)) => {
+                if !skip_check && !since_satisfies(since, lock_since) {
+                    return Err(format!(
+                        "The input since {:#x} does not satisfy the since {:#x} in multisig lock args",
+                        since, lock_since
+                    ));
+                }
+                since
             }
+            (Some(since), None) => since,
+            (None, lock_since) => lock_since.unwrap_or(0),
         };
 
         let input = CellInput::new_builder()
@@ -112,23 +139,50 @@ impl TxHelper {
             .build();
 
         self.transaction = self.transaction.as_advanced_builder().input(input).build();
-        let mut cell_deps: HashSet<CellDep> = HashSet::default();
         for ((code_hash, _), _) in self.input_group(get_live_cell, skip_check)?.into_iter() {
             let code_hash: H256 = code_hash.unpack();
+            // The cell deps of other lock scripts are added by `add_cell_dep`
             if code_hash == SIGHASH_TYPE_HASH {
-                cell_deps.insert(genesis_info.sighash_dep());
+                self.add_cell_dep(genesis_info.sighash_dep());
             } else if code_hash == MULTISIG_TYPE_HASH {
-                cell_deps.insert(genesis_info.multisig_dep());
-            } else {
-                panic!("Unexpected input code_hash: {:#x}", code_hash);
+                self.add_cell_dep(genesis_info.multisig_dep());
             }
         }
+        Ok(())
+    }
+
+    pub fn add_cell_dep(&mut self, cell_dep: CellDep) -> bool {
+        if self
+            .transaction
+            .cell_deps()
+            .into_iter()
+            .any(|dep| dep == cell_dep)
+        {
+            return false;
+        }
         self.transaction = self
             .transaction
             .as_advanced_builder()
-            .set_cell_deps(cell_deps.into_iter().collect())
+            .cell_dep(cell_dep)
             .build();
-        Ok(())
+        true
+    }
+
+    pub fn add_header_dep(&mut self, block_hash: Byte32) -> bool {
+        if self
+            .transaction
+            .header_deps()
+            .into_iter()
+            .any(|hash| hash == block_hash)
+        {
+            return false;
+        }
+        self.transaction = self
+            .transaction
+            .as_advanced_builder()
+            .header_dep(block_hash)
+            .build();
+        true
     }
 
     pub fn add_output(&mut self, output: CellOutput, data: Bytes) {
@@ -141,6 +195,20 @@ impl TxHelper {
             .build()
     }
 
+    pub fn update_output(&mut self, index: usize, output: CellOutput) -> Result<(), String> {
+        let mut outputs: Vec<CellOutput> = self.transaction.outputs().into_iter().collect();
+        let old_output = outputs
+            .get_mut(index)
+            .ok_or_else(|| format!("Output index out of bound: {}", index))?;
+        *old_output = output;
+        self.transaction = self
+            .transaction
+            .as_advanced_builder()
+            .set_outputs(outputs)
+            .build();
+        Ok(())
+    }
+
     pub fn add_signature(&mut self, lock_arg: Bytes, signature: Bytes) -> Result<bool, String> {
         if lock_arg.len() != 20 && lock_arg.len() != 28 {
             return Err(format!(
@@ -164,6 +232,22 @@ impl TxHelper {
             .or_default()
             .insert(signature))
     }
+    pub fn remove_signature(&mut self, lock_arg: &Bytes, signature: &Bytes) -> bool {
+        let removed = self
+            .signatures
+            .get_mut(lock_arg)
+            .map(|signatures| signatures.remove(signature))
+            .unwrap_or(false);
+        if self
+            .signatures
+            .get(lock_arg)
+            .map(HashSet::is_empty)
+            .unwrap_or(false)
+        {
+            self.signatures.remove(lock_arg);
+        }
+        removed
+    }
     pub fn add_multisig_config(&mut self, config: MultisigConfig) {
         self.multisig_configs.insert(config.hash160(), config);
     }
@@ -269,15 +353,70 @@ impl TxHelper {
         Ok(signatures)
     }
 
+    /// Unlock the inputs not locked by sighash/multisig lock script, the
+    /// witnesses are saved in the transaction. Return the unlocked lock scripts.
+    ///
+    /// The unlocker fills the witnesses of the inputs (by index) locked by the
+    /// lock script and returns all the witnesses of the transaction, or `None`
+    /// when the lock script can not be unlocked.
+    pub fn unlock_inputs<C, U>(
+        &mut self,
+        mut unlocker: U,
+        mut get_live_cell: C,
+    ) -> Result<Vec<Script>, String>
+    where
+        C: FnMut(OutPoint, bool) -> Result<CellOutput, String>,
+        U: FnMut(&Script, &[usize], &TransactionView) -> Result<Option<Vec<packed::Bytes>>, String>,
+    {
+        let mut lock_groups: Vec<(Script, Vec<usize>)> = Vec::new();
+        for (idx, input) in self.transaction.inputs().into_iter().enumerate() {
+            let lock = get_live_cell(input.previous_output(), false)?.lock();
+            let code_hash: H256 = lock.code_hash().unpack();
+            if code_hash == SIGHASH_TYPE_HASH || code_hash == MULTISIG_TYPE_HASH {
+                continue;
+            }
+            match lock_groups
+                .iter_mut()
+                .find(|(group_lock, _)| group_lock == &lock)
+            {
+                Some((_, idxs)) => idxs.push(idx),
+                None => lock_groups.push((lock, vec![idx])),
+            }
+        }
+
+        let mut unlocked = Vec::new();
+        for (lock, idxs) in lock_groups {
+            let tx = self
+                .transaction
+                .as_advanced_builder()
+                .set_witnesses(self.init_witnesses())
+                .build();
+            if let Some(witnesses) = unlocker(&lock, &idxs, &tx)? {
+                if witnesses.len() < tx.inputs().len() {
+                    return Err(format!(
+                        "Invalid witnesses length: {}, expected at least: {}",
+                        witnesses.len(),
+                        tx.inputs().len()
+                    ));
+                }
+                self.transaction = tx.as_advanced_builder().set_witnesses(witnesses).build();
+                unlocked.push(lock);
+            }
+        }
+        Ok(unlocked)
+    }
+
     pub fn build_tx<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
         &self,
         get_live_cell: F,
         skip_check: bool,
     ) -> Result<TransactionView, String> {
         let mut witnesses = self.init_witnesses();
-        for ((code_hash, lock_arg), idxs) in
-            self.input_group(get_live_cell, skip_check)?.into_iter()
-        {
+        // The other lock scripts are unlocked (and checked) by `unlock_inputs`
+        for ((code_hash, lock_arg), idxs) in self.input_group(get_live_cell, true)?.into_iter() {
+            if code_hash != SIGHASH_TYPE_HASH.pack() && code_hash != MULTISIG_TYPE_HASH.pack() {
+                continue;
+            }
             if skip_check && !self.signatures.contains_key(&lock_arg) {
                 continue;
             }
@@ -342,13 +481,63 @@ impl TxHelper {
             .build())
     }
 
+    /// Build the transaction with placeholder witnesses of sighash/multisig
+    /// inputs for estimating the transaction size. The witnesses of other lock
+    /// scripts are kept, return the first input index of the lock groups whose
+    /// witness is empty (the size can not be estimated).
+    pub fn build_placeholder_tx<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
+        &self,
+        get_live_cell: F,
+    ) -> Result<(TransactionView, Vec<usize>), String> {
+        let mut witnesses = self.init_witnesses();
+        let mut unknown_idxs = Vec::new();
+        for ((code_hash, lock_arg), idxs) in self.input_group(get_live_cell, true)?.into_iter() {
+            let lock_size = if code_hash == SIGHASH_TYPE_HASH.pack() {
+                SECP_SIGNATURE_SIZE
+            } else if code_hash == MULTISIG_TYPE_HASH.pack() {
+                // The multisig config is checked by `input_group`
+                let hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
+                let multisig_config = self.multisig_configs.get(&hash160).unwrap();
+                multisig_config.to_witness_data().len()
+                    + multisig_config.threshold() as usize * SECP_SIGNATURE_SIZE
+            } else {
+                if witnesses[idxs[0]].raw_data().is_empty() {
+                    unknown_idxs.push(idxs[0]);
+                }
+                continue;
+            };
+            let init_witness = if witnesses[idxs[0]].raw_data().is_empty() {
+                WitnessArgs::default()
+            } else {
+                WitnessArgs::from_slice(witnesses[idxs[0]].raw_data().as_ref())
+                    .map_err(|err| err.to_string())?
+            };
+            witnesses[idxs[0]] = init_witness
+                .as_builder()
+                .lock(Some(Bytes::from(vec![0u8; lock_size])).pack())
+                .build()
+                .as_bytes()
+                .pack();
+        }
+        unknown_idxs.sort_unstable();
+        let tx = self
+            .transaction
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build();
+        Ok((tx, unknown_idxs))
+    }
+
+    /// Check the inputs and outputs, return the total capacity of inputs and
+    /// outputs. The input total is `None` when there are inputs not locked by
+    /// sighash/multisig lock script, the capacity check should be skipped.
     pub fn check_tx<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
         &self,
         mut get_live_cell: F,
-    ) -> Result<(u64, u64), String> {
+    ) -> Result<(Option<u64>, u64), String> {
         // Check inputs
         let mut previous_outputs: HashSet<OutPoint> = HashSet::default();
-        let mut input_total: u64 = 0;
+        let mut input_total: Option<u64> = Some(0);
         for (i, input) in self.transaction.inputs().into_iter().enumerate() {
             let out_point = input.previous_output();
             if previous_outputs.contains(&out_point) {
@@ -357,25 +546,221 @@ impl TxHelper {
                 previous_outputs.insert(out_point.clone());
             }
             let output = get_live_cell(out_point, false)?;
-            let capacity: u64 = output.capacity().unpack();
-            input_total += capacity;
-
-            check_lock_script(&output.lock(), false)
-                .map_err(|err| format!("Input(no.{}) {}", i + 1, err))?;
+            let lock = output.lock();
+            let code_hash: H256 = lock.code_hash().unpack();
+            if code_hash == SIGHASH_TYPE_HASH || code_hash == MULTISIG_TYPE_HASH {
+                check_lock_script(&lock, false)
+                    .map_err(|err| format!("Input(no.{}) {}", i + 1, err))?;
+                let capacity: u64 = output.capacity().unpack();
+                input_total = input_total.map(|total| total + capacity);
+            } else {
+                input_total = None;
+            }
         }
 
-        // Check output
+        // Check output, any lock script is allowed
         let mut output_total: u64 = 0;
-        for (i, output) in self.transaction.outputs().into_iter().enumerate() {
+        for (i, (output, data)) in self.transaction.outputs_with_data_iter().enumerate() {
             let capacity: u64 = output.capacity().unpack();
             output_total += capacity;
 
-            check_lock_script(&output.lock(), false)
+            check_lock_script(&output.lock(), true)
                 .map_err(|err| format!("Output(no.{}) {}", i + 1, err))?;
+            let occupied_capacity = output
+                .occupied_capacity(Capacity::bytes(data.len()).map_err(|err| err.to_string())?)
+                .map_err(|err| err.to_string())?
+                .as_u64();
+            if capacity < occupied_capacity {
+                return Err(format!(
+                    "Output(no.{}) capacity {} is less than the occupied capacity {} (unit: shannon)",
+                    i + 1,
+                    capacity,
+                    occupied_capacity,
+                ));
+            }
         }
 
         Ok((input_total, output_total))
     }
+
+    /// Collect the signing requirement and status of each lock script group,
+    /// the groups are in the order of their first input.
+    pub fn signing_status<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
+        &self,
+        mut get_live_cell: F,
+    ) -> Result<Vec<LockGroupStatus>, String> {
+        let mut groups: Vec<(Script, Vec<usize>)> = Vec::new();
+        for (idx, input) in self.transaction.inputs().into_iter().enumerate() {
+            let lock = get_live_cell(input.previous_output(), false)?.lock();
+            if let Some((_, idxs)) = groups
+                .iter_mut()
+                .find(|(group_lock, _)| group_lock == &lock)
+            {
+                idxs.push(idx);
+            } else {
+                groups.push((lock, vec![idx]));
+            }
+        }
+
+        let witnesses = self.init_witnesses();
+        let input_size = self.transaction.inputs().len();
+        let mut status = Vec::with_capacity(groups.len());
+        for (lock, input_idxs) in groups {
+            let lock_arg = lock.args().raw_data();
+            let code_hash: H256 = lock.code_hash().unpack();
+            let multisig_config = if code_hash == MULTISIG_TYPE_HASH && lock_arg.len() >= 20 {
+                let hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
+                self.multisig_configs.get(&hash160).cloned()
+            } else {
+                None
+            };
+            // The signing message is only known for sighash lock and multisig
+            // lock with config
+            let mut message = None;
+            if code_hash == SIGHASH_TYPE_HASH || multisig_config.is_some() {
+                build_signature(
+                    &self.transaction,
+                    input_size,
+                    &input_idxs,
+                    &witnesses,
+                    multisig_config.as_ref(),
+                    |msg: &H256, _tx: &rpc_types::Transaction| {
+                        message = Some(msg.clone());
+                        Ok([0u8; SECP_SIGNATURE_SIZE])
+                    },
+                )?;
+            }
+            let signatures = self
+                .signatures
+                .get(&lock_arg)
+                .map(|signatures| {
+                    signatures
+                        .iter()
+                        .map(|signature| {
+                            let signer = message
+                                .as_ref()
+                                .and_then(|message| recover_signer(message, signature));
+                            (signature.clone(), signer)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let since: Vec<u64> = input_idxs
+                .iter()
+                .map(|idx| {
+                    self.transaction
+                        .inputs()
+                        .get(*idx)
+                        .unwrap()
+                        .since()
+                        .unpack()
+                })
+                .collect();
+            status.push(LockGroupStatus {
+                lock,
+                input_idxs,
+                since,
+                multisig_config,
+                signatures,
+            });
+        }
+        Ok(status)
+    }
+}
+
+/// The signing requirement and status of the inputs with the same lock script
+#[derive(Clone, Debug)]
+pub struct LockGroupStatus {
+    pub lock: Script,
+    pub input_idxs: Vec<usize>,
+    /// The since value of each input
+    pub since: Vec<u64>,
+    /// `None` for sighash lock or the multisig config is not added yet
+    pub multisig_config: Option<MultisigConfig>,
+    /// The added signatures and the signer recovered from the signature
+    pub signatures: Vec<(Bytes, Option<H160>)>,
+}
+
+impl LockGroupStatus {
+    pub fn is_sighash(&self) -> bool {
+        self.lock.code_hash() == SIGHASH_TYPE_HASH.pack()
+    }
+    pub fn is_multisig(&self) -> bool {
+        self.lock.code_hash() == MULTISIG_TYPE_HASH.pack()
+    }
+
+    /// All the pubkey hashes can sign this group
+    pub fn signers(&self) -> Vec<H160> {
+        if let Some(cfg) = self.multisig_config.as_ref() {
+            cfg.sighash_addresses().clone()
+        } else if self.is_sighash() {
+            vec![H160::from_slice(&self.lock.args().raw_data()).unwrap()]
+        } else {
+            Vec::new()
+        }
+    }
+    /// The pubkey hashes must sign this group (`require_first_n` of multisig)
+    pub fn required_signers(&self) -> Vec<H160> {
+        if let Some(cfg) = self.multisig_config.as_ref() {
+            cfg.sighash_addresses()[..cfg.require_first_n() as usize].to_vec()
+        } else {
+            self.signers()
+        }
+    }
+    pub fn threshold(&self) -> usize {
+        if let Some(cfg) = self.multisig_config.as_ref() {
+            cfg.threshold() as usize
+        } else if self.is_sighash() {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// The signers already signed this group
+    pub fn signed_by(&self) -> Vec<H160> {
+        let signers = self.signers();
+        let mut signed_by: Vec<H160> = Vec::new();
+        for signer in self
+            .signatures
+            .iter()
+            .filter_map(|(_, signer)| signer.as_ref())
+        {
+            if signers.contains(signer) && !signed_by.contains(signer) {
+                signed_by.push(signer.clone());
+            }
+        }
+        signed_by
+    }
+    /// The signatures not signed by any signer of this group
+    pub fn invalid_signatures(&self) -> Vec<Bytes> {
+        let signers = self.signers();
+        self.signatures
+            .iter()
+            .filter(|(_, signer)| {
+                signer
+                    .as_ref()
+                    .map(|signer| !signers.contains(signer))
+                    .unwrap_or(true)
+            })
+            .map(|(signature, _)| signature.clone())
+            .collect()
+    }
+    pub fn missing_signatures(&self) -> usize {
+        self.threshold().saturating_sub(self.signed_by().len())
+    }
+
+    /// All the required signatures are added and no redundant signature
+    pub fn is_ready(&self) -> bool {
+        let signed_by = self.signed_by();
+        (self.is_sighash() || self.multisig_config.is_some())
+            && self.signatures.len() == self.threshold()
+            && signed_by.len() == self.threshold()
+            && self
+                .required_signers()
+                .iter()
+                .all(|signer| signed_by.contains(signer))
+    }
 }
 
 pub type SignerFn = Box<
@@ -432,6 +817,37 @@ pub fn check_lock_script(lock: &Script, skip_check: bool) -> Result<(), String>
     }
 }
 
+fn recover_signer(message: &H256, signature: &[u8]) -> Option<H160> {
+    let recov_id = RecoveryId::from_i32(i32::from(signature[64])).ok()?;
+    let signature = RecoverableSignature::from_compact(&signature[0..64], recov_id).ok()?;
+    let message = secp256k1::Message::from_digest_slice(message.as_bytes()).ok()?;
+    let pubkey = SECP256K1.recover_ecdsa(&message, &signature).ok()?;
+    H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).ok()
+}
+
+/// Check the input since satisfies the since in the multisig lock args, the
+/// flags must be the same and the value must not be smaller.
+fn since_satisfies(since: u64, lock_since: u64) -> bool {
+    // The highest 8 bits are flags
+    if since >> 56 != lock_since >> 56 {
+        return false;
+    }
+    match (
+        Since::from_raw_value(since).extract_metric(),
+        Since::from_raw_value(lock_since).extract_metric(),
+    ) {
+        (
+            Some((SinceType::EpochNumberWithFraction, value)),
+            Some((SinceType::EpochNumberWithFraction, lock_value)),
+        ) => {
+            EpochNumberWithFraction::from_full_value(value).to_rational()
+                >= EpochNumberWithFraction::from_full_value(lock_value).to_rational()
+        }
+        (Some((_, value)), Some((_, lock_value))) => value >= lock_value,
+        _ => false,
+    }
+}
+
 pub fn build_signature<
     S: FnMut(&H256, &rpc_types::Transaction) -> Result<[u8; SECP_SIGNATURE_SIZE], String>,
 >(
@@ -496,6 +912,7 @@ pub fn build_signature<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{arg_parser::PrivkeyWrapper, other::get_privkey_signer};
     use ckb_types::{h160, h256};
 
     #[test]
@@ -585,4 +1002,243 @@ mod tests {
             assert_eq!(check_lock_script(script, *skip_check).is_ok(), *is_ok);
         }
     }
+
+    #[test]
+    fn test_signing_status() {
+        let privkeys = (1u8..=3)
+            .map(|n| secp256k1::SecretKey::from_slice(&[n; 32]).unwrap())
+            .collect::<Vec<_>>();
+        let hash160s = privkeys
+            .iter()
+            .map(|privkey| {
+                let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, privkey);
+                H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let cfg = MultisigConfig::new_with(hash160s.clone(), 1, 2).unwrap();
+        let multisig_lock = Script::new_builder()
+            .code_hash(MULTISIG_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(cfg.hash160().as_bytes().to_vec()).pack())
+            .build();
+        let sighash_lock = Script::new_builder()
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(hash160s[0].as_bytes().to_vec()).pack())
+            .build();
+        let cells: HashMap<OutPoint, CellOutput> = vec![
+            (OutPoint::new(h256!("0x1").pack(), 0), multisig_lock),
+            (OutPoint::new(h256!("0x1").pack(), 1), sighash_lock.clone()),
+            (OutPoint::new(h256!("0x2").pack(), 0), sighash_lock),
+        ]
+        .into_iter()
+        .map(|(out_point, lock)| (out_point, CellOutput::new_builder().lock(lock).build()))
+        .collect();
+        let get_live_cell = |out_point: OutPoint, _with_data: bool| -> Result<CellOutput, String> {
+            Ok(cells.get(&out_point).unwrap().clone())
+        };
+        let tx = TransactionBuilder::default()
+            .inputs(vec![
+                CellInput::new(OutPoint::new(h256!("0x1").pack(), 0), 0),
+                CellInput::new(OutPoint::new(h256!("0x1").pack(), 1), 0),
+                CellInput::new(OutPoint::new(h256!("0x2").pack(), 0), 0),
+            ])
+            .output(CellOutput::default())
+            .output_data(Bytes::new().pack())
+            .build();
+        let mut helper = TxHelper::new(tx);
+        helper.add_multisig_config(cfg);
+        let sign_by = |helper: &mut TxHelper, privkey: &secp256k1::SecretKey| {
+            let mut signer = get_privkey_signer(PrivkeyWrapper(*privkey));
+            let signatures = helper
+                .sign_inputs(&mut signer, get_live_cell, false)
+                .unwrap();
+            for (lock_arg, signature) in signatures {
+                helper.add_signature(lock_arg, signature).unwrap();
+            }
+        };
+
+        let status = helper.signing_status(get_live_cell).unwrap();
+        assert_eq!(status.len(), 2);
+        assert!(status[0].is_multisig());
+        assert_eq!(status[0].input_idxs, vec![0]);
+        assert_eq!(status[0].required_signers(), vec![hash160s[0].clone()]);
+        assert_eq!(status[0].missing_signatures(), 2);
+        assert!(status[1].is_sighash());
+        assert_eq!(status[1].input_idxs, vec![1, 2]);
+        assert_eq!(status[1].missing_signatures(), 1);
+
+        // The first signer is required by `require_first_n`
+        sign_by(&mut helper, &privkeys[1]);
+        let status = helper.signing_status(get_live_cell).unwrap();
+        assert_eq!(status[0].signed_by(), vec![hash160s[1].clone()]);
+        assert_eq!(status[0].missing_signatures(), 1);
+        assert!(!status[0].is_ready());
+        assert_eq!(status[1].missing_signatures(), 1);
+
+        sign_by(&mut helper, &privkeys[0]);
+        let status = helper.signing_status(get_live_cell).unwrap();
+        assert!(status.iter().all(LockGroupStatus::is_ready));
+        assert!(helper.build_tx(get_live_cell, false).is_ok());
+
+        // A signature of another message is invalid
+        helper
+            .add_signature(
+                Bytes::from(hash160s[0].as_bytes().to_vec()),
+                Bytes::from(vec![1u8; SECP_SIGNATURE_SIZE]),
+            )
+            .unwrap();
+        let status = helper.signing_status(get_live_cell).unwrap();
+        assert_eq!(status[1].invalid_signatures().len(), 1);
+        assert!(!status[1].is_ready());
+    }
+
+    #[test]
+    fn test_unlock_inputs() {
+        let other_lock = Script::new_builder()
+            .code_hash(h256!("0xdeadbeef").pack())
+            .hash_type(ScriptHashType::Data1.into())
+            .args(Bytes::from(vec![0x33; 20]).pack())
+            .build();
+        let sighash_lock = Script::new_builder()
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(vec![0x44; 20]).pack())
+            .build();
+        let cells: HashMap<OutPoint, CellOutput> = vec![
+            (OutPoint::new(h256!("0x1").pack(), 0), other_lock.clone()),
+            (OutPoint::new(h256!("0x1").pack(), 1), sighash_lock),
+            (OutPoint::new(h256!("0x2").pack(), 0), other_lock.clone()),
+        ]
+        .into_iter()
+        .map(|(out_point, lock)| (out_point, CellOutput::new_builder().lock(lock).build()))
+        .collect();
+        let get_live_cell = |out_point: OutPoint, _with_data: bool| -> Result<CellOutput, String> {
+            Ok(cells.get(&out_point).unwrap().clone())
+        };
+        let tx = TransactionBuilder::default()
+            .inputs(vec![
+                CellInput::new(OutPoint::new(h256!("0x1").pack(), 0), 0),
+                CellInput::new(OutPoint::new(h256!("0x1").pack(), 1), 0),
+                CellInput::new(OutPoint::new(h256!("0x2").pack(), 0), 0),
+            ])
+            .build();
+        let mut helper = TxHelper::new(tx);
+
+        // No unlocker for the lock script
+        let unlocked = helper
+            .unlock_inputs(|_, _, _| Ok(None), get_live_cell)
+            .unwrap();
+        assert!(unlocked.is_empty());
+        assert_eq!(helper.transaction().witnesses().len(), 0);
+
+        let mut calls = Vec::new();
+        let unlocker = |lock: &Script, idxs: &[usize], tx: &TransactionView| {
+            calls.push((lock.clone(), idxs.to_vec()));
+            let mut witnesses: Vec<packed::Bytes> = tx.witnesses().into_iter().collect();
+            for idx in idxs {
+                witnesses[*idx] = Bytes::from(vec![*idx as u8 + 1]).pack();
+            }
+            Ok(Some(witnesses))
+        };
+        let unlocked = helper.unlock_inputs(unlocker, get_live_cell).unwrap();
+        assert_eq!(unlocked, vec![other_lock.clone()]);
+        assert_eq!(calls, vec![(other_lock, vec![0, 2])]);
+        let witnesses = helper
+            .transaction()
+            .witnesses()
+            .into_iter()
+            .map(|witness| witness.raw_data())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            witnesses,
+            vec![Bytes::from(vec![1]), Bytes::new(), Bytes::from(vec![3])]
+        );
+    }
+
+    #[test]
+    fn test_build_placeholder_tx() {
+        let cfg =
+            MultisigConfig::new_with(vec![h160!("0x1"), h160!("0x2"), h160!("0x3")], 0, 2).unwrap();
+        let multisig_lock = Script::new_builder()
+            .code_hash(MULTISIG_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(cfg.hash160().as_bytes().to_vec()).pack())
+            .build();
+        let sighash_lock = Script::new_builder()
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(vec![0x44; 20]).pack())
+            .build();
+        let other_lock = Script::new_builder()
+            .code_hash(h256!("0xdeadbeef").pack())
+            .hash_type(ScriptHashType::Data1.into())
+            .build();
+        let cells: HashMap<OutPoint, CellOutput> = vec![
+            (OutPoint::new(h256!("0x1").pack(), 0), sighash_lock),
+            (OutPoint::new(h256!("0x1").pack(), 1), multisig_lock),
+            (OutPoint::new(h256!("0x1").pack(), 2), other_lock),
+        ]
+        .into_iter()
+        .map(|(out_point, lock)| (out_point, CellOutput::new_builder().lock(lock).build()))
+        .collect();
+        let get_live_cell = |out_point: OutPoint, _with_data: bool| -> Result<CellOutput, String> {
+            Ok(cells.get(&out_point).unwrap().clone())
+        };
+        let tx = TransactionBuilder::default()
+            .inputs(
+                (0..3).map(|index| CellInput::new(OutPoint::new(h256!("0x1").pack(), index), 0)),
+            )
+            .build();
+        let mut helper = TxHelper::new(tx);
+        helper.add_multisig_config(cfg.clone());
+
+        let (tx, unknown_idxs) = helper.build_placeholder_tx(get_live_cell).unwrap();
+        assert_eq!(unknown_idxs, vec![2]);
+        let lock_size = |index: usize| {
+            WitnessArgs::from_slice(&tx.witnesses().get(index).unwrap().raw_data())
+                .unwrap()
+                .lock()
+                .to_opt()
+                .map(|lock| lock.raw_data().len())
+        };
+        assert_eq!(lock_size(0), Some(SECP_SIGNATURE_SIZE));
+        assert_eq!(
+            lock_size(1),
+            Some(cfg.to_witness_data().len() + 2 * SECP_SIGNATURE_SIZE)
+        );
+        assert!(tx.witnesses().get(2).unwrap().raw_data().is_empty());
+    }
+
+    #[test]
+    fn test_since_satisfies() {
+        let epoch_since = |number, index, length| {
+            let epoch = EpochNumberWithFraction::new(number, index, length);
+            Since::new(
+                SinceType::EpochNumberWithFraction,
+                epoch.full_value(),
+                false,
+            )
+            .value()
+        };
+        assert!(since_satisfies(
+            epoch_since(10, 0, 1),
+            epoch_since(10, 0, 1)
+        ));
+        assert!(since_satisfies(
+            epoch_since(10, 1, 2),
+            epoch_since(10, 300, 1800)
+        ));
+        assert!(!since_satisfies(
+            epoch_since(10, 1, 10),
+            epoch_since(10, 300, 1800)
+        ));
+        assert!(since_satisfies(101, 100));
+        assert!(!since_satisfies(99, 100));
+        // Different flags (relative block number)
+        let relative_block = Since::new(SinceType::BlockNumber, 200, true).value();
+        assert!(!since_satisfies(relative_block, 100));
+        // Different metric (epoch)
+        assert!(!since_satisfies(epoch_since(10, 0, 1), 5));
+    }
 }
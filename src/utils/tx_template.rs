@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use ckb_sdk::{Address, AddressPayload, HumanCapacity, NetworkType};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType},
+    packed::{CellDep, CellOutput, Script},
+    prelude::*,
+    H160,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::arg_parser::{
+    AddressParser, ArgParser, CapacityParser, FixedHashParser, HexParser, OutPointParser,
+    ScriptParser,
+};
+use super::config::{replace_vars, ENV_PATTERN};
+
+pub const DEFAULT_TEMPLATE_FEE_RATE: u64 = 1000;
+
+/// A parameterized transaction (format: toml), the `${name}` variables are
+/// replaced by the given values before parsing. Example:
+///
+/// ```toml
+/// # Collect inputs from (and send change back to) this address or account
+/// from = "${from}"
+/// fee_rate = 1000
+/// header_deps = []
+///
+/// [[cell_deps]]
+/// out_point = "0xc7813f6a415144643970c2e88e0bb6ca6a8edc5dd7c1022746f628284a9936d5-0"
+/// dep_type = "code"
+///
+/// [[outputs]]
+/// address = "${to}"
+/// capacity = "${amount}"
+/// # type = "<code_hash>-<hash_type>-<args>"
+/// # data = "0x"
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TxTemplate {
+    /// The address (or account lock arg) to collect inputs from, the change
+    /// output is also sent to it
+    pub from: String,
+    #[serde(default = "default_fee_rate")]
+    pub fee_rate: u64,
+    #[serde(default)]
+    pub cell_deps: Vec<TemplateCellDep>,
+    #[serde(default)]
+    pub header_deps: Vec<String>,
+    pub outputs: Vec<TemplateOutput>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateCellDep {
+    /// Format: {tx_hash}-{index}
+    pub out_point: String,
+    /// "code" or "dep_group"
+    #[serde(default = "default_dep_type")]
+    pub dep_type: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateOutput {
+    /// The address of the lock script (conflicts with `lock`)
+    pub address: Option<String>,
+    /// The lock script (format: `{code_hash}-{hash_type}-{args}` or json)
+    pub lock: Option<String>,
+    /// The capacity in CKB (example: "100.5")
+    pub capacity: String,
+    #[serde(rename = "type")]
+    pub type_script: Option<String>,
+    /// The output data in hex
+    pub data: Option<String>,
+}
+
+fn default_fee_rate() -> u64 {
+    DEFAULT_TEMPLATE_FEE_RATE
+}
+
+fn default_dep_type() -> String {
+    "code".to_string()
+}
+
+impl TxTemplate {
+    /// Replace the variables in the template content and parse it, all the
+    /// variables must be given.
+    pub fn render(content: &str, vars: &HashMap<String, String>) -> Result<TxTemplate, String> {
+        let regex = Regex::new(ENV_PATTERN).expect("valid env pattern");
+        let mut missing = Vec::new();
+        let content = replace_vars(&regex, content, |key| {
+            let value = vars.get(key).cloned();
+            if value.is_none() && !missing.iter().any(|name| name == key) {
+                missing.push(key.to_string());
+            }
+            value
+        });
+        if !missing.is_empty() {
+            return Err(format!(
+                "Missing template variables: {}",
+                missing.join(", ")
+            ));
+        }
+        toml::from_str(&content).map_err(|err| format!("Invalid transaction template: {}", err))
+    }
+
+    pub fn sender_lock(&self, network: NetworkType) -> Result<Script, String> {
+        if let Ok(lock_arg) = FixedHashParser::<H160>::default().parse(&self.from) {
+            Ok(Script::from(&AddressPayload::from_pubkey_hash(lock_arg)))
+        } else {
+            let address: Address = AddressParser::default()
+                .set_network(network)
+                .parse(&self.from)?;
+            Ok(Script::from(address.payload()))
+        }
+    }
+}
+
+impl TemplateCellDep {
+    pub fn to_cell_dep(&self) -> Result<CellDep, String> {
+        let dep_type = match self.dep_type.as_str() {
+            "code" => DepType::Code,
+            "dep_group" => DepType::DepGroup,
+            _ => return Err(format!("Invalid dep_type: {}", self.dep_type)),
+        };
+        Ok(CellDep::new_builder()
+            .out_point(OutPointParser.parse(&self.out_point)?)
+            .dep_type(dep_type.into())
+            .build())
+    }
+}
+
+impl TemplateOutput {
+    pub fn to_output(&self, network: NetworkType) -> Result<(CellOutput, Bytes), String> {
+        let lock = match (self.address.as_ref(), self.lock.as_ref()) {
+            (Some(address), None) => {
+                let address: Address = AddressParser::default()
+                    .set_network(network)
+                    .parse(address)?;
+                Script::from(address.payload())
+            }
+            (None, Some(lock)) => ScriptParser.parse(lock)?,
+            _ => return Err("Output requires one of address and lock".to_string()),
+        };
+        let type_script = self
+            .type_script
+            .as_ref()
+            .map(|type_script| ScriptParser.parse(type_script))
+            .transpose()?;
+        let data = match self.data.as_ref() {
+            Some(data) => Bytes::from(HexParser.parse(data)?),
+            None => Bytes::new(),
+        };
+        let capacity: u64 = CapacityParser.parse(&self.capacity)?.into();
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(capacity).pack())
+            .lock(lock)
+            .type_(type_script.pack())
+            .build();
+        let occupied_capacity = output
+            .occupied_capacity(Capacity::bytes(data.len()).map_err(|err| err.to_string())?)
+            .map_err(|err| err.to_string())?
+            .as_u64();
+        if capacity < occupied_capacity {
+            return Err(format!(
+                "Capacity {:#} is less than the occupied capacity {:#}",
+                HumanCapacity(capacity),
+                HumanCapacity(occupied_capacity),
+            ));
+        }
+        Ok((output, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"
+from = "${from}"
+
+[[cell_deps]]
+out_point = "${dep_tx}-${dep_index}"
+
+[[outputs]]
+address = "${to}"
+capacity = "${ amount }"
+data = "0x1234"
+"#;
+
+    #[test]
+    fn test_render() {
+        let mut vars: HashMap<String, String> = vec![
+            ("from", "0x0000000000000000000000000000000000000001"),
+            ("to", "ckt1qyqp76jus2sst4qy57nnphuqgsmlmzkv2l7s8ksggy"),
+            ("amount", "100.5"),
+            (
+                "dep_tx",
+                "0xc7813f6a415144643970c2e88e0bb6ca6a8edc5dd7c1022746f628284a9936d5",
+            ),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(
+            TxTemplate::render(TEMPLATE, &vars),
+            Err("Missing template variables: dep_index".to_string())
+        );
+
+        vars.insert("dep_index".to_string(), "0".to_string());
+        let template = TxTemplate::render(TEMPLATE, &vars).unwrap();
+        assert_eq!(template.fee_rate, DEFAULT_TEMPLATE_FEE_RATE);
+        assert_eq!(
+            template.cell_deps[0].out_point,
+            "0xc7813f6a415144643970c2e88e0bb6ca6a8edc5dd7c1022746f628284a9936d5-0"
+        );
+        assert_eq!(template.cell_deps[0].dep_type, "code");
+        assert!(template.cell_deps[0].to_cell_dep().is_ok());
+        assert!(template.sender_lock(NetworkType::Testnet).is_ok());
+
+        let (output, data) = template.outputs[0].to_output(NetworkType::Testnet).unwrap();
+        let capacity: u64 = output.capacity().unpack();
+        assert_eq!(capacity, 100_5000_0000);
+        assert_eq!(data, Bytes::from(vec![0x12, 0x34]));
+
+        let mut output = template.outputs[0].clone();
+        output.capacity = "10".to_string();
+        assert!(output.to_output(NetworkType::Testnet).is_err());
+    }
+}
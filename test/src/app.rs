@@ -5,6 +5,10 @@ pub struct App {
     ckb_bin: String,
     cli_bin: String,
     keystore_plugin_bin: String,
+    mock_signer_bin: String,
+    mock_unlocker_bin: String,
+    xudt_bin: String,
+    xudt_extension_bin: String,
 }
 
 impl App {
@@ -16,6 +20,16 @@ impl App {
             .get_one::<String>("keystore-plugin")
             .unwrap()
             .to_owned();
+        let mock_signer_bin: String = matches.get_one::<String>("mock-signer").unwrap().to_owned();
+        let mock_unlocker_bin: String = matches
+            .get_one::<String>("mock-unlocker")
+            .unwrap()
+            .to_owned();
+        let xudt_bin: String = matches.get_one::<String>("xudt-bin").unwrap().to_owned();
+        let xudt_extension_bin: String = matches
+            .get_one::<String>("xudt-extension-bin")
+            .unwrap()
+            .to_owned();
         assert!(
             Path::new(&ckb_bin).exists(),
             "ckb-bin binary not exists: {}",
@@ -31,10 +45,34 @@ impl App {
             "keystore plugin binary not exists: {}",
             keystore_plugin_bin,
         );
+        assert!(
+            Path::new(&mock_signer_bin).exists(),
+            "mock signer binary not exists: {}",
+            mock_signer_bin,
+        );
+        assert!(
+            Path::new(&mock_unlocker_bin).exists(),
+            "mock unlocker plugin binary not exists: {}",
+            mock_unlocker_bin,
+        );
+        assert!(
+            Path::new(&xudt_bin).exists(),
+            "xudt script binary not exists: {}",
+            xudt_bin,
+        );
+        assert!(
+            Path::new(&xudt_extension_bin).exists(),
+            "xudt extension script binary not exists: {}",
+            xudt_extension_bin,
+        );
         Self {
             ckb_bin,
             cli_bin,
             keystore_plugin_bin,
+            mock_signer_bin,
+            mock_unlocker_bin,
+            xudt_bin,
+            xudt_extension_bin,
         }
     }
 
@@ -50,6 +88,22 @@ impl App {
         &self.keystore_plugin_bin
     }
 
+    pub fn mock_signer_bin(&self) -> &str {
+        &self.mock_signer_bin
+    }
+
+    pub fn mock_unlocker_bin(&self) -> &str {
+        &self.mock_unlocker_bin
+    }
+
+    pub fn xudt_bin(&self) -> &str {
+        &self.xudt_bin
+    }
+
+    pub fn xudt_extension_bin(&self) -> &str {
+        &self.xudt_extension_bin
+    }
+
     fn matches() -> clap::ArgMatches {
         clap::Command::new("ckb-cli-test")
             .arg(
@@ -73,6 +127,34 @@ impl App {
                     .value_name("PATH")
                     .help("Path to keystore plugin executable"),
             )
+            .arg(
+                clap::Arg::new("mock-signer")
+                    .long("mock-signer")
+                    .required(true)
+                    .value_name("PATH")
+                    .help("Path to mock external signer executable"),
+            )
+            .arg(
+                clap::Arg::new("mock-unlocker")
+                    .long("mock-unlocker")
+                    .required(true)
+                    .value_name("PATH")
+                    .help("Path to mock unlocker plugin executable"),
+            )
+            .arg(
+                clap::Arg::new("xudt-bin")
+                    .long("xudt-bin")
+                    .required(true)
+                    .value_name("PATH")
+                    .help("Path to xUDT script binary"),
+            )
+            .arg(
+                clap::Arg::new("xudt-extension-bin")
+                    .long("xudt-extension-bin")
+                    .required(true)
+                    .value_name("PATH")
+                    .help("Path to xUDT extension script binary"),
+            )
             .get_matches()
     }
 }
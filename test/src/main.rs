@@ -8,10 +8,14 @@ use crate::app::App;
 use crate::setup::Setup;
 use crate::spec::{
     AccountKeystoreExportPerm, AccountKeystorePerm, AccountKeystoreUpdatePassword,
-    DaoPrepareMultiple, DaoPrepareOne, DaoWithdrawMultiple, Plugin, RpcGetTipBlockNumber, Spec,
-    SudtIssueToAcp, SudtIssueToCheque, SudtTransferToChequeForClaim,
-    SudtTransferToChequeForWithdraw, SudtTransferToMultiAcp, Util, WalletTimelockedAddress,
-    WalletTransfer,
+    AccountWatchOnly, DaoPrepareMultiple, DaoPrepareOne, DaoWithdrawMultiple, Plugin,
+    RpcGetTipBlockNumber, Spec, SudtAirdrop, SudtBalances, SudtHolders, SudtIssueToAcp,
+    SudtIssueToCheque, SudtTokenRegistry, SudtTransferToChequeForClaim,
+    SudtTransferToChequeForWithdraw, SudtTransferToMultiAcp, TxAddOutputScripts, TxBalance,
+    TxCellDeps, TxExplain, TxMultisigCombine, TxMultisigStatus, TxSince, TxTemplate,
+    TxUnlockerPlugin, Util, WalletBatchTransfer, WalletBuildOnly, WalletBumpFee, WalletConsolidate,
+    WalletDryRun, WalletExternalSigner, WalletHistory, WalletLockUntil, WalletTimelockedAddress,
+    WalletTransfer, XudtArgs, XudtIssueTransfer,
 };
 use crate::util::{find_available_port, run_cmd, temp_dir};
 use std::env;
@@ -60,6 +64,10 @@ fn run_spec(spec: Box<dyn Spec>, app: &App) {
         app.ckb_bin().to_string(),
         app.cli_bin().to_string(),
         app.keystore_plugin_bin().to_string(),
+        app.mock_signer_bin().to_string(),
+        app.mock_unlocker_bin().to_string(),
+        app.xudt_bin().to_string(),
+        app.xudt_extension_bin().to_string(),
         ckb_dir,
         rpc_port,
         tempdir,
@@ -74,13 +82,37 @@ fn all_specs() -> Vec<Box<dyn Spec>> {
         Box::new(AccountKeystorePerm),
         Box::new(AccountKeystoreExportPerm),
         Box::new(AccountKeystoreUpdatePassword),
+        Box::new(AccountWatchOnly),
         Box::new(SudtIssueToCheque),
         Box::new(SudtIssueToAcp),
         Box::new(SudtTransferToMultiAcp),
         Box::new(SudtTransferToChequeForClaim),
         Box::new(SudtTransferToChequeForWithdraw),
+        Box::new(XudtArgs),
+        Box::new(XudtIssueTransfer),
+        Box::new(SudtTokenRegistry),
+        Box::new(SudtBalances),
+        Box::new(SudtHolders),
+        Box::new(SudtAirdrop),
         Box::new(WalletTransfer),
         Box::new(WalletTimelockedAddress),
+        Box::new(WalletBatchTransfer),
+        Box::new(WalletBuildOnly),
+        Box::new(WalletBumpFee),
+        Box::new(WalletConsolidate),
+        Box::new(WalletHistory),
+        Box::new(WalletLockUntil),
+        Box::new(WalletExternalSigner),
+        Box::new(WalletDryRun),
+        Box::new(TxMultisigStatus),
+        Box::new(TxMultisigCombine),
+        Box::new(TxAddOutputScripts),
+        Box::new(TxCellDeps),
+        Box::new(TxBalance),
+        Box::new(TxSince),
+        Box::new(TxExplain),
+        Box::new(TxTemplate),
+        Box::new(TxUnlockerPlugin),
         Box::new(Util),
         Box::new(Plugin),
         Box::new(RpcGetTipBlockNumber),
@@ -16,6 +16,10 @@ pub struct Setup {
     ckb_bin: String,
     cli_bin: String,
     pub keystore_plugin_bin: String,
+    pub mock_signer_bin: String,
+    pub mock_unlocker_bin: String,
+    pub xudt_bin: String,
+    pub xudt_extension_bin: String,
     ckb_dir: String,
     rpc_port: u16,
     miner: Option<Miner>,
@@ -30,6 +34,10 @@ impl Setup {
         ckb_bin: String,
         cli_bin: String,
         keystore_plugin_bin: String,
+        mock_signer_bin: String,
+        mock_unlocker_bin: String,
+        xudt_bin: String,
+        xudt_extension_bin: String,
         ckb_dir: String,
         rpc_port: u16,
         tempdir: tempfile::TempDir,
@@ -38,6 +46,10 @@ impl Setup {
             ckb_bin,
             cli_bin,
             keystore_plugin_bin,
+            mock_signer_bin,
+            mock_unlocker_bin,
+            xudt_bin,
+            xudt_extension_bin,
             ckb_dir,
             rpc_port,
             miner: None,
@@ -0,0 +1,134 @@
+use crate::setup::Setup;
+use crate::spec::{Spec, ACCOUNT2_ADDRESS};
+use log::info;
+use std::fs;
+use tempfile::tempdir;
+
+pub struct AccountWatchOnly;
+
+const CLI_PASSWORD: &str = "abc123456";
+
+impl Spec for AccountWatchOnly {
+    fn run(&self, setup: &mut Setup) {
+        let output = setup.cli_command(&["account", "new"], &[CLI_PASSWORD, CLI_PASSWORD]);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let lock_arg = value["lock_arg"].as_str().unwrap().to_string();
+
+        let output = setup.cli_command(
+            &[
+                "account",
+                "bitcoin-xpub",
+                "--lock-arg",
+                &lock_arg,
+                "--path",
+                "m/44'/309'/0'",
+            ],
+            &[CLI_PASSWORD],
+        );
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let xpub = value["bitcoin-xpub"].as_str().unwrap().to_string();
+
+        // Only the ckb root path is accepted
+        let output = setup.cli(&format!(
+            "account import-watch-only --xpub {}",
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"
+        ));
+        assert!(output.contains("m/44'/309'/0'"), "{}", output);
+
+        let output = setup.cli(&format!("account import-watch-only --xpub {}", xpub));
+        info!("output = {}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let watch_lock_arg = value["lock_arg"].as_str().unwrap().to_string();
+        assert!(value["watch_only"].as_bool().unwrap());
+
+        // Same derived addresses as the origin account
+        let addresses = |lock_arg: &str| {
+            let output = setup.cli(&format!(
+                "account bip44-addresses --lock-arg {} --network testnet --receiving-length 3 --change-length 3",
+                lock_arg
+            ));
+            serde_yaml::from_str::<serde_yaml::Value>(&output).unwrap()
+        };
+        let watch_addresses = addresses(&watch_lock_arg);
+        assert_eq!(addresses(&lock_arg), watch_addresses);
+        let receiving_address = watch_addresses["receiving"][0]["address"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let change_address = watch_addresses["change"][0]["address"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        // The account id is the lock arg of the first receiving address
+        assert_eq!(
+            value["address"]["testnet"].as_str(),
+            Some(receiving_address.as_str())
+        );
+
+        setup.miner().generate_blocks(30);
+        let tx_hash = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 2000 --skip-check-to-address",
+            setup.miner().privkey_path(),
+            receiving_address,
+        ));
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        let output = setup.cli(&format!(
+            "wallet get-capacity --from-account {}",
+            watch_lock_arg
+        ));
+        assert_eq!(output, "total: 2000.0 (CKB)");
+
+        // Build an unsigned transaction without password
+        let tmp_dir = tempdir().expect("create tempdir failed");
+        let tx_file = tmp_dir.path().join("tx.json");
+        let output = setup.cli(&format!(
+            "wallet transfer --from-account {} --to-address {} --capacity 100 --derive-change-address {} --build-only --output-file {}",
+            watch_lock_arg,
+            ACCOUNT2_ADDRESS,
+            change_address,
+            tx_file.to_str().unwrap(),
+        ));
+        assert!(output.starts_with("0x"), "{}", output);
+        assert!(tx_file.exists());
+
+        // Without derived change address the change goes back to the account
+        let output = setup.cli(&format!(
+            "wallet transfer --from-account {} --to-address {} --capacity 100 --build-only --output-file {}",
+            watch_lock_arg,
+            ACCOUNT2_ADDRESS,
+            tx_file.to_str().unwrap(),
+        ));
+        assert!(output.starts_with("0x"), "{}", output);
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        let outputs = content["tx"]["outputs"].as_array().unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(
+            outputs[1]["lock"]["args"].as_str(),
+            Some(watch_lock_arg.as_str())
+        );
+
+        // Signing is not possible
+        let output = setup.cli_command(
+            &[
+                "wallet",
+                "transfer",
+                "--from-account",
+                &watch_lock_arg,
+                "--to-address",
+                ACCOUNT2_ADDRESS,
+                "--capacity",
+                "100",
+                "--derive-change-address",
+                &change_address,
+            ],
+            &[CLI_PASSWORD],
+        );
+        assert!(output.contains("watch-only"), "{}", output);
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "AccountWatchOnly"
+    }
+}
@@ -1,15 +1,19 @@
 mod account_keystore_perm;
+mod account_watch_only;
 mod dao;
 mod plugin;
 mod rpc;
+mod tx;
 mod udt;
 mod util;
 mod wallet;
 
 pub use account_keystore_perm::*;
+pub use account_watch_only::*;
 pub use dao::*;
 pub use plugin::*;
 pub use rpc::*;
+pub use tx::*;
 pub use udt::*;
 pub use util::*;
 pub use wallet::*;
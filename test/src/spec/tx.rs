@@ -0,0 +1,702 @@
+use super::udt::{prepare, ACCOUNT1_ADDR as UDT_ACCOUNT1_ADDR, ACCOUNT2_ADDR as UDT_ACCOUNT2_ADDR};
+use super::wallet::get_capacity;
+use crate::miner::Miner;
+use crate::setup::Setup;
+use crate::spec::{Spec, ACCOUNT1_ADDRESS, ACCOUNT1_PRIVKEY, ACCOUNT2_ADDRESS, ACCOUNT2_PRIVKEY};
+use ckb_chain_spec::consensus::TYPE_ID_CODE_HASH;
+use ckb_sdk::{Address, AddressPayload, NetworkType};
+use ckb_types::{bytes::Bytes, core::ScriptHashType, h256, prelude::*, H256};
+use std::{env, fs, str::FromStr};
+use tempfile::tempdir;
+
+// Build a 2-of-2 (require first 1) multisig transaction spending a 1000 CKB
+// multisig cell, return the multisig address.
+fn prepare_multisig_tx(setup: &mut Setup, tx_file: &str) -> String {
+    let output = setup.cli(&format!(
+        "tx build-multisig-address --sighash-address {} --sighash-address {} --require-first-n 1 --threshold 2",
+        ACCOUNT1_ADDRESS, ACCOUNT2_ADDRESS,
+    ));
+    let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+    let multisig_address = value["testnet"].as_str().unwrap().to_string();
+
+    setup.miner().generate_blocks(30);
+    let tx_hash = setup.cli(&format!(
+        "wallet transfer --privkey-path {} --to-address {} --capacity 1000 --skip-check-to-address",
+        setup.miner().privkey_path(),
+        multisig_address,
+    ));
+    setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+    setup.cli(&format!("tx init --tx-file {}", tx_file));
+    setup.cli(&format!(
+        "tx add-multisig-config --sighash-address {} --sighash-address {} --require-first-n 1 --threshold 2 --tx-file {}",
+        ACCOUNT1_ADDRESS, ACCOUNT2_ADDRESS, tx_file,
+    ));
+    setup.cli(&format!(
+        "tx add-input --tx-hash {} --index 0 --tx-file {}",
+        tx_hash, tx_file,
+    ));
+    setup.cli(&format!(
+        "tx add-output --to-sighash-address {} --capacity 999.99 --tx-file {}",
+        Miner::address(),
+        tx_file,
+    ));
+
+    multisig_address
+}
+
+pub struct TxMultisigStatus;
+
+impl Spec for TxMultisigStatus {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let account1_privkey = format!("{}/account1", path);
+        let account2_privkey = format!("{}/account2", path);
+        fs::write(&account1_privkey, ACCOUNT1_PRIVKEY).unwrap();
+        fs::write(&account2_privkey, ACCOUNT2_PRIVKEY).unwrap();
+        let tx_file = format!("{}/multisig.json", path);
+        let multisig_address = prepare_multisig_tx(setup, &tx_file);
+
+        // The input cell and its header are embedded in the file
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        assert_eq!(content["version"].as_u64(), Some(1));
+        assert!(content["inputs"][0]["header"].is_object());
+
+        let status = |setup: &mut Setup| {
+            let output = setup.cli(&format!("tx status --tx-file {}", tx_file));
+            log::info!("tx status: {}", output);
+            serde_yaml::from_str::<serde_yaml::Value>(&output).unwrap()
+        };
+        let value = status(setup);
+        assert_eq!(value["embedded_inputs"].as_u64(), Some(1));
+        assert_eq!(value["ready"].as_bool(), Some(false));
+        let group = &value["lock_groups"][0];
+        assert_eq!(group["lock_type"].as_str(), Some("multisig"));
+        assert_eq!(group["threshold"].as_u64(), Some(2));
+        assert_eq!(group["missing_signatures"].as_u64(), Some(2));
+        assert_eq!(group["signers"][0]["required"].as_bool(), Some(true));
+        assert_eq!(group["signers"][1]["required"].as_bool(), Some(false));
+
+        // The second signer signed, the first signer is still required
+        setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account2_privkey, tx_file,
+        ));
+        let value = status(setup);
+        assert_eq!(value["ready"].as_bool(), Some(false));
+        let group = &value["lock_groups"][0];
+        assert_eq!(group["missing_signatures"].as_u64(), Some(1));
+        assert_eq!(group["signers"][0]["signed"].as_bool(), Some(false));
+        assert_eq!(group["signers"][1]["signed"].as_bool(), Some(true));
+        let missing = value["missing"].as_sequence().unwrap();
+        assert!(missing
+            .iter()
+            .any(|item| item.as_str().unwrap().contains(ACCOUNT1_ADDRESS)));
+
+        setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account1_privkey, tx_file,
+        ));
+        let value = status(setup);
+        assert_eq!(value["ready"].as_bool(), Some(true), "{:?}", value);
+        assert!(value["missing"].as_sequence().unwrap().is_empty());
+
+        let sent_tx_hash = setup.cli(&format!("tx send --tx-file {}", tx_file));
+        assert!(sent_tx_hash.starts_with("0x"), "{}", sent_tx_hash);
+        setup.miner().mine_until_transaction_confirm(&sent_tx_hash);
+        let output = get_capacity(setup, &multisig_address, "total: 0.0 (CKB)");
+        assert_eq!(output, "total: 0.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxMultisigStatus"
+    }
+}
+
+pub struct TxMultisigCombine;
+
+impl Spec for TxMultisigCombine {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let account1_privkey = format!("{}/account1", path);
+        let account2_privkey = format!("{}/account2", path);
+        fs::write(&account1_privkey, ACCOUNT1_PRIVKEY).unwrap();
+        fs::write(&account2_privkey, ACCOUNT2_PRIVKEY).unwrap();
+        let tx_file = format!("{}/multisig.json", path);
+        let multisig_address = prepare_multisig_tx(setup, &tx_file);
+
+        // Each co-signer signs a copy of the same file
+        let tx_file1 = format!("{}/signed1.json", path);
+        let tx_file2 = format!("{}/signed2.json", path);
+        let other_tx_file = format!("{}/other.json", path);
+        let merged_tx_file = format!("{}/merged.json", path);
+        fs::copy(&tx_file, &tx_file1).unwrap();
+        fs::copy(&tx_file, &tx_file2).unwrap();
+        fs::copy(&tx_file, &other_tx_file).unwrap();
+        setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account1_privkey, tx_file1,
+        ));
+        setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account2_privkey, tx_file2,
+        ));
+
+        // Different transactions can not be combined
+        setup.cli(&format!(
+            "tx add-output --to-sighash-address {} --capacity 100 --tx-file {}",
+            ACCOUNT1_ADDRESS, other_tx_file,
+        ));
+        let output = setup.cli(&format!(
+            "tx combine --tx-file {} --tx-file {} --output-file {}",
+            tx_file1, other_tx_file, merged_tx_file,
+        ));
+        assert!(output.contains("Transaction conflict"), "{}", output);
+
+        // A signature not signed by the signers is rejected
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file2).unwrap()).unwrap();
+        let lock_arg = content["signing_status"][0]["lock_arg"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        setup.cli(&format!(
+            "tx add-signature --lock-arg {} --signature 0x{} --tx-file {}",
+            lock_arg,
+            "01".repeat(65),
+            tx_file2,
+        ));
+
+        let output = setup.cli(&format!(
+            "tx combine --tx-file {} --tx-file {} --output-file {}",
+            tx_file1, tx_file2, merged_tx_file,
+        ));
+        log::info!("tx combine: {}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["signatures"].as_u64(), Some(2));
+        assert_eq!(value["rejected"].as_sequence().unwrap().len(), 1);
+        assert_eq!(value["ready"].as_bool(), Some(true));
+
+        let output = setup.cli(&format!("tx status --tx-file {}", merged_tx_file));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["ready"].as_bool(), Some(true), "{}", output);
+
+        let sent_tx_hash = setup.cli(&format!("tx send --tx-file {}", merged_tx_file));
+        assert!(sent_tx_hash.starts_with("0x"), "{}", sent_tx_hash);
+        setup.miner().mine_until_transaction_confirm(&sent_tx_hash);
+        let output = get_capacity(setup, &multisig_address, "total: 0.0 (CKB)");
+        assert_eq!(output, "total: 0.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxMultisigCombine"
+    }
+}
+
+pub struct TxAddOutputScripts;
+
+impl Spec for TxAddOutputScripts {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let tx_file = format!("{}/multisig.json", path);
+        prepare_multisig_tx(setup, &tx_file);
+
+        // A full format address with any lock script
+        let payload = AddressPayload::new_full(
+            ScriptHashType::Data1,
+            h256!("0x1234").pack(),
+            Bytes::from(vec![0x33; 20]),
+        );
+        let address = Address::new(NetworkType::Testnet, payload, true).to_string();
+        setup.cli(&format!(
+            "tx add-output --to-address {} --capacity 200 --type-id --tx-file {}",
+            address, tx_file,
+        ));
+        let type_script = format!("0x{:x}-data1-0x01", h256!("0x5678"));
+        let output = setup.cli(&format!(
+            "tx add-output --to-address {} --capacity 61 --type-script {} --tx-file {}",
+            address, type_script, tx_file,
+        ));
+        assert!(output.contains("occupied capacity"), "{}", output);
+        setup.cli(&format!(
+            "tx add-output --to-address {} --capacity 200 --type-script {} --tx-file {}",
+            address, type_script, tx_file,
+        ));
+
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        let outputs = content["transaction"]["outputs"].as_array().unwrap();
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(
+            outputs[1]["lock"]["code_hash"].as_str().unwrap(),
+            format!("{:#x}", h256!("0x1234"))
+        );
+        assert_eq!(
+            outputs[1]["type"]["code_hash"].as_str().unwrap(),
+            format!("{:#x}", TYPE_ID_CODE_HASH)
+        );
+        assert_eq!(outputs[1]["type"]["args"].as_str().unwrap().len(), 66);
+        assert_eq!(outputs[2]["type"]["args"].as_str().unwrap(), "0x01");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxAddOutputScripts"
+    }
+}
+
+pub struct TxCellDeps;
+
+impl Spec for TxCellDeps {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let tx_file = format!("{}/multisig.json", path);
+        prepare_multisig_tx(setup, &tx_file);
+
+        let output = setup.cli("util genesis-scripts");
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let out_point_value = &value["secp256k1_data"]["out_point"];
+        let out_point = format!(
+            "{}-{}",
+            out_point_value["tx_hash"].as_str().unwrap(),
+            out_point_value["index"].as_u64().unwrap()
+        );
+        setup.cli(&format!(
+            "tx add-cell-dep --out-point {} --tx-file {}",
+            out_point, tx_file,
+        ));
+        // Dead or unknown cell is rejected
+        let output = setup.cli(&format!(
+            "tx add-cell-dep --out-point {:#x}-0 --dep-type dep_group --tx-file {}",
+            h256!("0x1234"),
+            tx_file,
+        ));
+        assert!(output.contains("Invalid cell dep status"), "{}", output);
+
+        let block_hash = setup.miner().generate_block();
+        setup.cli(&format!(
+            "tx add-header-dep --block-hash {:#x} --tx-file {}",
+            block_hash, tx_file,
+        ));
+        let output = setup.cli(&format!(
+            "tx add-header-dep --block-hash {:#x} --tx-file {}",
+            h256!("0x1234"),
+            tx_file,
+        ));
+        assert!(output.contains("Block not found"), "{}", output);
+
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        // The multisig dep group is added by `tx add-input`
+        let cell_deps = content["transaction"]["cell_deps"].as_array().unwrap();
+        assert_eq!(cell_deps.len(), 2);
+        assert_eq!(cell_deps[1]["dep_type"].as_str().unwrap(), "code");
+        let header_deps = content["transaction"]["header_deps"].as_array().unwrap();
+        assert_eq!(
+            header_deps[0].as_str().unwrap(),
+            format!("{:#x}", block_hash)
+        );
+
+        let output = setup.cli(&format!("tx info --tx-file {}", tx_file));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["cell_deps"][1]["status"].as_str().unwrap(), "live");
+        assert!(value["header_deps"][0]["block_number"].as_u64().is_some());
+
+        for field in &["cell-deps", "header-deps"] {
+            setup.cli(&format!(
+                "tx clear-field --field {} --tx-file {}",
+                field, tx_file
+            ));
+        }
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        assert!(content["transaction"]["cell_deps"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        assert!(content["transaction"]["header_deps"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxCellDeps"
+    }
+}
+
+pub struct TxBalance;
+
+impl Spec for TxBalance {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let account1_privkey = format!("{}/account1", path);
+        fs::write(&account1_privkey, ACCOUNT1_PRIVKEY).unwrap();
+        let tx_file = format!("{}/balance.json", path);
+
+        setup.miner().generate_blocks(30);
+        for capacity in &["300", "500"] {
+            let tx_hash = setup.cli(&format!(
+                "wallet transfer --privkey-path {} --to-address {} --capacity {} --skip-check-to-address",
+                setup.miner().privkey_path(),
+                ACCOUNT1_ADDRESS,
+                capacity,
+            ));
+            setup.miner().mine_until_transaction_confirm(&tx_hash);
+        }
+
+        setup.cli(&format!("tx init --tx-file {}", tx_file));
+        setup.cli(&format!(
+            "tx add-output --to-sighash-address {} --capacity 600 --tx-file {}",
+            ACCOUNT2_ADDRESS, tx_file,
+        ));
+        // Not enough capacity in the change address
+        let output = setup.cli(&format!(
+            "tx balance --change-address {} --fee-rate 1000 --tx-file {}",
+            ACCOUNT2_ADDRESS, tx_file,
+        ));
+        assert!(output.contains("Capacity not enough"), "{}", output);
+
+        let output = setup.cli(&format!(
+            "tx balance --change-address {} --fee-rate 1000 --tx-file {}",
+            ACCOUNT1_ADDRESS, tx_file,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["new_inputs"].as_sequence().unwrap().len(), 2);
+        assert_eq!(value["change"]["index"].as_u64(), Some(1));
+        assert_eq!(value["fee_rate"].as_u64(), Some(1000));
+
+        // The change output is adjusted by the new fee rate
+        let output = setup.cli(&format!(
+            "tx balance --change-address {} --fee-rate 2000 --tx-file {}",
+            ACCOUNT1_ADDRESS, tx_file,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(value["new_inputs"].as_sequence().unwrap().is_empty());
+        assert_eq!(value["change"]["index"].as_u64(), Some(1));
+        assert!(value["fee_rate"].as_u64().unwrap() >= 2000);
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        assert_eq!(
+            content["transaction"]["outputs"].as_array().unwrap().len(),
+            2
+        );
+
+        setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account1_privkey, tx_file,
+        ));
+        let output = setup.cli(&format!("tx info --tx-file {}", tx_file));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let size = &value["size"];
+        assert_eq!(size["current"].as_u64(), size["estimated"].as_u64());
+        assert!(size["limit"].as_u64().unwrap() > size["current"].as_u64().unwrap());
+        let cycles = &value["cycles"];
+        assert_eq!(cycles["script_groups"].as_sequence().unwrap().len(), 1);
+        let group = &cycles["script_groups"][0];
+        assert_eq!(group["group_type"].as_str().unwrap(), "lock");
+        assert!(group["cycles"].as_u64().unwrap() > 0, "{}", output);
+        assert_eq!(cycles["total"].as_u64(), group["cycles"].as_u64());
+
+        let sent_tx_hash = setup.cli(&format!("tx send --tx-file {}", tx_file));
+        assert!(sent_tx_hash.starts_with("0x"), "{}", sent_tx_hash);
+        setup.miner().mine_until_transaction_confirm(&sent_tx_hash);
+        let output = get_capacity(setup, ACCOUNT2_ADDRESS, "total: 600.0 (CKB)");
+        assert_eq!(output, "total: 600.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxBalance"
+    }
+}
+
+pub struct TxSince;
+
+impl Spec for TxSince {
+    fn run(&self, setup: &mut Setup) {
+        let output = setup.cli("util since --value relative:block:100");
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["raw"].as_str().unwrap(), "0x8000000000000064");
+        assert!(value["relative"].as_bool().unwrap());
+        assert_eq!(value["metric"].as_str().unwrap(), "block_number");
+        assert_eq!(value["value"].as_u64().unwrap(), 100);
+
+        let output = setup.cli("util since --value 0x20000a0001000005");
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(!value["relative"].as_bool().unwrap());
+        assert_eq!(value["metric"].as_str().unwrap(), "epoch");
+        assert_eq!(value["value"]["number"].as_u64().unwrap(), 5);
+        assert_eq!(value["value"]["index"].as_u64().unwrap(), 1);
+        assert_eq!(value["value"]["length"].as_u64().unwrap(), 10);
+
+        let output = setup.cli("util since --value 0x0100000000000000");
+        assert!(output.contains("Invalid since flags"), "{}", output);
+
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let tx_file = format!("{}/multisig.json", path);
+        prepare_multisig_tx(setup, &tx_file);
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        let out_point = &content["transaction"]["inputs"][0]["previous_output"];
+        let tx_hash = out_point["tx_hash"].as_str().unwrap().to_string();
+
+        let output = setup.cli(&format!("tx info --tx-file {}", tx_file));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["inputs"][0]["since"]["raw"].as_str().unwrap(), "0x0");
+        assert!(value["inputs"][0]["since"]["spendable"].as_bool().unwrap());
+        // Not signed yet: the placeholder witness is larger and the lock script fails
+        assert!(
+            value["size"]["estimated"].as_u64().unwrap()
+                > value["size"]["current"].as_u64().unwrap()
+        );
+        assert!(value["cycles"]["script_groups"][0]["error"]
+            .as_str()
+            .is_some());
+
+        for (since, spendable) in &[("relative:block:1000", false), ("block:1", true)] {
+            setup.cli(&format!(
+                "tx clear-field --field inputs --tx-file {}",
+                tx_file
+            ));
+            setup.cli(&format!(
+                "tx add-input --tx-hash {} --index 0 --since {} --tx-file {}",
+                tx_hash, since, tx_file,
+            ));
+            let output = setup.cli(&format!("tx info --tx-file {}", tx_file));
+            let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+            assert_eq!(
+                value["inputs"][0]["since"]["spendable"].as_bool(),
+                Some(*spendable),
+                "{}",
+                output
+            );
+        }
+
+        // Conflicts with --since-absolute-epoch
+        let output = setup.cli(&format!(
+            "tx add-input --tx-hash {} --index 0 --since block:1 --since-absolute-epoch 1 --tx-file {}",
+            tx_hash, tx_file,
+        ));
+        assert!(output.contains("cannot be used with"), "{}", output);
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxSince"
+    }
+}
+
+pub struct TxExplain;
+
+impl Spec for TxExplain {
+    fn run(&self, setup: &mut Setup) {
+        setup.miner().generate_blocks(30);
+        let tx_hash = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 100 --fee-rate 1000",
+            setup.miner().privkey_path(),
+            ACCOUNT1_ADDRESS,
+        ));
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        // The inputs are dead cells now
+        let output = setup.cli(&format!("tx explain --tx-hash {}", tx_hash));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["tx_hash"].as_str().unwrap(), tx_hash);
+        assert_eq!(value["tx_status"]["status"].as_str().unwrap(), "committed");
+        assert_eq!(
+            value["inputs"][0]["lock"]["name"].as_str().unwrap(),
+            "sighash"
+        );
+        assert_eq!(value["cell_deps"][0]["name"].as_str().unwrap(), "sighash");
+        assert!(value["fee_rate"].as_u64().unwrap() >= 1000);
+        let changes = value["balance_changes"].as_sequence().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .any(|change| change["capacity"].as_str().unwrap() == "+100.0"));
+
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let tx_file = format!("{}/multisig.json", path);
+        prepare_multisig_tx(setup, &tx_file);
+        let output = setup.cli(&format!("tx explain --tx-file {}", tx_file));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(
+            value["inputs"][0]["lock"]["name"].as_str().unwrap(),
+            "multisig"
+        );
+        assert_eq!(
+            value["outputs"][0]["lock"]["name"].as_str().unwrap(),
+            "sighash"
+        );
+        assert_eq!(value["fee"].as_str().unwrap(), "0.01");
+        assert!(value["tx_status"].is_null());
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxExplain"
+    }
+}
+
+pub struct TxTemplate;
+
+const TX_TEMPLATE: &str = r#"
+from = "${from}"
+fee_rate = 1000
+
+[[outputs]]
+address = "${to}"
+capacity = "${amount}"
+"#;
+
+impl Spec for TxTemplate {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let template_file = format!("{}/transfer.toml", path);
+        fs::write(&template_file, TX_TEMPLATE).unwrap();
+        let tx_file = format!("{}/transfer.json", path);
+
+        setup.miner().generate_blocks(30);
+        let output = setup.cli(&format!(
+            "tx template render --template {} --var from={} --var to={} --tx-file {}",
+            template_file,
+            Miner::address(),
+            ACCOUNT2_ADDRESS,
+            tx_file,
+        ));
+        assert!(
+            output.contains("Missing template variables: amount"),
+            "{}",
+            output
+        );
+
+        let output = setup.cli(&format!(
+            "tx template render --template {} --var from={} --var to={} --var amount=100 --tx-file {}",
+            template_file,
+            Miner::address(),
+            ACCOUNT2_ADDRESS,
+            tx_file,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(!value["new_inputs"].as_sequence().unwrap().is_empty());
+        assert_eq!(value["change"]["index"].as_u64(), Some(1));
+
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&tx_file).unwrap()).unwrap();
+        assert_eq!(
+            content["transaction"]["outputs"][0]["capacity"]
+                .as_str()
+                .unwrap(),
+            "0x2540be400"
+        );
+        let output = setup.cli(&format!("tx status --tx-file {}", tx_file));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["lock_groups"].as_sequence().unwrap().len(), 1);
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxTemplate"
+    }
+}
+
+pub struct TxUnlockerPlugin;
+
+impl Spec for TxUnlockerPlugin {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        prepare(setup, &path);
+        let account2_privkey = format!("{}/account2", path);
+        let tx_file = format!("{}/unlocker.json", path);
+
+        let cell_deps: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(format!("{}/cell_deps.json", path)).unwrap())
+                .unwrap();
+        let acp_item = &cell_deps["items"]["acp"];
+        let acp_code_hash = acp_item["script_id"]["code_hash"].as_str().unwrap();
+        let acp_dep_tx_hash = acp_item["cell_dep"]["out_point"]["tx_hash"]
+            .as_str()
+            .unwrap();
+
+        // An anyone-can-pay cell, it is unlocked by the mock unlocker plugin
+        let acp_args = Address::from_str(UDT_ACCOUNT1_ADDR)
+            .unwrap()
+            .payload()
+            .args();
+        let payload = AddressPayload::new_full(
+            ScriptHashType::Type,
+            H256::from_str(&acp_code_hash[2..]).unwrap().pack(),
+            acp_args,
+        );
+        let acp_address = Address::new(NetworkType::Testnet, payload, true).to_string();
+        let acp_tx_hash = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 200 --skip-check-to-address",
+            setup.miner().privkey_path(),
+            acp_address,
+        ));
+        setup.miner().mine_until_transaction_confirm(&acp_tx_hash);
+
+        // Mixed with the anyone-can-pay input and the sighash inputs
+        setup.cli(&format!("tx init --tx-file {}", tx_file));
+        setup.cli(&format!(
+            "tx add-input --tx-hash {} --index 0 --skip-check --tx-file {}",
+            acp_tx_hash, tx_file,
+        ));
+        setup.cli(&format!(
+            "tx add-cell-dep --out-point {}-0 --dep-type dep_group --tx-file {}",
+            acp_dep_tx_hash, tx_file,
+        ));
+        setup.cli(&format!(
+            "tx add-output --to-address {} --capacity 300 --tx-file {}",
+            acp_address, tx_file,
+        ));
+        let output = setup.cli(&format!(
+            "tx balance --change-address {} --fee-rate 2000 --tx-file {}",
+            UDT_ACCOUNT2_ADDR, tx_file,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(!value["new_inputs"].as_sequence().unwrap().is_empty());
+
+        // No unlocker plugin for the anyone-can-pay lock
+        let output = setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account2_privkey, tx_file,
+        ));
+        assert!(output.contains("No unlocker plugin found"), "{}", output);
+
+        env::set_var("MOCK_UNLOCKER_CODE_HASH", acp_code_hash);
+        let output = setup.cli(&format!(
+            "plugin install --binary-path {}",
+            setup.mock_unlocker_bin
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["name"].as_str().unwrap(), "mock_unlocker");
+
+        let output = setup.cli(&format!(
+            "tx sign-inputs --privkey-path {} --add-signatures --tx-file {}",
+            account2_privkey, tx_file,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let items = value.as_sequence().unwrap();
+        assert_eq!(items.len(), 2, "{}", output);
+        assert!(items
+            .iter()
+            .any(|item| item["unlocked-by"].as_str() == Some("mock_unlocker")));
+
+        let sent_tx_hash = setup.cli(&format!("tx send --tx-file {}", tx_file));
+        assert!(sent_tx_hash.starts_with("0x"), "{}", sent_tx_hash);
+        setup.miner().mine_until_transaction_confirm(&sent_tx_hash);
+        let output = get_capacity(setup, &acp_address, "total: 300.0 (CKB)");
+        assert_eq!(output, "total: 300.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "TxUnlockerPlugin"
+    }
+}
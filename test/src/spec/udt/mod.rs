@@ -1,8 +1,9 @@
 mod sudt;
 
 pub use sudt::{
-    SudtIssueToAcp, SudtIssueToCheque, SudtTransferToChequeForClaim,
-    SudtTransferToChequeForWithdraw, SudtTransferToMultiAcp,
+    SudtAirdrop, SudtBalances, SudtHolders, SudtIssueToAcp, SudtIssueToCheque, SudtTokenRegistry,
+    SudtTransferToChequeForClaim, SudtTransferToChequeForWithdraw, SudtTransferToMultiAcp,
+    XudtArgs, XudtIssueTransfer,
 };
 
 use core::panic;
@@ -56,23 +57,7 @@ pub fn prepare(setup: &mut Setup, tmp_path: &str) {
         (CHEQUE_BIN.len(), cheque_bin),
         (SUDT_BIN.len(), sudt_bin),
     ] {
-        let capacity_ckb = size + 200;
-        let tx_hash = setup.cli(&format!(
-            "wallet transfer --privkey-path {} --to-address {} --to-data-path {} --capacity {} --type-id",
-            miner_privkey,
-            miner_address,
-            bin_path,
-            capacity_ckb,
-        ));
-        log::info!("deploy script binary {}, tx_hash: {}", bin_path, tx_hash);
-        setup.miner().mine_until_transaction_confirm(&tx_hash);
-        let code_hash = {
-            let output = setup.cli(&format!("util cell-meta --tx-hash {} --index 0", tx_hash));
-            log::info!("cell-meta output: {}", output);
-            let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
-            value["type_hash"].as_str().unwrap().to_string()
-        };
-        tx_hashes.push((tx_hash, code_hash));
+        tx_hashes.push(deploy_script(setup, &bin_path, size));
     }
 
     let secp_data_out_point = {
@@ -193,16 +178,50 @@ pub fn prepare(setup: &mut Setup, tmp_path: &str) {
     }
 }
 
+/// Deploy a script binary with type id, return the transaction hash and the
+/// type hash (code hash of the script)
+pub fn deploy_script(setup: &mut Setup, bin_path: &str, size: usize) -> (String, String) {
+    let miner_privkey = setup.miner().privkey_path().to_string();
+    let miner_address = Miner::address();
+    let capacity_ckb = size + 200;
+    let tx_hash = setup.cli(&format!(
+        "wallet transfer --privkey-path {} --to-address {} --to-data-path {} --capacity {} --type-id",
+        miner_privkey,
+        miner_address,
+        bin_path,
+        capacity_ckb,
+    ));
+    log::info!("deploy script binary {}, tx_hash: {}", bin_path, tx_hash);
+    setup.miner().mine_until_transaction_confirm(&tx_hash);
+    let output = setup.cli(&format!("util cell-meta --tx-hash {} --index 0", tx_hash));
+    log::info!("cell-meta output: {}", output);
+    let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+    let code_hash = value["type_hash"].as_str().unwrap().to_string();
+    (tx_hash, code_hash)
+}
+
 pub fn check_amount(
     setup: &mut Setup,
     owner_addr: &str,
     cell_deps_path: &str,
     addr: &str,
     expected_amount: u128,
+) {
+    check_udt_amount(setup, owner_addr, cell_deps_path, "", addr, expected_amount);
+}
+
+/// Check the amount with the udt type arguments (e.g. `--udt-type xudt`)
+pub fn check_udt_amount(
+    setup: &mut Setup,
+    owner_addr: &str,
+    cell_deps_path: &str,
+    udt_args: &str,
+    addr: &str,
+    expected_amount: u128,
 ) {
     let output = setup.cli(&format!(
-        "sudt get-amount --owner {} --address {} --cell-deps {}",
-        owner_addr, addr, cell_deps_path,
+        "sudt get-amount --owner {} --address {} --cell-deps {} {}",
+        owner_addr, addr, cell_deps_path, udt_args,
     ));
     log::debug!("get amount:\n{}", output);
     setup.miner().generate_blocks(6);
@@ -1,8 +1,13 @@
+use std::fs;
+
 use tempfile::tempdir;
 
 use ckb_chain_spec::ChainSpec;
 
-use super::{check_amount, create_acp_cell, prepare, ACCOUNT1_ADDR, ACCOUNT2_ADDR, OWNER_ADDR};
+use super::{
+    check_amount, check_udt_amount, create_acp_cell, deploy_script, prepare, ACCOUNT1_ADDR,
+    ACCOUNT2_ADDR, OWNER_ADDR,
+};
 use crate::setup::Setup;
 use crate::spec::Spec;
 
@@ -495,3 +500,603 @@ impl Spec for SudtTransferToChequeForWithdraw {
         "SudtTransferToChequeForWithdraw"
     }
 }
+
+pub struct XudtArgs;
+
+impl Spec for XudtArgs {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap();
+        let cell_deps_path = format!("{}/cell_deps.json", path);
+        prepare(setup, path);
+
+        // The xudt flags are rejected for sudt
+        let output = setup.cli(&format!(
+            "sudt get-amount --owner {} --address {} --cell-deps {} --xudt-owner-mode input-type",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path,
+        ));
+        assert!(
+            output.contains("require <udt-type> to be xudt"),
+            "{}",
+            output
+        );
+
+        // The xudt script is resolved by the cell deps file
+        let output = setup.cli(&format!(
+            "sudt get-amount --owner {} --address {} --cell-deps {} --udt-type xudt",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path,
+        ));
+        assert!(
+            output.contains("no xudt cell_dep item in cell_deps"),
+            "{}",
+            output
+        );
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "XudtArgs"
+    }
+}
+
+pub struct XudtIssueTransfer;
+
+impl Spec for XudtIssueTransfer {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap();
+        let owner_key_path = format!("{}/owner", path);
+        let account1_key_path = format!("{}/account1", path);
+        prepare(setup, path);
+
+        // Deploy the xudt script and the extension script, add them to the cell deps
+        let xudt_bin = setup.xudt_bin.clone();
+        let xudt_extension_bin = setup.xudt_extension_bin.clone();
+        let (xudt_tx_hash, xudt_code_hash) = deploy_script(
+            setup,
+            &xudt_bin,
+            fs::metadata(&xudt_bin).unwrap().len() as usize,
+        );
+        let (extension_tx_hash, extension_code_hash) = deploy_script(
+            setup,
+            &xudt_extension_bin,
+            fs::metadata(&xudt_extension_bin).unwrap().len() as usize,
+        );
+        let cell_dep_item = |tx_hash: &str, code_hash: &str| {
+            serde_json::json!({
+                "script_id": {
+                    "hash_type": "type",
+                    "code_hash": code_hash,
+                },
+                "cell_dep": {
+                    "out_point": {
+                        "tx_hash": tx_hash,
+                        "index": "0x0"
+                    },
+                    "dep_type": "code"
+                }
+            })
+        };
+        let mut cell_deps: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(format!("{}/cell_deps.json", path)).unwrap())
+                .unwrap();
+        cell_deps["items"]["xudt"] = cell_dep_item(&xudt_tx_hash, &xudt_code_hash);
+        cell_deps["extensions"] =
+            serde_json::json!([cell_dep_item(&extension_tx_hash, &extension_code_hash)]);
+        let cell_deps_path = format!("{}/xudt_cell_deps.json", path);
+        fs::write(
+            &cell_deps_path,
+            serde_json::to_string_pretty(&cell_deps).unwrap(),
+        )
+        .unwrap();
+
+        // Issue and transfer the xudt without flags
+        let udt_args = "--udt-type xudt";
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:1000 --cell-deps {} --privkey-path {} {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path, owner_key_path, udt_args
+        ));
+        log::info!("Issue 1000 xUDT to account 1:\n{}", output);
+        setup.miner().generate_blocks(6);
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            udt_args,
+            ACCOUNT1_ADDR,
+            1000,
+        );
+        let output = setup.cli(&format!(
+            "sudt transfer --owner {} --sender {} --udt-to {}:300 --cell-deps {} --privkey-path {} {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, ACCOUNT2_ADDR, cell_deps_path, account1_key_path, udt_args
+        ));
+        log::info!("Transfer 300 xUDT from account 1 to account 2:\n{}", output);
+        setup.miner().generate_blocks(6);
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            udt_args,
+            ACCOUNT1_ADDR,
+            700,
+        );
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            udt_args,
+            ACCOUNT2_ADDR,
+            300,
+        );
+
+        // The owner mode flag is in the args, the owner lock still unlocks
+        // the owner mode when only input-type is set
+        let udt_args = "--udt-type xudt --xudt-owner-mode input-type";
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:400 --cell-deps {} --privkey-path {} {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path, owner_key_path, udt_args
+        ));
+        log::info!(
+            "Issue 400 xUDT (owner mode: input-type) to account 1:\n{}",
+            output
+        );
+        setup.miner().generate_blocks(6);
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            udt_args,
+            ACCOUNT1_ADDR,
+            400,
+        );
+        // The owner lock can not issue the xudt when no-input-lock is set
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:400 --cell-deps {} --privkey-path {} --udt-type xudt --xudt-owner-mode no-input-lock",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path, owner_key_path
+        ));
+        assert!(output.contains("ValidationFailure"), "{}", output);
+
+        // The extension script is stored in the args and its cell dep is
+        // resolved by the cell deps file
+        let udt_args = format!(
+            "--udt-type xudt --xudt-extension {}-type-0x",
+            extension_code_hash
+        );
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:500 --cell-deps {} --privkey-path {} {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path, owner_key_path, udt_args
+        ));
+        log::info!("Issue 500 xUDT (with extension) to account 1:\n{}", output);
+        setup.miner().generate_blocks(6);
+        let output = setup.cli(&format!(
+            "sudt transfer --owner {} --sender {} --udt-to {}:200 --cell-deps {} --privkey-path {} {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, ACCOUNT2_ADDR, cell_deps_path, account1_key_path, udt_args
+        ));
+        log::info!(
+            "Transfer 200 xUDT (with extension) from account 1 to account 2:\n{}",
+            output
+        );
+        setup.miner().generate_blocks(6);
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            &udt_args,
+            ACCOUNT1_ADDR,
+            300,
+        );
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            &udt_args,
+            ACCOUNT2_ADDR,
+            200,
+        );
+        // Each flag set is a different token
+        check_udt_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            "--udt-type xudt",
+            ACCOUNT1_ADDR,
+            700,
+        );
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "XudtIssueTransfer"
+    }
+}
+
+pub struct SudtTokenRegistry;
+
+impl Spec for SudtTokenRegistry {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap();
+        let owner_key_path = format!("{}/owner", path);
+        let cell_deps_path = format!("{}/cell_deps.json", path);
+        prepare(setup, path);
+
+        // The registry is in the shared ckb-cli home directory
+        setup.cli("sudt token remove --symbol TST");
+        let output = setup.cli(&format!(
+            "sudt token add --symbol TST --decimals 2 --owner {} --cell-deps {}",
+            OWNER_ADDR, cell_deps_path,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["symbol"].as_str().unwrap(), "TST");
+        assert_eq!(value["udt_type"].as_str().unwrap(), "sudt");
+        let output = setup.cli(&format!(
+            "sudt token add --symbol TST --decimals 8 --owner {} --cell-deps {}",
+            OWNER_ADDR, cell_deps_path,
+        ));
+        assert!(output.contains("Token already exists"), "{}", output);
+
+        let output = setup.cli("sudt token list");
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(value
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .any(|token| token["symbol"].as_str() == Some("TST")));
+
+        // Too many decimal places
+        let output = setup.cli(&format!(
+            "sudt issue --token TST --udt-to {}:12.555 --privkey-path {}",
+            ACCOUNT1_ADDR, owner_key_path,
+        ));
+        assert!(output.contains("too many decimal places"), "{}", output);
+
+        let output = setup.cli(&format!(
+            "sudt issue --token TST --udt-to {}:12.5 --privkey-path {}",
+            ACCOUNT1_ADDR, owner_key_path,
+        ));
+        log::info!("Issue 12.5 TST to account 1:\n{}", output);
+        setup.miner().generate_blocks(6);
+        // The raw amount in base units
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            ACCOUNT1_ADDR,
+            1250,
+        );
+        let output = setup.cli(&format!(
+            "sudt get-amount --token TST --address {}",
+            ACCOUNT1_ADDR
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["total_amount_display"].as_str().unwrap(), "12.5 TST");
+
+        setup.cli("sudt token remove --symbol TST");
+        let output = setup.cli(&format!(
+            "sudt get-amount --token TST --address {}",
+            ACCOUNT1_ADDR
+        ));
+        assert!(output.contains("Token not found"), "{}", output);
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "SudtTokenRegistry"
+    }
+}
+
+pub struct SudtBalances;
+
+impl Spec for SudtBalances {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap();
+        let owner_key_path = format!("{}/owner", path);
+        let account1_key_path = format!("{}/account1", path);
+        let cell_deps_path = format!("{}/cell_deps.json", path);
+        prepare(setup, path);
+
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:1000 --to-cheque-address --cell-deps {} --privkey-path {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path, owner_key_path
+        ));
+        log::info!("Issue 1000 SUDT to account 1 cheque address:\n{}", output);
+        setup.miner().generate_blocks(6);
+
+        let account1_acp_addr = create_acp_cell(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            ACCOUNT1_ADDR,
+            account1_key_path.as_str(),
+        );
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:300 --to-acp-address --cell-deps {} --privkey-path {}",
+            OWNER_ADDR, account1_acp_addr, cell_deps_path, owner_key_path
+        ));
+        log::info!("Issue 300 SUDT to account 1 acp address:\n{}", output);
+        setup.miner().generate_blocks(6);
+
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:50 --cell-deps {} --privkey-path {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, cell_deps_path, owner_key_path
+        ));
+        log::info!("Issue 50 SUDT to account 1 sighash address:\n{}", output);
+        setup.miner().generate_blocks(6);
+
+        let output = setup.cli(&format!(
+            "sudt balances --address {} --cell-deps {}",
+            ACCOUNT1_ADDR, cell_deps_path
+        ));
+        log::info!("SUDT balances of account 1:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let balances = value["balances"].as_sequence().unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0]["udt_type"].as_str().unwrap(), "sudt");
+        assert_eq!(balances[0]["cell_count"].as_u64().unwrap(), 3);
+        assert_eq!(balances[0]["total_amount"].as_str().unwrap(), "1350");
+        let amounts = &balances[0]["amounts"];
+        assert_eq!(amounts["address"].as_str().unwrap(), "50");
+        assert_eq!(amounts["acp"].as_str().unwrap(), "300");
+        assert_eq!(amounts["cheque_receiver"].as_str().unwrap(), "1000");
+
+        let output = setup.cli(&format!(
+            "sudt balances --address {} --cell-deps {}",
+            OWNER_ADDR, cell_deps_path
+        ));
+        log::info!("SUDT balances of the owner:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let balances = value["balances"].as_sequence().unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(
+            balances[0]["amounts"]["cheque_sender"].as_str().unwrap(),
+            "1000"
+        );
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "SudtBalances"
+    }
+}
+
+pub struct SudtHolders;
+
+impl Spec for SudtHolders {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap();
+        let owner_key_path = format!("{}/owner", path);
+        let account1_key_path = format!("{}/account1", path);
+        let cell_deps_path = format!("{}/cell_deps.json", path);
+        let export_path = format!("{}/holders.csv", path);
+        prepare(setup, path);
+
+        let output = setup.cli(&format!(
+            "sudt issue --owner {} --udt-to {}:1000 --udt-to {}:500 --cell-deps {} --privkey-path {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, ACCOUNT2_ADDR, cell_deps_path, owner_key_path
+        ));
+        log::info!(
+            "Issue 1000 SUDT to account 1 and 500 SUDT to account 2:\n{}",
+            output
+        );
+        setup.miner().generate_blocks(6);
+        let snapshot_block: u64 = setup
+            .cli("rpc get_tip_block_number")
+            .parse()
+            .expect("block number");
+
+        let output = setup.cli(&format!(
+            "sudt transfer --owner {} --sender {} --udt-to {}:300 --cell-deps {} --privkey-path {}",
+            OWNER_ADDR, ACCOUNT1_ADDR, ACCOUNT2_ADDR, cell_deps_path, account1_key_path
+        ));
+        log::info!("Transfer 300 SUDT from account 1 to account 2:\n{}", output);
+        setup.miner().generate_blocks(6);
+
+        let output = setup.cli(&format!(
+            "sudt holders --owner {} --cell-deps {} --at-block {} --export {}",
+            OWNER_ADDR, cell_deps_path, snapshot_block, export_path
+        ));
+        log::info!("SUDT holders at block {}:\n{}", snapshot_block, output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["holder_count"].as_u64().unwrap(), 2);
+        assert_eq!(value["total_amount"].as_str().unwrap(), "1500");
+        assert_eq!(value["supply"]["minted"].as_str().unwrap(), "1500");
+        assert!(value["supply"]["reconciled"].as_bool().unwrap());
+        assert_eq!(
+            value["holders"][0]["address"].as_str().unwrap(),
+            ACCOUNT1_ADDR
+        );
+        assert_eq!(value["holders"][0]["amount"].as_str().unwrap(), "1000");
+        let content = fs::read_to_string(&export_path).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "address,lock_script_hash,amount,cell_count");
+        assert!(lines[1].starts_with(ACCOUNT1_ADDR), "{}", content);
+        assert!(lines[1].ends_with(",1000,1"), "{}", content);
+
+        let output = setup.cli(&format!(
+            "sudt holders --owner {} --cell-deps {}",
+            OWNER_ADDR, cell_deps_path
+        ));
+        log::info!("Current SUDT holders:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["total_amount"].as_str().unwrap(), "1500");
+        assert!(value["supply"]["reconciled"].as_bool().unwrap());
+        assert_eq!(
+            value["holders"][0]["address"].as_str().unwrap(),
+            ACCOUNT2_ADDR
+        );
+        assert_eq!(value["holders"][0]["amount"].as_str().unwrap(), "800");
+        assert_eq!(
+            value["holders"][1]["address"].as_str().unwrap(),
+            ACCOUNT1_ADDR
+        );
+        assert_eq!(value["holders"][1]["amount"].as_str().unwrap(), "700");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "SudtHolders"
+    }
+}
+
+pub struct SudtAirdrop;
+
+impl Spec for SudtAirdrop {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap();
+        let owner_key_path = format!("{}/owner", path);
+        let account1_key_path = format!("{}/account1", path);
+        let cell_deps_path = format!("{}/cell_deps.json", path);
+        let recipients_path = format!("{}/recipients.csv", path);
+        prepare(setup, path);
+
+        let account1_acp_addr = create_acp_cell(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            ACCOUNT1_ADDR,
+            account1_key_path.as_str(),
+        );
+        fs::write(
+            &recipients_path,
+            format!(
+                "address,amount,kind\n{},100\n{},200,cheque\n{},300\n",
+                ACCOUNT1_ADDR, ACCOUNT2_ADDR, account1_acp_addr
+            ),
+        )
+        .unwrap();
+
+        let airdrop_cmd = format!(
+            "sudt airdrop --owner {} --cell-deps {} --file {} --max-recipients 2 --privkey-path {}",
+            OWNER_ADDR, cell_deps_path, recipients_path, owner_key_path
+        );
+        let output = setup.cli(&airdrop_cmd);
+        log::info!("Airdrop SUDT to 3 recipients:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["paid_before"].as_u64().unwrap(), 0);
+        assert_eq!(value["paid_now"].as_u64().unwrap(), 3);
+        assert_eq!(value["transactions"].as_sequence().unwrap().len(), 2);
+        setup.miner().generate_blocks(6);
+
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            ACCOUNT1_ADDR,
+            100,
+        );
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            account1_acp_addr.as_str(),
+            300,
+        );
+        let cheque_addr = setup.cli(&format!(
+            "sudt build-cheque-address --receiver {} --sender {} --cell-deps {}",
+            ACCOUNT2_ADDR, OWNER_ADDR, cell_deps_path
+        ));
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            cheque_addr.as_str(),
+            200,
+        );
+
+        // Resume the finished airdrop, nothing is paid again
+        let output = setup.cli(&airdrop_cmd);
+        log::info!("Resume the finished airdrop:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["paid_before"].as_u64().unwrap(), 3);
+        assert_eq!(value["paid_now"].as_u64().unwrap(), 0);
+        let transactions = value["transactions"].as_sequence().unwrap();
+        assert!(transactions
+            .iter()
+            .all(|tx| tx["status"].as_str() == Some("committed")));
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            ACCOUNT1_ADDR,
+            100,
+        );
+
+        // Interrupt an airdrop: the last recorded transaction is not sent (it
+        // is removed from the tx pool) and the others are still in the tx pool
+        let interrupted_path = format!("{}/interrupted.csv", path);
+        fs::write(
+            &interrupted_path,
+            format!(
+                "{},20\n{},30\n{},40\n",
+                ACCOUNT2_ADDR, account1_acp_addr, OWNER_ADDR
+            ),
+        )
+        .unwrap();
+        let interrupted_cmd = format!(
+            "sudt airdrop --owner {} --cell-deps {} --file {} --max-recipients 1 --privkey-path {}",
+            OWNER_ADDR, cell_deps_path, interrupted_path, owner_key_path
+        );
+        let output = setup.cli(&interrupted_cmd);
+        log::info!("Airdrop SUDT to 3 recipients one by one:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let transactions = value["transactions"].as_sequence().unwrap();
+        assert_eq!(transactions.len(), 3);
+        let unsent_tx_hash = transactions[2]["tx_hash"].as_str().unwrap().to_string();
+        let output = setup.cli(&format!(
+            "rpc remove_transaction --tx-hash {}",
+            unsent_tx_hash
+        ));
+        log::info!("Remove the last airdrop transaction:\n{}", output);
+
+        // Only the recipient of the unsent transaction is paid
+        let output = setup.cli(&interrupted_cmd);
+        log::info!("Resume the interrupted airdrop:\n{}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["paid_before"].as_u64().unwrap(), 2);
+        assert_eq!(value["paid_now"].as_u64().unwrap(), 1);
+        let transactions = value["transactions"].as_sequence().unwrap();
+        assert_eq!(transactions.len(), 3);
+        for tx in &transactions[0..2] {
+            assert!(
+                ["pending", "proposed"].contains(&tx["status"].as_str().unwrap()),
+                "{:?}",
+                tx
+            );
+        }
+        assert_eq!(transactions[2]["status"].as_str().unwrap(), "sent");
+        assert_eq!(transactions[2]["recipients"][0].as_u64().unwrap(), 2);
+        setup.miner().generate_blocks(6);
+
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            ACCOUNT2_ADDR,
+            20,
+        );
+        check_amount(
+            setup,
+            OWNER_ADDR,
+            cell_deps_path.as_str(),
+            account1_acp_addr.as_str(),
+            330,
+        );
+        check_amount(setup, OWNER_ADDR, cell_deps_path.as_str(), OWNER_ADDR, 40);
+        let output = setup.cli(&interrupted_cmd);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["paid_before"].as_u64().unwrap(), 3);
+        assert_eq!(value["paid_now"].as_u64().unwrap(), 0);
+
+        // The journal belongs to the original recipients file
+        fs::write(&recipients_path, format!("{},100\n", ACCOUNT2_ADDR)).unwrap();
+        let output = setup.cli(&airdrop_cmd);
+        assert!(
+            output.contains("The recipients file is changed"),
+            "{}",
+            output
+        );
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "SudtAirdrop"
+    }
+}
@@ -2,9 +2,16 @@ use crate::miner::Miner;
 use crate::setup::Setup;
 use crate::spec::Spec;
 use ckb_chain_spec::consensus::TYPE_ID_CODE_HASH;
-use ckb_sdk::{constants::ONE_CKB, HumanCapacity};
+use ckb_sdk::{
+    constants::{MULTISIG_TYPE_HASH, ONE_CKB},
+    unlock::MultisigConfig,
+    Address, AddressPayload, HumanCapacity, NetworkType, Since, SinceType,
+};
+use ckb_types::{bytes::Bytes, core::ScriptHashType, prelude::*, H160};
 use tempfile::tempdir;
 
+use std::io::Write;
+use std::process::{Command, Stdio};
 use std::{fs, str::FromStr, thread, time::Duration};
 
 // Random private key just for tests
@@ -15,7 +22,7 @@ pub const ACCOUNT2_PRIVKEY: &str =
     "0x11e86559b6d71abcf9fe2d6dd4f5e6d2fb8a1ef79db0d4b535244ceb13add189";
 pub const ACCOUNT2_ADDRESS: &str = "ckt1qyq2em03yml8thgy6wthjfvfgepds9e63pxs0zc6k7";
 
-fn get_capacity(setup: &mut Setup, address: &str, target: &str) -> String {
+pub(crate) fn get_capacity(setup: &mut Setup, address: &str, target: &str) -> String {
     let cli = format!("wallet get-capacity --address {}", address);
     let mut cnt = 0;
     loop {
@@ -271,3 +278,458 @@ impl Spec for WalletTimelockedAddress {
         "WalletTimelockedAddress"
     }
 }
+
+pub struct WalletBatchTransfer;
+
+impl Spec for WalletBatchTransfer {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let batch_file = format!("{}/targets.csv", path);
+        fs::write(
+            &batch_file,
+            format!(
+                "address,capacity,data\n{},1000,\n{},2000,0x1234\n",
+                ACCOUNT1_ADDRESS, ACCOUNT2_ADDRESS
+            ),
+        )
+        .unwrap();
+
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        setup.miner().generate_blocks(30);
+
+        let output = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --batch-file {}",
+            miner_privkey, batch_file,
+        ));
+        log::info!("batch transfer from miner: {}", output);
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let transactions = value["transactions"].as_sequence().unwrap();
+        assert_eq!(transactions.len(), 1);
+        let tx_hash = transactions[0]["tx_hash"].as_str().unwrap().to_string();
+        let targets = value["targets"].as_sequence().unwrap();
+        assert_eq!(targets.len(), 2);
+        for target in targets {
+            assert_eq!(target["tx_hash"].as_str().unwrap(), tx_hash);
+        }
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        let output = get_capacity(setup, ACCOUNT1_ADDRESS, "total: 1000.0 (CKB)");
+        assert_eq!(output, "total: 1000.0 (CKB)");
+        let output = get_capacity(setup, ACCOUNT2_ADDRESS, "total: 2000.0 (CKB)");
+        assert_eq!(output, "total: 2000.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletBatchTransfer"
+    }
+}
+
+pub struct WalletBuildOnly;
+
+impl Spec for WalletBuildOnly {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let tx_file = format!("{}/unsigned.json", path);
+        let signed_tx_file = format!("{}/signed.json", path);
+
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        let miner_address = Miner::address();
+        setup.miner().generate_blocks(30);
+
+        let tx_hash = setup.cli(&format!(
+            "wallet transfer --from-account {} --to-address {} --capacity 1000 --build-only --output-file {}",
+            miner_address, ACCOUNT1_ADDRESS, tx_file,
+        ));
+        log::info!("build unsigned transaction: {}", tx_hash);
+        assert!(tx_hash.starts_with("0x"));
+
+        let output = setup.cli(&format!(
+            "tx sign-file --privkey-path {} --tx-file {} --output-file {}",
+            miner_privkey, tx_file, signed_tx_file,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["tx-hash"].as_str().unwrap(), tx_hash);
+        assert_eq!(value["still-locked-groups"].as_u64().unwrap(), 0);
+
+        let sent_tx_hash = setup.cli(&format!("tx broadcast --tx-file {}", signed_tx_file));
+        assert_eq!(sent_tx_hash, tx_hash);
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+        let output = get_capacity(setup, ACCOUNT1_ADDRESS, "total: 1000.0 (CKB)");
+        assert_eq!(output, "total: 1000.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletBuildOnly"
+    }
+}
+
+pub struct WalletBumpFee;
+
+impl Spec for WalletBumpFee {
+    fn run(&self, setup: &mut Setup) {
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        setup.miner().generate_blocks(30);
+
+        let old_tx_hash = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 1000 --fee-rate 1000",
+            miner_privkey, ACCOUNT2_ADDRESS,
+        ));
+        log::info!("transfer tx: {}", old_tx_hash);
+
+        // The default min_rbf_rate of the tx pool is 1500
+        let output = setup.cli(&format!(
+            "wallet bump-fee --privkey-path {} --tx-hash {} --fee-rate 1200",
+            miner_privkey, old_tx_hash,
+        ));
+        assert!(
+            output.contains("is less than the min_rbf_rate"),
+            "{}",
+            output
+        );
+        // The new fee must cover the old fee and the RBF fee of its own size
+        let output = setup.cli(&format!(
+            "wallet bump-fee --privkey-path {} --tx-hash {} --fee-rate 2000",
+            miner_privkey, old_tx_hash,
+        ));
+        assert!(output.contains("minimal replace fee"), "{}", output);
+
+        let output = setup.cli(&format!(
+            "wallet bump-fee --privkey-path {} --tx-hash {} --fee-rate 5000",
+            miner_privkey, old_tx_hash,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["old_tx_hash"].as_str().unwrap(), old_tx_hash);
+        let tx_hash = value["tx_hash"].as_str().unwrap().to_string();
+        assert_ne!(tx_hash, old_tx_hash);
+        let parse_fee = |key: &str| {
+            let fee = value[key].as_str().unwrap().trim_end_matches(" (CKB)");
+            HumanCapacity::from_str(fee).unwrap().0
+        };
+        assert!(parse_fee("new_tx_fee") > parse_fee("old_tx_fee"));
+
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+        let output = get_capacity(setup, ACCOUNT2_ADDRESS, "total: 1000.0 (CKB)");
+        assert_eq!(output, "total: 1000.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletBumpFee"
+    }
+}
+
+pub struct WalletConsolidate;
+
+impl Spec for WalletConsolidate {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let account1_privkey = format!("{}/account1", path);
+        fs::write(&account1_privkey, ACCOUNT1_PRIVKEY).unwrap();
+
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        setup.miner().generate_blocks(30);
+
+        for _ in 0..3 {
+            let tx_hash = setup.cli(&format!(
+                "wallet transfer --privkey-path {} --to-address {} --capacity 100 --fee-rate 1000",
+                miner_privkey, ACCOUNT1_ADDRESS,
+            ));
+            setup.miner().mine_until_transaction_confirm(&tx_hash);
+        }
+        get_capacity(setup, ACCOUNT1_ADDRESS, "total: 300.0 (CKB)");
+
+        let output = setup.cli(&format!(
+            "wallet consolidate --privkey-path {} --min-cell-capacity 1000 --dry-run",
+            account1_privkey,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(value["dry_run"].as_bool().unwrap());
+        assert_eq!(value["total_inputs"].as_u64().unwrap(), 3);
+        let output = setup.cli(&format!(
+            "wallet get-live-cells --address {}",
+            ACCOUNT1_ADDRESS
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["live_cells"].as_sequence().unwrap().len(), 3);
+
+        let output = setup.cli(&format!(
+            "wallet consolidate --privkey-path {} --min-cell-capacity 1000",
+            account1_privkey,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let transactions = value["transactions"].as_sequence().unwrap();
+        assert_eq!(transactions.len(), 1);
+        let tx_hash = transactions[0]["tx_hash"].as_str().unwrap();
+        setup.miner().mine_until_transaction_confirm(tx_hash);
+
+        let output = setup.cli(&format!(
+            "wallet get-live-cells --address {}",
+            ACCOUNT1_ADDRESS
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["live_cells"].as_sequence().unwrap().len(), 1);
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletConsolidate"
+    }
+}
+
+pub struct WalletHistory;
+
+impl Spec for WalletHistory {
+    fn run(&self, setup: &mut Setup) {
+        let tempdir = tempdir().expect("create tempdir failed");
+        let path = tempdir.path().to_str().unwrap().to_owned();
+        let csv_path = format!("{}/history.csv", path);
+
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        let miner_address = Miner::address();
+        setup.miner().generate_blocks(30);
+
+        let tx_hash = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 1000 --fee-rate 1000",
+            miner_privkey, ACCOUNT2_ADDRESS,
+        ));
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        let output = setup.cli(&format!(
+            "wallet history --address {} --export {}",
+            ACCOUNT2_ADDRESS, csv_path
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(!value["more"].as_bool().unwrap());
+        assert_eq!(value["transactions"].as_sequence().unwrap().len(), 1);
+        let item = &value["transactions"][0];
+        assert_eq!(item["tx_hash"].as_str().unwrap(), tx_hash);
+        assert_eq!(item["capacity_change"].as_str().unwrap(), "+1000.0");
+        let miner_full_address = Address::new(
+            miner_address.network(),
+            miner_address.payload().clone(),
+            true,
+        );
+        assert_eq!(
+            item["counterparties"][0].as_str().unwrap(),
+            miner_full_address.to_string()
+        );
+        let content = fs::read_to_string(&csv_path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.lines().nth(1).unwrap().starts_with(&tx_hash));
+
+        let output = setup.cli(&format!(
+            "wallet history --address {} --page 1 --limit 1",
+            ACCOUNT2_ADDRESS
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(!value["more"].as_bool().unwrap());
+        assert!(value["transactions"].as_sequence().unwrap().is_empty());
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletHistory"
+    }
+}
+
+pub struct WalletLockUntil;
+
+impl Spec for WalletLockUntil {
+    fn run(&self, setup: &mut Setup) {
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        setup.miner().generate_blocks(30);
+
+        // A passed block number
+        let output = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 500 --lock-until block:1",
+            miner_privkey, ACCOUNT2_ADDRESS,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let tx_hash = value["tx_hash"].as_str().unwrap().to_string();
+        assert!(value["lock_address"].as_str().unwrap().starts_with("ckt1"));
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        // A far future epoch
+        let output = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 300 --lock-until epoch:100000",
+            miner_privkey, ACCOUNT2_ADDRESS,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let tx_hash = value["tx_hash"].as_str().unwrap().to_string();
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        // The relative since is based on the block the cell is committed in
+        let account2 = Address::from_str(ACCOUNT2_ADDRESS).unwrap();
+        let account2_lock_arg = H160::from_slice(account2.payload().args().as_ref()).unwrap();
+        let config = MultisigConfig::new_with(vec![account2_lock_arg], 0, 1).unwrap();
+        for (blocks, capacity) in [(2, 200), (100000, 100)] {
+            let since = Since::new(SinceType::BlockNumber, blocks, true).value();
+            let mut args = config.hash160().as_bytes().to_vec();
+            args.extend_from_slice(&since.to_le_bytes());
+            let payload = AddressPayload::new_full(
+                ScriptHashType::Type,
+                MULTISIG_TYPE_HASH.pack(),
+                Bytes::from(args),
+            );
+            let address = Address::new(NetworkType::Testnet, payload, true);
+            let tx_hash = setup.cli(&format!(
+                "wallet transfer --privkey-path {} --to-address {} --capacity {} --skip-check-to-address",
+                miner_privkey, address, capacity,
+            ));
+            setup.miner().mine_until_transaction_confirm(&tx_hash);
+        }
+        setup.miner().generate_blocks(3);
+
+        let output = setup.cli(&format!(
+            "wallet get-capacity --address {}",
+            ACCOUNT2_ADDRESS
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(value["total"].as_str().unwrap(), "0.0 (CKB)");
+        assert_eq!(value["timelock"]["locked"].as_str().unwrap(), "400.0 (CKB)");
+        assert_eq!(
+            value["timelock"]["unlocked"].as_str().unwrap(),
+            "700.0 (CKB)"
+        );
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletLockUntil"
+    }
+}
+
+pub struct WalletExternalSigner;
+
+impl Spec for WalletExternalSigner {
+    fn run(&self, setup: &mut Setup) {
+        let signer_bin = setup.mock_signer_bin.clone();
+
+        // Import the ckb root of the external signer for deriving addresses
+        let mut child = Command::new(&signer_bin)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("start mock signer");
+        child
+            .stdin
+            .as_mut()
+            .unwrap()
+            .write_all(
+                b"{\"id\":0,\"method\":\"get_xpub\",\"params\":{\"path\":\"m/44'/309'/0'\"}}\n",
+            )
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        let response: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let xpub = response["result"]["xpub"].as_str().unwrap().to_string();
+        let output = setup.cli(&format!("account import-watch-only --xpub {}", xpub));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let account = value["lock_arg"].as_str().unwrap().to_string();
+
+        let output = setup.cli(&format!(
+            "account bip44-addresses --lock-arg {} --network testnet --receiving-length 1 --change-length 1",
+            account
+        ));
+        let addresses: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let receiving_address = addresses["receiving"][0]["address"].as_str().unwrap();
+        let change_address = addresses["change"][0]["address"].as_str().unwrap();
+
+        setup.miner().generate_blocks(30);
+        let tx_hash = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 2000 --skip-check-to-address",
+            setup.miner().privkey_path(),
+            receiving_address,
+        ));
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+
+        // No password required, signed by the external signer
+        let tx_hash = setup.cli(&format!(
+            "wallet transfer --from-account {} --signer-cmd {} --to-address {} --capacity 100 --derive-change-address {}",
+            account, signer_bin, ACCOUNT2_ADDRESS, change_address,
+        ));
+        assert!(tx_hash.starts_with("0x"), "{}", tx_hash);
+        setup.miner().mine_until_transaction_confirm(&tx_hash);
+        get_capacity(setup, ACCOUNT2_ADDRESS, "total: 100.0 (CKB)");
+
+        // The signer can not sign for an account not derived from its key
+        let output = setup.cli(&format!(
+            "wallet transfer --from-account {} --signer-cmd {} --to-address {} --capacity 100",
+            ACCOUNT1_ADDRESS, signer_bin, ACCOUNT2_ADDRESS,
+        ));
+        assert!(output.contains("can not sign for lock arg"), "{}", output);
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletExternalSigner"
+    }
+}
+
+pub struct WalletDryRun;
+
+impl Spec for WalletDryRun {
+    fn run(&self, setup: &mut Setup) {
+        let miner_privkey = setup.miner().privkey_path().to_string();
+        setup.miner().generate_blocks(30);
+
+        let output = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --to-address {} --capacity 20000 --fee-rate 1000 --dry-run",
+            miner_privkey, ACCOUNT1_ADDRESS,
+        ));
+        let value: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert!(value["tx_hash"].as_str().unwrap().starts_with("0x"));
+        assert!(value["size"].as_u64().unwrap() > 0);
+        assert!(value["fee_rate"].as_u64().unwrap() >= 1000);
+        assert_eq!(value["outputs"]["count"].as_u64().unwrap(), 2);
+        assert_eq!(value["change"]["index"].as_u64().unwrap(), 1);
+        let miner_address = Miner::address();
+        let miner_full_address = Address::new(
+            miner_address.network(),
+            miner_address.payload().clone(),
+            true,
+        );
+        assert_eq!(
+            value["change"]["address"].as_str().unwrap(),
+            miner_full_address.to_string()
+        );
+        // The transaction is not signed, only the lock script fails
+        assert!(!value["test_tx_pool_accept"]["accepted"].as_bool().unwrap());
+        assert!(value["estimate_cycles"]["error"].is_string());
+        let groups = value["script_groups"].as_sequence().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0]["group_type"].as_str().unwrap(), "lock");
+        assert!(groups[0]["error"].is_string());
+        assert!(groups[0]["expected_error"].as_bool().unwrap());
+
+        // Batch transfer reports every transaction
+        let tempdir = tempdir().expect("create tempdir failed");
+        let batch_file = tempdir.path().join("targets.csv");
+        fs::write(
+            &batch_file,
+            format!(
+                "address,capacity\n{},1000\n{},2000\n",
+                ACCOUNT1_ADDRESS, ACCOUNT2_ADDRESS
+            ),
+        )
+        .unwrap();
+        let output = setup.cli(&format!(
+            "wallet transfer --privkey-path {} --batch-file {} --dry-run",
+            miner_privkey,
+            batch_file.to_str().unwrap(),
+        ));
+        let reports: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        let reports = reports.as_sequence().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["outputs"]["count"].as_u64().unwrap(), 3);
+
+        // Nothing is sent
+        setup.miner().generate_blocks(3);
+        let output = setup.cli(&format!(
+            "wallet get-capacity --address {}",
+            ACCOUNT1_ADDRESS
+        ));
+        assert_eq!(output, "total: 0.0 (CKB)");
+    }
+
+    fn spec_name(&self) -> &'static str {
+        "WalletDryRun"
+    }
+}